@@ -294,6 +294,26 @@ pub enum ErrorCode {
     /// Oracle price manipulation detected
     #[msg("Oracle price manipulation detected")]
     OracleManipulationDetected,
+
+    /// Referral chain would contain a cycle
+    #[msg("Referral chain would contain a cycle")]
+    ReferralCycleDetected,
+
+    /// Referral code has an invalid length or characters
+    #[msg("Invalid referral code")]
+    InvalidReferralCode,
+
+    /// Referral code has been revoked
+    #[msg("Referral code has been revoked")]
+    ReferralCodeRevoked,
+
+    /// Transaction executed after its expiry timestamp
+    #[msg("Transaction has expired")]
+    TransactionExpired,
+
+    /// User has already bought their full presale allocation
+    #[msg("Presale allocation exceeded")]
+    PresaleAllocationExceeded,
 }
 
 /// Macro for printing error information with file and line details
@@ -0,0 +1,195 @@
+//! Instruction: Accept Agent Rental
+//!
+//! Lets the renter named in a rental offer accept it and pay the upfront price to the owner.
+//! The yield the agent earned up to the acceptance is settled to the owner first. From the later
+//! of the term start and the acceptance until the term ends, the agent's yield is credited to
+//! the renter's `User` account.
+//!
+//! Accounts:
+//! - authority: The renter (signer)
+//! - user: User account PDA of the renter
+//! - owner_user: User account PDA of the owner (credited the yield earned before acceptance)
+//! - agent: Agent account PDA (the rented agent)
+//! - master_agent: Master agent account PDA (parent/master of the agent)
+//! - rental: Rental PDA of the agent
+//! - renter_y_mint_ta: Renter's Y-mint token account (payer of the price)
+//! - owner_y_mint_ta: Owner's Y-mint token account (receiver of the price)
+//! - y_mint: Y-mint SPL token mint (payment token for protocol)
+//! - t_yield: Protocol global state/config PDA
+//! - token_program: SPL token program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{AccrueYieldEvent, Agent, AgentRental, MasterAgent, RentalAcceptedEvent, TYield, User},
+    try_from,
+};
+
+#[derive(Accounts)]
+pub struct AcceptAgentRental<'info> {
+    /// The renter. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// User account PDA of the renter.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// User account PDA of the owner.
+    /// PDA: ["user", rental.owner]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), rental.owner.as_ref()],
+        bump = owner_user.bump
+    )]
+    pub owner_user: Box<Account<'info, User>>,
+
+    /// Agent account PDA (the rented agent).
+    /// PDA: ["agent", agent.mint]
+    #[account(
+        mut,
+        seeds = [b"agent".as_ref(), agent.mint.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Master agent account PDA (parent/master of the agent).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+        constraint = agent.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Rental PDA of the agent.
+    /// PDA: ["agent_rental", agent]
+    #[account(
+        mut,
+        seeds = [b"agent_rental".as_ref(), agent.key().as_ref()],
+        bump = rental.bump,
+        constraint = rental.renter == authority.key() @ ErrorCode::InvalidAuthority,
+    )]
+    pub rental: Box<Account<'info, AgentRental>>,
+
+    /// Renter's Y-mint token account (payer of the price).
+    #[account(
+        mut,
+        constraint = renter_y_mint_ta.mint == t_yield.y_mint,
+        constraint = renter_y_mint_ta.owner == authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub renter_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Owner's Y-mint token account (receiver of the price).
+    #[account(
+        mut,
+        constraint = owner_y_mint_ta.mint == t_yield.y_mint,
+        constraint = owner_y_mint_ta.owner == rental.owner @ ErrorCode::InvalidAccount,
+    )]
+    pub owner_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Y-mint SPL token mint (payment token for protocol).
+    #[account(address = t_yield.y_mint)]
+    pub y_mint: AccountInfo<'info>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Accept Agent Rental
+///
+/// Settles the owner's yield up to now, pays the rental price to the owner and starts the
+/// rental term.
+pub fn accept_agent_rental<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcceptAgentRental<'info>>,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let user = ctx.accounts.user.as_mut();
+    let owner_user = ctx.accounts.owner_user.as_mut();
+    let agent = ctx.accounts.agent.as_mut();
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let rental = ctx.accounts.rental.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+
+    if !user.can_perform_actions() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    // The owner cannot change while the rental is open
+    if !agent.is_owned_by(&rental.owner) {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    // Yield earned before the acceptance belongs to the owner
+    let (owner_amount, _) = rental.settle_yield(agent, master_agent, current_time)?;
+    if owner_amount > 0 {
+        owner_user.add_unclaimed_yield(owner_amount)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(owner_amount)?;
+
+        emit_cpi!(AccrueYieldEvent {
+            agent: agent.key(),
+            owner: agent.owner,
+            master_agent: agent.master_agent,
+            amount: owner_amount,
+            timestamp: current_time,
+        });
+    }
+
+    rental.accept(current_time)?;
+
+    if rental.price > 0 {
+        let mint = try_from!(Account<Mint>, ctx.accounts.y_mint)
+            .map_err(|_| ErrorCode::AccountFromError)?;
+
+        TYield::transfer_tokens(
+            ctx.accounts.renter_y_mint_ta.to_account_info(),
+            mint.to_account_info(),
+            ctx.accounts.owner_y_mint_ta.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            rental.price,
+            mint.decimals,
+        )
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+    }
+
+    user.update_last_activity(current_time)?;
+
+    emit_cpi!(RentalAcceptedEvent {
+        agent: agent.key(),
+        owner: rental.owner,
+        renter: rental.renter,
+        price: rental.price,
+        start_time: rental.start_time,
+        end_time: rental.end_time,
+    });
+
+    Ok(0)
+}
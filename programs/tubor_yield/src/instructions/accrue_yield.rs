@@ -0,0 +1,204 @@
+//! Instruction: Accrue Yield
+//!
+//! Credits the yield earned by an agent since its last checkpoint to the agent owner's `User` account.
+//! Can be called by anyone. Yield is computed from the master agent's `w_yield`, the time held and the agent booster.
+//! While the agent's yield is rented out, yield settled during the rental term is credited to the renter instead.
+//! A keeper reward is paid only when the caller accrues yield for someone else and at least
+//! `KEEPER_ACCRUAL_INTERVAL` has passed since the agent's last accrual, so an agent earns the keeper at most
+//! one reward per interval however often it is cranked. The reward is `keeper_reward` capped at
+//! `KEEPER_ACCRUAL_REWARD_SHARE` of the yield accrued, so it scales with the work done and cranking
+//! agents that earn little pays little.
+//!
+//! Accounts:
+//! - authority: The caller (anyone can call)
+//! - agent: Agent account PDA whose yield is settled (mut)
+//! - master_agent: Master agent account PDA the agent belongs to (mut, yield index checkpoint)
+//! - user: User account PDA of the agent owner (mut)
+//! - rental: Rental PDA of the agent (optional, required while the agent is rented)
+//! - renter_user: User account PDA of the renter (optional, required while the agent is rented)
+//! - keeper_user: User account PDA of the caller (optional, receives the keeper reward when yield is accrued for others)
+//! - t_yield: Protocol global state/config PDA (mut, tracks yield owed to users)
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::{SafeMath, KEEPER_ACCRUAL_INTERVAL},
+    state::{
+        AccrueYieldEvent, Agent, AgentRental, KeeperAction, KeeperRewardEvent, MasterAgent,
+        RentalYieldEvent, TYield, User,
+    },
+};
+
+#[derive(Accounts)]
+pub struct AccrueYield<'info> {
+    /// The caller (anyone can call this instruction).
+    pub authority: Signer<'info>,
+
+    /// Agent account PDA whose yield is settled.
+    /// PDA: ["agent", agent.mint]
+    #[account(
+        mut,
+        seeds = [b"agent".as_ref(), agent.mint.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Master agent account PDA the agent belongs to.
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+        constraint = agent.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// User account PDA of the agent owner (receives the accrued yield).
+    /// PDA: ["user", agent.owner]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), agent.owner.as_ref()],
+        bump = user.bump,
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Rental of the agent's yield. Required while the agent is rented.
+    /// PDA: ["agent_rental", agent]
+    #[account(
+        mut,
+        seeds = [b"agent_rental".as_ref(), agent.key().as_ref()],
+        bump = rental.bump,
+    )]
+    pub rental: Option<Box<Account<'info, AgentRental>>>,
+
+    /// User account PDA of the renter (receives the yield earned during the rental term).
+    /// PDA: ["user", rental.renter]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), renter_user.authority.as_ref()],
+        bump = renter_user.bump,
+    )]
+    pub renter_user: Option<Box<Account<'info, User>>>,
+
+    /// User account PDA of the caller. Receives the keeper reward when provided.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = keeper_user.bump,
+    )]
+    pub keeper_user: Option<Box<Account<'info, User>>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Accrue Yield
+///
+/// Settles the agent's yield up to the current time and credits it to the owner's unclaimed yield,
+/// or to the renter's for the part earned during a rental term.
+/// Returns the amount credited (0 if nothing has accrued yet).
+pub fn accrue_yield<'info>(
+    ctx: Context<'_, '_, '_, 'info, AccrueYield<'info>>,
+) -> TYieldResult<u64> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let agent = ctx.accounts.agent.as_mut();
+    let user = ctx.accounts.user.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+
+    if user.authority != agent.owner {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    let last_accrual = agent.last_yield_accrual;
+
+    let (amount, renter_amount) = if agent.is_rented {
+        let rental = ctx
+            .accounts
+            .rental
+            .as_mut()
+            .ok_or(ErrorCode::CannotPerformAction)?;
+        let (amount, renter_amount) = rental.settle_yield(agent, master_agent, current_time)?;
+        if renter_amount > 0 {
+            let renter_user = ctx
+                .accounts
+                .renter_user
+                .as_mut()
+                .ok_or(ErrorCode::CannotPerformAction)?;
+            if renter_user.authority != rental.renter {
+                return Err(ErrorCode::CannotPerformAction);
+            }
+            renter_user.add_unclaimed_yield(renter_amount)?;
+            t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(renter_amount)?;
+
+            emit_cpi!(RentalYieldEvent {
+                agent: agent.key(),
+                renter: rental.renter,
+                amount: renter_amount,
+                timestamp: current_time,
+            });
+        }
+        (amount, renter_amount)
+    } else {
+        (agent.settle_yield(master_agent, current_time)?, 0)
+    };
+    if amount > 0 {
+        user.add_unclaimed_yield(amount)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(amount)?;
+    }
+
+    emit_cpi!(AccrueYieldEvent {
+        agent: agent.key(),
+        owner: agent.owner,
+        master_agent: agent.master_agent,
+        amount,
+        timestamp: current_time,
+    });
+
+    // Reward the keeper only for accruals that credited yield to someone else, once per interval
+    let accrued = amount.safe_add(renter_amount)?;
+    let interval_elapsed = current_time >= last_accrual.safe_add(KEEPER_ACCRUAL_INTERVAL)?;
+    let keeper = ctx.accounts.authority.key();
+    let is_beneficiary = keeper == agent.owner
+        || ctx
+            .accounts
+            .rental
+            .as_ref()
+            .is_some_and(|rental| agent.is_rented && rental.renter == keeper);
+    if let Some(keeper_user) = ctx
+        .accounts
+        .keeper_user
+        .as_mut()
+        .filter(|_| accrued > 0 && interval_elapsed && !is_beneficiary)
+    {
+        let reward = t_yield.reward_accrual_keeper(keeper_user, accrued, current_time)?;
+        if reward > 0 {
+            emit_cpi!(KeeperRewardEvent {
+                keeper: keeper_user.authority,
+                action: KeeperAction::YieldAccrual,
+                actions: 1,
+                amount: reward,
+                timestamp: current_time,
+            });
+        }
+    }
+
+    Ok(accrued)
+}
@@ -0,0 +1,294 @@
+//! Instruction: Bid Auction
+//!
+//! Buys an agent NFT from a master agent at the current Dutch auction price. The buyer pays the
+//! current price plus tax and is refunded down to the clearing price once the auction is settled.
+//! Enforces the same whitelist, ban, and protocol constraints as `buy_agent`.
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{
+        Agent, AgentAuction, AuctionBid, AuctionBidEvent, MasterAgent, Size, TYield,
+        TransferAgentParams, User,
+    },
+    try_from,
+};
+
+/// Parameters for bidding in a Dutch auction.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BidAuctionParams {
+    /// Merkle proof of the bidder's wallet, required when the master agent has a whitelist root
+    pub whitelist_proof: Vec<[u8; 32]>,
+}
+
+/// Accounts required for buying an agent NFT through a Dutch auction.
+#[derive(Accounts)]
+pub struct BidAuction<'info> {
+    /// The user purchasing the agent. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Instructions sysvar (required for Metaplex CPI).
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+
+    /// CHECK: Metaplex token metadata program.
+    #[account(address = METADATA_PROGRAM_ID)]
+    pub metadata_program: AccountInfo<'info>,
+
+    /// CHECK: Metadata account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    // --- Protocol/User/Agent State ---
+    /// User account PDA.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Agent account PDA (the agent being purchased).
+    /// PDA: ["agent", mint]
+    #[account(
+        mut,
+        seeds = [b"agent".as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Master agent account PDA (the parent/master of the agent).
+    /// PDA: ["master_agent", master_agent_mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent_mint.key().as_ref()],
+        bump = master_agent.bump,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Auction PDA of the master agent.
+    /// PDA: ["auction", master_agent]
+    #[account(
+        mut,
+        seeds = [b"auction".as_ref(), master_agent.key().as_ref()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, AgentAuction>>,
+
+    /// Bid receipt of the buyer, refunded down to the clearing price on settlement.
+    /// PDA: ["auction_bid", auction, authority]
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = AuctionBid::SIZE,
+        seeds = [b"auction_bid".as_ref(), auction.key().as_ref(), authority.key().as_ref()],
+        bump,
+    )]
+    pub bid: Box<Account<'info, AuctionBid>>,
+
+    /// Mint account for the agent NFT being purchased.
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// Mint account for the master agent NFT.
+    pub master_agent_mint: Box<Account<'info, Mint>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// CHECK: Transfer authority PDA (protocol authority for token/NFT transfers).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    // --- Token Accounts ---
+    /// Protocol's token account holding the agent NFT (source for transfer).
+    /// Must have mint == agent mint.
+    #[account(
+        mut,
+        constraint = transfer_authority_ta.mint == mint.key()
+    )]
+    pub transfer_authority_ta: Box<Account<'info, TokenAccount>>,
+
+    /// User's token account to receive the agent NFT.
+    /// Must have mint == agent mint.
+    #[account(
+        mut,
+        constraint = user_agent_ta.mint == mint.key()
+    )]
+    pub user_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// User's Y-mint token account (payer for the purchase).
+    /// Must have mint == t_yield.y_mint.
+    #[account(
+        mut,
+        constraint = user_y_mint_ta.mint == t_yield.y_mint
+    )]
+    pub user_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol's Y-mint token account (receiver of payment).
+    /// Must have mint == t_yield.y_mint.
+    #[account(
+        mut,
+        constraint = transfer_authority_y_mint_ta.mint == t_yield.y_mint
+    )]
+    pub transfer_authority_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Y-mint SPL token mint (payment token for protocol).
+    #[account(address = t_yield.y_mint)]
+    pub y_mint: AccountInfo<'info>,
+
+    // --- Misc ---
+    /// CHECK: Event authority for CPI event logs (used for event emission; not written to).
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Bid Auction
+///
+/// Transfers an agent NFT from protocol to user at the current auction price and records the bid.
+/// The whole payment stays reserved in the protocol until the bid is refunded at the clearing price.
+pub fn bid_auction<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BidAuction<'info>>,
+    params: BidAuctionParams,
+) -> TYieldResult<()> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let user = ctx.accounts.user.as_mut();
+    let agents = ctx.accounts.agent.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+    let auction = ctx.accounts.auction.as_mut();
+    let bid = ctx.accounts.bid.as_mut();
+
+    // --- Access control checks ---
+    if !user.can_perform_actions() || !master_agent.auction_live {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if master_agent.is_whitelist_mode()
+        && !master_agent.is_wallet_whitelisted(
+            &ctx.accounts.authority.key(),
+            &params.whitelist_proof,
+            user.is_whitelisted(),
+        )
+    {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if !agents.belongs_to_master_agent(&master_agent.key()) {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    // --- Record the bid at the current auction price ---
+    if bid.quantity == 0 {
+        bid.auction = auction.key();
+        bid.buyer = ctx.accounts.authority.key();
+        bid.bump = ctx.bumps.bid;
+    }
+    let price = auction.record_bid(bid, current_time)?;
+
+    // --- Payment transfer (Y-mint) ---
+    let mint =
+        try_from!(Account<Mint>, ctx.accounts.y_mint).map_err(|_| ErrorCode::AccountFromError)?;
+    TYield::transfer_tokens(
+        ctx.accounts.user_y_mint_ta.to_account_info(),
+        mint.to_account_info(),
+        ctx.accounts.transfer_authority_y_mint_ta.to_account_info(),
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        price.0,
+        mint.decimals,
+    )
+    .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    // --- Remove agent from master agent ---
+    // Only the floor price is guaranteed to back the agent; the rest is settled with the clearing price.
+    master_agent.remove_agent(current_time)?;
+    master_agent.record_agent_purchase(auction.floor_price)?;
+
+    // --- Update protocol state ---
+    // The full payment is reserved until the bid is refunded and the tax is known.
+    t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_add(price.0)?;
+
+    // --- Transfer agent NFT to user ---
+    let transfer_agent_params = TransferAgentParams {
+        payer: ctx.accounts.authority.to_account_info(),
+        sender_nft_token_account: ctx.accounts.transfer_authority_ta.to_account_info(),
+        authority: ctx.accounts.transfer_authority.to_account_info(),
+        receiver_token_account: ctx.accounts.user_agent_ta.to_account_info(),
+        receiver: ctx.accounts.authority.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+        metadata_program: ctx.accounts.metadata_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+    };
+    t_yield
+        .transfer_agent(transfer_agent_params)
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    // --- Update user and agent state ---
+    user.add_agent(auction.floor_price)?;
+    agents.transfer_ownership(ctx.accounts.authority.key(), current_time)?;
+    // Agents returned under a relist cooldown become buyable once it elapses
+    agents.release_cooldown(current_time);
+    agents.unlist(current_time)?;
+    // Yield accrues to the buyer from the moment of purchase
+    agents.reset_yield_accrual(master_agent, current_time)?;
+    user.history.add_agents_purchased(auction.floor_price)?;
+    user.validate_user()?;
+
+    master_agent.increment_trade_count(current_time)?;
+    master_agent.validate_security(current_time)?;
+
+    // --- Emit event ---
+    emit_cpi!(AuctionBidEvent {
+        master_agent: master_agent.key(),
+        agent: agents.key(),
+        buyer: agents.owner,
+        price: price.2,
+        tax_amount: price.1,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
@@ -2,6 +2,8 @@
 //!
 //! Allows a user to purchase an agent NFT from a master agent, transferring ownership and updating protocol/user state.
 //! Handles tax, price calculation, and all token/NFT transfers. Enforces whitelist, ban, and protocol constraints.
+//! The buyer bounds the fill with a maximum total price and an expiry timestamp. In whitelist mode,
+//! buyers of a master agent with a whitelist root prove their wallet with a Merkle proof.
 
 use anchor_lang::prelude::*;
 use anchor_spl::{
@@ -14,10 +16,24 @@ use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
 use crate::{
     error::{ErrorCode, TYieldResult},
     math::SafeMath,
-    state::{Agent, BuyAgentEvent, MasterAgent, TYield, TransferAgentParams, User},
+    state::{
+        Agent, BuyAgentEvent, MasterAgent, ReferralEarningEvent, ReferralLink, ReferralRegistry,
+        TYield, TransferAgentParams, User,
+    },
     try_from,
 };
 
+/// Parameters for buying an agent.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BuyAgentParams {
+    /// Maximum total price (including tax) the buyer accepts
+    pub max_total_price: u64,
+    /// Unix timestamp after which the purchase is rejected
+    pub expiry: i64,
+    /// Merkle proof of the buyer's wallet, required when the master agent has a whitelist root
+    pub whitelist_proof: Vec<[u8; 32]>,
+}
+
 /// Accounts required for buying an agent NFT from a master agent.
 ///
 /// This instruction:
@@ -71,6 +87,26 @@ pub struct BuyAgent<'info> {
     )]
     pub user: Box<Account<'info, User>>,
 
+    /// Referral registry of the buyer's direct referrer. Required when the buyer has a
+    /// referrer, who is credited `ref_earn_percentage` of the tax.
+    /// PDA: ["referral_registry", user.referrer]
+    #[account(
+        mut,
+        seeds = [b"referral_registry".as_ref(), user.referrer.as_ref()],
+        bump = referral_registry.bump
+    )]
+    pub referral_registry: Option<Box<Account<'info, ReferralRegistry>>>,
+
+    /// Referral link between the buyer and their referrer. Required when the buyer has a
+    /// referrer. The registries of all higher referral levels are read from `remaining_accounts`
+    /// (nearest first) and credited `ref_tier_percentages` of the tax.
+    /// PDA: ["referral_link", user.referrer, authority]
+    #[account(
+        seeds = [b"referral_link".as_ref(), user.referrer.as_ref(), authority.key().as_ref()],
+        bump = referral_link.bump
+    )]
+    pub referral_link: Option<Box<Account<'info, ReferralLink>>>,
+
     /// Agent account PDA (the agent being purchased).
     /// PDA: ["agent", mint]
     #[account(
@@ -98,6 +134,7 @@ pub struct BuyAgent<'info> {
     /// Protocol global state PDA.
     /// PDA: ["t_yield"]
     #[account(
+        mut,
         seeds = [b"t_yield"],
         bump = t_yield.t_yield_bump
     )]
@@ -161,8 +198,15 @@ pub struct BuyAgent<'info> {
 ///
 /// Transfers an agent NFT from protocol to user, collects payment, updates protocol/user state, and emits an event.
 /// Enforces bans, whitelists, and protocol constraints. Handles all token/NFT transfers and price/tax logic.
-pub fn buy_agent<'info>(ctx: Context<'_, '_, '_, 'info, BuyAgent<'info>>) -> TYieldResult<()> {
+/// Rejects the purchase if the total price exceeds `params.max_total_price` or it lands after `params.expiry`.
+pub fn buy_agent<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BuyAgent<'info>>,
+    params: BuyAgentParams,
+) -> TYieldResult<()> {
     let current_time = ctx.accounts.t_yield.get_time()?;
+    if current_time > params.expiry {
+        return Err(ErrorCode::TransactionExpired);
+    }
     let master_agent = ctx.accounts.master_agent.as_mut();
     let user = ctx.accounts.user.as_mut();
     let agents = ctx.accounts.agent.as_mut();
@@ -172,7 +216,25 @@ pub fn buy_agent<'info>(ctx: Context<'_, '_, '_, 'info, BuyAgent<'info>>) -> TYi
     if !user.can_perform_actions() {
         return Err(ErrorCode::CannotPerformAction);
     }
-    if master_agent.is_whitelist_mode() && !user.is_whitelisted() {
+    // Supply is sold through `bid_auction` while an auction is running
+    // and through `buy_presale_agent` while the presale window is open.
+    // Sunset master agents sell nothing.
+    if master_agent.close_presale_if_ended(current_time) {
+        msg!("Presale ended, master agent switched to public mode");
+    }
+    if master_agent.auction_live
+        || master_agent.is_presale_active(current_time)
+        || master_agent.is_sunset()
+    {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if master_agent.is_whitelist_mode()
+        && !master_agent.is_wallet_whitelisted(
+            &ctx.accounts.authority.key(),
+            &params.whitelist_proof,
+            user.is_whitelisted(),
+        )
+    {
         return Err(ErrorCode::CannotPerformAction);
     }
     if !agents.belongs_to_master_agent(&master_agent.key()) {
@@ -180,7 +242,7 @@ pub fn buy_agent<'info>(ctx: Context<'_, '_, '_, 'info, BuyAgent<'info>>) -> TYi
     }
 
     // --- Price/tax calculation ---
-    let price = master_agent.calculate_buy_price_with_tax()?;
+    let price = master_agent.calculate_buy_price_with_limit(params.max_total_price)?;
 
     // --- Payment transfer (Y-mint) ---
     let mint =
@@ -198,9 +260,36 @@ pub fn buy_agent<'info>(ctx: Context<'_, '_, '_, 'info, BuyAgent<'info>>) -> TYi
 
     // --- Remove agent from master agent ---
     master_agent.remove_agent(current_time)?;
+    master_agent.record_agent_purchase(price.2)?;
 
     // --- Update protocol state ---
-    t_yield.protocol_total_fees = t_yield.protocol_total_fees.safe_add(price.1)?;
+    // --- Referral shares of the tax ---
+    let referrals = t_yield.credit_referrals(
+        user,
+        ctx.accounts
+            .referral_registry
+            .as_deref_mut()
+            .map(|registry| &mut **registry),
+        ctx.accounts.referral_link.as_deref().map(|link| &**link),
+        ctx.remaining_accounts,
+        price.1,
+        current_time,
+    )?;
+    let mut referral_fee = 0;
+    for (referrer, level, fee) in referrals {
+        referral_fee = referral_fee.safe_add(fee)?;
+        emit_cpi!(ReferralEarningEvent {
+            referrer,
+            referred_user: user.authority,
+            level,
+            amount: fee,
+            tax: price.1,
+            timestamp: current_time,
+        });
+    }
+    t_yield.protocol_total_fees = t_yield
+        .protocol_total_fees
+        .safe_add(price.1.safe_sub(referral_fee)?)?;
     t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_add(price.2)?;
     t_yield.protocol_total_balance_usd = t_yield.protocol_total_balance_usd.safe_add(price.1)?;
     t_yield.protocol_total_earnings = t_yield.protocol_total_earnings.safe_add(price.0)?;
@@ -227,7 +316,11 @@ pub fn buy_agent<'info>(ctx: Context<'_, '_, '_, 'info, BuyAgent<'info>>) -> TYi
     // --- Update user and agent state ---
     user.add_agent(price.2)?; // Pass base_price to track total value spent
     agents.transfer_ownership(ctx.accounts.authority.key(), current_time)?;
+    // Agents returned under a relist cooldown become buyable once it elapses
+    agents.release_cooldown(current_time);
     agents.unlist(current_time)?;
+    // Yield accrues to the buyer from the moment of purchase
+    agents.reset_yield_accrual(master_agent, current_time)?;
     user.history.add_agents_purchased(price.2)?;
     user.history.add_fees_spent(price.1)?;
     user.validate_user()?;
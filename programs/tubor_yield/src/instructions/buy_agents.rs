@@ -0,0 +1,312 @@
+//! Instruction: Buy Agents
+//!
+//! Allows a user to purchase several agent NFTs from the same master agent in one instruction.
+//! The agents are read from `remaining_accounts` in groups of `AgentBatchAccounts::LEN`
+//! (`[agent, mint, metadata, transfer_authority_ta, user_agent_ta]`), followed by the upline
+//! referral registries. Payment is a single Y-mint transfer for the whole batch and the user,
+//! master agent and protocol counters are updated once.
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::{SafeMath, MAX_AGENT_BATCH_SIZE},
+    state::{
+        AgentBatchAccounts, BuyAgentsEvent, MasterAgent, ReferralEarningEvent, ReferralLink,
+        ReferralRegistry, TYield, TransferAgentParams, User,
+    },
+    try_from,
+};
+
+/// Parameters for buying several agents.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BuyAgentsParams {
+    /// Number of agents to buy (at most `MAX_AGENT_BATCH_SIZE`)
+    pub count: u8,
+    /// Maximum total price (including tax) the buyer accepts for the whole batch
+    pub max_total_price: u64,
+    /// Unix timestamp after which the purchase is rejected
+    pub expiry: i64,
+    /// Merkle proof of the buyer's wallet, required when the master agent has a whitelist root
+    pub whitelist_proof: Vec<[u8; 32]>,
+}
+
+/// Accounts required for buying several agent NFTs from a master agent.
+///
+/// Per-agent accounts are passed through `remaining_accounts`.
+#[derive(Accounts)]
+pub struct BuyAgents<'info> {
+    /// The user purchasing the agents. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Instructions sysvar (required for Metaplex CPI).
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+
+    /// CHECK: Metaplex token metadata program.
+    #[account(address = METADATA_PROGRAM_ID)]
+    pub metadata_program: AccountInfo<'info>,
+
+    // --- Protocol/User State ---
+    /// User account PDA.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Referral registry of the buyer's direct referrer. Required when the buyer has a
+    /// referrer, who is credited `ref_earn_percentage` of the tax.
+    /// PDA: ["referral_registry", user.referrer]
+    #[account(
+        mut,
+        seeds = [b"referral_registry".as_ref(), user.referrer.as_ref()],
+        bump = referral_registry.bump
+    )]
+    pub referral_registry: Option<Box<Account<'info, ReferralRegistry>>>,
+
+    /// Referral link between the buyer and their referrer. Required when the buyer has a
+    /// referrer. The registries of all higher referral levels are read from `remaining_accounts`
+    /// after the agent groups (nearest first).
+    /// PDA: ["referral_link", user.referrer, authority]
+    #[account(
+        seeds = [b"referral_link".as_ref(), user.referrer.as_ref(), authority.key().as_ref()],
+        bump = referral_link.bump
+    )]
+    pub referral_link: Option<Box<Account<'info, ReferralLink>>>,
+
+    /// Master agent account PDA (the parent/master of the agents).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// CHECK: Transfer authority PDA (protocol authority for token/NFT transfers).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    // --- Token Accounts ---
+    /// User's Y-mint token account (payer for the purchase).
+    /// Must have mint == t_yield.y_mint.
+    #[account(
+        mut,
+        constraint = user_y_mint_ta.mint == t_yield.y_mint
+    )]
+    pub user_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol's Y-mint token account (receiver of payment).
+    /// Must have mint == t_yield.y_mint.
+    #[account(
+        mut,
+        constraint = transfer_authority_y_mint_ta.mint == t_yield.y_mint
+    )]
+    pub transfer_authority_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Y-mint SPL token mint (payment token for protocol).
+    #[account(address = t_yield.y_mint)]
+    pub y_mint: AccountInfo<'info>,
+
+    // --- Misc ---
+    /// CHECK: Event authority for CPI event logs (used for event emission; not written to).
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Buy Agents
+///
+/// Transfers `params.count` agent NFTs from protocol to user for a single payment, updates
+/// protocol/user state once, and emits one aggregated event. Rejects the batch if its total price
+/// exceeds `params.max_total_price` or it lands after `params.expiry`.
+pub fn buy_agents<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BuyAgents<'info>>,
+    params: BuyAgentsParams,
+) -> TYieldResult<()> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    if current_time > params.expiry {
+        return Err(ErrorCode::TransactionExpired);
+    }
+    let count = params.count as usize;
+    if count > MAX_AGENT_BATCH_SIZE {
+        return Err(ErrorCode::InvalidAccount);
+    }
+
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let user = ctx.accounts.user.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+
+    // --- Access control checks ---
+    if !user.can_perform_actions() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    // Supply is sold through `bid_auction` while an auction is running
+    // and through `buy_presale_agent` while the presale window is open.
+    // Sunset master agents sell nothing.
+    if master_agent.close_presale_if_ended(current_time) {
+        msg!("Presale ended, master agent switched to public mode");
+    }
+    if master_agent.auction_live
+        || master_agent.is_presale_active(current_time)
+        || master_agent.is_sunset()
+    {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if master_agent.is_whitelist_mode()
+        && !master_agent.is_wallet_whitelisted(
+            &ctx.accounts.authority.key(),
+            &params.whitelist_proof,
+            user.is_whitelisted(),
+        )
+    {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    let mut batch = AgentBatchAccounts::load_batch(
+        ctx.remaining_accounts,
+        count,
+        &master_agent.key(),
+        &ctx.accounts.transfer_authority.key(),
+        &ctx.accounts.authority.key(),
+    )?;
+
+    // --- Price/tax calculation for the whole batch ---
+    let price =
+        master_agent.calculate_buy_quote_with_limit(count as u64, params.max_total_price)?;
+
+    // --- Payment transfer (Y-mint) ---
+    let mint =
+        try_from!(Account<Mint>, ctx.accounts.y_mint).map_err(|_| ErrorCode::AccountFromError)?;
+    TYield::transfer_tokens(
+        ctx.accounts.user_y_mint_ta.to_account_info(),
+        mint.to_account_info(),
+        ctx.accounts.transfer_authority_y_mint_ta.to_account_info(),
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        price.0,
+        mint.decimals,
+    )
+    .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    // --- Remove agents from master agent ---
+    master_agent.remove_agents(count as u64, current_time)?;
+    master_agent.record_agent_purchases(count as u64, price.2)?;
+
+    // --- Referral shares of the tax ---
+    let referrals = t_yield.credit_referrals(
+        user,
+        ctx.accounts
+            .referral_registry
+            .as_deref_mut()
+            .map(|registry| &mut **registry),
+        ctx.accounts.referral_link.as_deref().map(|link| &**link),
+        &ctx.remaining_accounts[count * AgentBatchAccounts::LEN..],
+        price.1,
+        current_time,
+    )?;
+    let mut referral_fee = 0;
+    for (referrer, level, fee) in referrals {
+        referral_fee = referral_fee.safe_add(fee)?;
+        emit_cpi!(ReferralEarningEvent {
+            referrer,
+            referred_user: user.authority,
+            level,
+            amount: fee,
+            tax: price.1,
+            timestamp: current_time,
+        });
+    }
+    t_yield.protocol_total_fees = t_yield
+        .protocol_total_fees
+        .safe_add(price.1.safe_sub(referral_fee)?)?;
+    t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_add(price.2)?;
+    t_yield.protocol_total_balance_usd = t_yield.protocol_total_balance_usd.safe_add(price.1)?;
+    t_yield.protocol_total_earnings = t_yield.protocol_total_earnings.safe_add(price.0)?;
+
+    // --- Transfer agent NFTs to user ---
+    let mut agents = Vec::with_capacity(count);
+    for accounts in batch.iter_mut() {
+        let transfer_agent_params = TransferAgentParams {
+            payer: ctx.accounts.authority.to_account_info(),
+            sender_nft_token_account: accounts.transfer_authority_ta.to_account_info(),
+            authority: ctx.accounts.transfer_authority.to_account_info(),
+            receiver_token_account: accounts.user_agent_ta.to_account_info(),
+            receiver: ctx.accounts.authority.to_account_info(),
+            mint: accounts.mint.clone(),
+            metadata: accounts.metadata.clone(),
+            metadata_program: ctx.accounts.metadata_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+        };
+        t_yield
+            .transfer_agent(transfer_agent_params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+        let agent = &mut accounts.agent;
+        agent.transfer_ownership(ctx.accounts.authority.key(), current_time)?;
+        agent.release_cooldown(current_time);
+        agent.unlist(current_time)?;
+        // Yield accrues to the buyer from the moment of purchase
+        agent.reset_yield_accrual(master_agent, current_time)?;
+        agent
+            .exit(&crate::ID)
+            .map_err(|_| ErrorCode::AccountFromError)?;
+        agents.push(agent.key());
+    }
+
+    // --- Update user state ---
+    user.add_agents(params.count as u32, price.2)?;
+    user.history.add_agents_purchased(price.2)?;
+    user.history.add_fees_spent(price.1)?;
+    user.validate_user()?;
+
+    master_agent.increment_trade_count(current_time)?;
+    master_agent.validate_security(current_time)?;
+
+    // --- Emit event ---
+    emit_cpi!(BuyAgentsEvent {
+        agents,
+        owner: ctx.accounts.authority.key(),
+        master_agent: master_agent.key(),
+        total_price: price.0,
+        tax_amount: price.1,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
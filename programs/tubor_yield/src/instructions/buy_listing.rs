@@ -0,0 +1,416 @@
+//! Instruction: Buy Listing
+//!
+//! Fills a peer-to-peer listing. The buyer pays the ask price in y_mint: the protocol sell tax
+//! goes to the protocol, a royalty of `seller_fee_basis_points` goes to the master agent authority
+//! and the rest goes to the seller. The buyer's referral chain is credited its share of the tax
+//! like in `buy_agent`. The escrowed agent NFT is moved to the buyer and the listing is closed
+//! (rent goes back to the seller).
+//!
+//! Accounts:
+//! - authority: The buyer (signer, payer)
+//! - seller: Wallet of the seller (receives the listing rent)
+//! - user: User account PDA of the buyer
+//! - seller_user: User account PDA of the seller
+//! - referral_registry: (Optional) Registry of the buyer's referrer, required when the buyer has a referrer
+//! - referral_link: (Optional) Referral link of the buyer, required when the buyer has a referrer
+//! - listing: The listing PDA (closed)
+//! - agent: Agent account PDA of the listed agent
+//! - master_agent: Master agent account PDA (parent/master of the agent)
+//! - mint: Mint account for the agent NFT
+//! - metadata: Metadata account for the agent NFT (Metaplex)
+//! - transfer_authority: Transfer authority PDA (escrow owner)
+//! - escrow_agent_ta: Escrow token account holding the agent NFT
+//! - buyer_agent_ta: Buyer's token account for the agent NFT (created if needed)
+//! - buyer_y_mint_ta: Buyer's y_mint token account (payer)
+//! - seller_y_mint_ta: Seller's y_mint token account (receives proceeds)
+//! - royalty_y_mint_ta: Master agent authority's y_mint token account (receives royalty)
+//! - transfer_authority_y_mint_ta: Protocol y_mint token account (receives tax)
+//! - y_mint: Protocol yield token mint
+//! - t_yield: Protocol global state/config PDA
+//! - system_program, token_program, associated_token_program: Solana programs
+//! - sysvar_instructions: Instructions sysvar (for Metaplex CPI)
+//! - metadata_program: Metaplex token metadata program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+//!
+//! The registries of the buyer's higher referral levels are read from `remaining_accounts`.
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{
+        Agent, AgentListing, BuyListingEvent, MasterAgent, ReferralEarningEvent, ReferralLink,
+        ReferralRegistry, TYield, TransferAgentParams, User,
+    },
+    try_from,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BuyListingParams {
+    /// Maximum price the buyer is willing to pay (protects against price updates)
+    pub max_price: u64,
+    /// Merkle proof of the buyer's wallet, required when the master agent has a whitelist root
+    pub whitelist_proof: Vec<[u8; 32]>,
+}
+
+#[derive(Accounts)]
+pub struct BuyListing<'info> {
+    /// The buyer. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Wallet of the seller, validated against `listing.seller`.
+    #[account(mut, address = listing.seller)]
+    pub seller: AccountInfo<'info>,
+
+    /// User account PDA of the buyer.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user", authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// User account PDA of the seller.
+    /// PDA: ["user", listing.seller]
+    #[account(
+        mut,
+        seeds = [b"user", listing.seller.as_ref()],
+        bump = seller_user.bump
+    )]
+    pub seller_user: Box<Account<'info, User>>,
+
+    /// Referral registry of the buyer's direct referrer. Required when the buyer has a
+    /// referrer, who is credited `ref_earn_percentage` of the tax.
+    /// PDA: ["referral_registry", user.referrer]
+    #[account(
+        mut,
+        seeds = [b"referral_registry".as_ref(), user.referrer.as_ref()],
+        bump = referral_registry.bump
+    )]
+    pub referral_registry: Option<Box<Account<'info, ReferralRegistry>>>,
+
+    /// Referral link between the buyer and their referrer. Required when the buyer has a
+    /// referrer. The registries of all higher referral levels are read from `remaining_accounts`
+    /// (nearest first) and credited `ref_tier_percentages` of the tax.
+    /// PDA: ["referral_link", user.referrer, authority]
+    #[account(
+        seeds = [b"referral_link".as_ref(), user.referrer.as_ref(), authority.key().as_ref()],
+        bump = referral_link.bump
+    )]
+    pub referral_link: Option<Box<Account<'info, ReferralLink>>>,
+
+    /// The listing PDA. Closed to the seller.
+    /// PDA: ["listing", mint]
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing".as_ref(), mint.key().as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Box<Account<'info, AgentListing>>,
+
+    /// Agent account PDA of the listed agent.
+    /// PDA: ["agent", mint]
+    #[account(
+        mut,
+        seeds = [b"agent".as_ref(), mint.key().as_ref()],
+        bump = agent.bump
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Master agent account PDA (the parent/master of the agent).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+        constraint = agent.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Mint account for the agent NFT.
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Metadata account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    /// CHECK: Transfer authority PDA (owner of the escrow token account).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    /// Escrow token account holding the agent NFT.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = transfer_authority,
+    )]
+    pub escrow_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Buyer's token account for the agent NFT.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = authority,
+    )]
+    pub buyer_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Buyer's Y-mint token account (payer for the purchase).
+    #[account(
+        mut,
+        constraint = buyer_y_mint_ta.mint == t_yield.y_mint,
+        constraint = buyer_y_mint_ta.owner == authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub buyer_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Seller's Y-mint token account (receives the proceeds).
+    #[account(
+        mut,
+        constraint = seller_y_mint_ta.mint == t_yield.y_mint,
+        constraint = seller_y_mint_ta.owner == listing.seller @ ErrorCode::InvalidAccount,
+    )]
+    pub seller_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Master agent authority's Y-mint token account (receives the royalty).
+    #[account(
+        mut,
+        constraint = royalty_y_mint_ta.mint == t_yield.y_mint,
+        constraint = royalty_y_mint_ta.owner == master_agent.authority @ ErrorCode::InvalidAccount,
+    )]
+    pub royalty_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol's Y-mint token account (receives the tax).
+    #[account(
+        mut,
+        constraint = transfer_authority_y_mint_ta.mint == t_yield.y_mint,
+        constraint = transfer_authority_y_mint_ta.owner == transfer_authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub transfer_authority_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Y-mint SPL token mint (payment token for protocol).
+    #[account(address = t_yield.y_mint)]
+    pub y_mint: AccountInfo<'info>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Instructions sysvar (required for Metaplex CPI).
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+
+    /// CHECK: Metaplex token metadata program.
+    #[account(address = METADATA_PROGRAM_ID)]
+    pub metadata_program: AccountInfo<'info>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Buy Listing
+///
+/// Pays the seller, the royalty and the protocol tax, then moves the agent to the buyer.
+/// Pending yield is settled to the seller first so the buyer only earns from the moment of purchase.
+pub fn buy_listing<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BuyListing<'info>>,
+    params: BuyListingParams,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let buyer = ctx.accounts.user.as_mut();
+    let seller = ctx.accounts.seller_user.as_mut();
+    let listing = &ctx.accounts.listing;
+    let agent = ctx.accounts.agent.as_mut();
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+
+    // 1. Access control checks
+    if listing.agent != agent.key() || !agent.is_owned_by(&listing.seller) {
+        return Err(ErrorCode::InvalidAccount);
+    }
+    // Rented agents cannot change hands until the rental is closed
+    if !agent.is_transferable() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if buyer.authority == listing.seller {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if !buyer.can_perform_actions() || !master_agent.can_perform_actions() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if master_agent.is_whitelist_mode()
+        && !master_agent.is_wallet_whitelisted(
+            &buyer.authority,
+            &params.whitelist_proof,
+            buyer.is_whitelisted(),
+        )
+    {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if listing.price > params.max_price {
+        return Err(ErrorCode::MaxPriceSlippage);
+    }
+
+    // 2. Split the price
+    let price = listing.price;
+    let (seller_proceeds, tax_amount, royalty_amount) =
+        master_agent.calculate_listing_fill(price)?;
+
+    // 3. Settle yield earned by the seller up to now. The agent is not rented, so all
+    // pending yield is the seller's.
+    let yield_settled = agent.settle_yield(master_agent, current_time)?;
+    if yield_settled > 0 {
+        seller.add_unclaimed_yield(yield_settled)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(yield_settled)?;
+    }
+
+    // 4. Payment transfers (Y-mint)
+    let mint =
+        try_from!(Account<Mint>, ctx.accounts.y_mint).map_err(|_| ErrorCode::AccountFromError)?;
+    let payments = [
+        (
+            ctx.accounts.seller_y_mint_ta.to_account_info(),
+            seller_proceeds,
+        ),
+        (
+            ctx.accounts.royalty_y_mint_ta.to_account_info(),
+            royalty_amount,
+        ),
+        (
+            ctx.accounts.transfer_authority_y_mint_ta.to_account_info(),
+            tax_amount,
+        ),
+    ];
+    for (to, amount) in payments {
+        if amount == 0 {
+            continue;
+        }
+        TYield::transfer_tokens(
+            ctx.accounts.buyer_y_mint_ta.to_account_info(),
+            mint.to_account_info(),
+            to,
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            amount,
+            mint.decimals,
+        )
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+    }
+
+    // 5. Credit the buyer's referral chain and update protocol state
+    let referrals = t_yield.credit_referrals(
+        buyer,
+        ctx.accounts
+            .referral_registry
+            .as_deref_mut()
+            .map(|registry| &mut **registry),
+        ctx.accounts.referral_link.as_deref().map(|link| &**link),
+        ctx.remaining_accounts,
+        tax_amount,
+        current_time,
+    )?;
+    let mut referral_fee = 0;
+    for (referrer, level, fee) in referrals {
+        referral_fee = referral_fee.safe_add(fee)?;
+        emit_cpi!(ReferralEarningEvent {
+            referrer,
+            referred_user: buyer.authority,
+            level,
+            amount: fee,
+            tax: tax_amount,
+            timestamp: current_time,
+        });
+    }
+    t_yield.protocol_total_fees = t_yield
+        .protocol_total_fees
+        .safe_add(tax_amount.safe_sub(referral_fee)?)?;
+    t_yield.protocol_total_balance_usd = t_yield.protocol_total_balance_usd.safe_add(tax_amount)?;
+    t_yield.protocol_total_earnings = t_yield.protocol_total_earnings.safe_add(tax_amount)?;
+
+    // 6. Move the agent NFT out of escrow
+    let transfer_agent_params = TransferAgentParams {
+        payer: ctx.accounts.authority.to_account_info(),
+        sender_nft_token_account: ctx.accounts.escrow_agent_ta.to_account_info(),
+        authority: ctx.accounts.transfer_authority.to_account_info(),
+        receiver_token_account: ctx.accounts.buyer_agent_ta.to_account_info(),
+        receiver: ctx.accounts.authority.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+        metadata_program: ctx.accounts.metadata_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+    };
+    t_yield
+        .transfer_agent(transfer_agent_params)
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    // 7. Update agent ownership and user stats
+    agent.transfer_ownership(buyer.authority, current_time)?;
+    seller.remove_agent(price)?;
+    buyer.add_agent(price)?;
+    buyer.history.add_fees_spent(tax_amount)?;
+    seller.update_last_activity(current_time)?;
+    buyer.update_last_activity(current_time)?;
+    seller.validate_user()?;
+    buyer.validate_user()?;
+
+    master_agent.increment_trade_count(current_time)?;
+
+    emit_cpi!(BuyListingEvent {
+        agent: agent.key(),
+        seller: listing.seller,
+        buyer: buyer.authority,
+        master_agent: master_agent.key(),
+        price,
+        protocol_tax: tax_amount,
+        royalty: royalty_amount,
+        seller_proceeds,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -0,0 +1,170 @@
+//! Instruction: Cancel Agent Rental
+//!
+//! Ends a rental of an agent's yield and unlocks the agent for transfers and sales. A pending
+//! offer can be withdrawn by the owner or the renter, an accepted rental can only be given up
+//! early by the renter (the price is not refunded), and anyone can close a rental once its term
+//! has ended. Yield earned up to now is settled to whoever holds the yield rights before the
+//! rental closes.
+//!
+//! Accounts:
+//! - authority: The caller (signer)
+//! - owner: Agent owner, receives the rental account rent
+//! - owner_user: User account PDA of the owner
+//! - renter_user: User account PDA of the renter (optional, required once the rental was accepted)
+//! - agent: Agent account PDA (the rented agent)
+//! - master_agent: Master agent account PDA (parent/master of the agent)
+//! - rental: Rental PDA of the agent (closed to the owner)
+//! - t_yield: Protocol global state/config PDA
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{
+        AccrueYieldEvent, Agent, AgentRental, MasterAgent, RentalClosedEvent, RentalYieldEvent,
+        TYield, User,
+    },
+};
+
+#[derive(Accounts)]
+pub struct CancelAgentRental<'info> {
+    /// The caller. Must sign the transaction.
+    pub authority: Signer<'info>,
+
+    /// CHECK: Agent owner, validated against the rental. Receives the rental account rent.
+    #[account(
+        mut,
+        address = rental.owner
+    )]
+    pub owner: AccountInfo<'info>,
+
+    /// User account PDA of the owner.
+    /// PDA: ["user", rental.owner]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), rental.owner.as_ref()],
+        bump = owner_user.bump
+    )]
+    pub owner_user: Box<Account<'info, User>>,
+
+    /// User account PDA of the renter. Required once the rental was accepted.
+    /// PDA: ["user", rental.renter]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), rental.renter.as_ref()],
+        bump = renter_user.bump
+    )]
+    pub renter_user: Option<Box<Account<'info, User>>>,
+
+    /// Agent account PDA (the rented agent).
+    /// PDA: ["agent", agent.mint]
+    #[account(
+        mut,
+        seeds = [b"agent".as_ref(), agent.mint.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Master agent account PDA (parent/master of the agent).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+        constraint = agent.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Rental PDA of the agent. Closed to the owner.
+    /// PDA: ["agent_rental", agent]
+    #[account(
+        mut,
+        seeds = [b"agent_rental".as_ref(), agent.key().as_ref()],
+        bump = rental.bump,
+        close = owner,
+    )]
+    pub rental: Box<Account<'info, AgentRental>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Cancel Agent Rental
+///
+/// Settles yield earned up to now to the holder of the yield rights, closes the rental and unlocks
+/// the agent.
+pub fn cancel_agent_rental<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelAgentRental<'info>>,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let agent = ctx.accounts.agent.as_mut();
+    let rental = ctx.accounts.rental.as_mut();
+    let owner_user = ctx.accounts.owner_user.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+
+    if !rental.can_close(&ctx.accounts.authority.key(), current_time) {
+        return Err(ErrorCode::InvalidAuthority);
+    }
+
+    // Settle the yield earned so far before the renter's claim ends
+    let (owner_amount, renter_amount) = rental.settle_yield(agent, master_agent, current_time)?;
+    if owner_amount > 0 {
+        owner_user.add_unclaimed_yield(owner_amount)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(owner_amount)?;
+
+        emit_cpi!(AccrueYieldEvent {
+            agent: agent.key(),
+            owner: agent.owner,
+            master_agent: agent.master_agent,
+            amount: owner_amount,
+            timestamp: current_time,
+        });
+    }
+    if renter_amount > 0 {
+        let renter_user = ctx
+            .accounts
+            .renter_user
+            .as_mut()
+            .ok_or(ErrorCode::CannotPerformAction)?;
+        renter_user.add_unclaimed_yield(renter_amount)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(renter_amount)?;
+
+        emit_cpi!(RentalYieldEvent {
+            agent: agent.key(),
+            renter: rental.renter,
+            amount: renter_amount,
+            timestamp: current_time,
+        });
+    }
+
+    agent.is_rented = false;
+    agent.last_updated = current_time;
+
+    emit_cpi!(RentalClosedEvent {
+        agent: agent.key(),
+        owner: rental.owner,
+        renter: rental.renter,
+        renter_yield: rental.renter_yield,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
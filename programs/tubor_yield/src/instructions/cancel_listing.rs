@@ -0,0 +1,163 @@
+//! Instruction: Cancel Listing
+//!
+//! Cancels an open peer-to-peer listing, returns the escrowed agent NFT to the seller
+//! and closes the listing account (rent goes back to the seller).
+//!
+//! Accounts:
+//! - authority: The seller (signer)
+//! - listing: The listing PDA (closed)
+//! - mint: Mint account for the agent NFT
+//! - metadata: Metadata account for the agent NFT (Metaplex)
+//! - transfer_authority: Transfer authority PDA (escrow owner)
+//! - escrow_agent_ta: Escrow token account holding the agent NFT
+//! - seller_agent_ta: Seller's token account for the agent NFT (created if needed)
+//! - t_yield: Protocol global state/config PDA
+//! - system_program, token_program, associated_token_program: Solana programs
+//! - sysvar_instructions: Instructions sysvar (for Metaplex CPI)
+//! - metadata_program: Metaplex token metadata program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{AgentListing, CancelListingEvent, TYield, TransferAgentParams},
+};
+
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    /// The seller of the listing. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The listing PDA. Closed to the seller.
+    /// PDA: ["listing", mint]
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"listing".as_ref(), mint.key().as_ref()],
+        bump = listing.bump,
+        constraint = listing.is_seller(&authority.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub listing: Box<Account<'info, AgentListing>>,
+
+    /// Mint account for the agent NFT.
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Metadata account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    /// CHECK: Transfer authority PDA (owner of the escrow token account).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    /// Escrow token account holding the agent NFT.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = transfer_authority,
+    )]
+    pub escrow_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Seller's token account receiving the agent NFT back.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = authority,
+    )]
+    pub seller_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Instructions sysvar (required for Metaplex CPI).
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+
+    /// CHECK: Metaplex token metadata program.
+    #[account(address = METADATA_PROGRAM_ID)]
+    pub metadata_program: AccountInfo<'info>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Cancel Listing
+///
+/// Returns the escrowed agent NFT to the seller. The listing account is closed by Anchor.
+pub fn cancel_listing<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelListing<'info>>,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let listing = &ctx.accounts.listing;
+
+    // 1. Return the agent NFT from escrow (signed by the transfer authority)
+    let transfer_agent_params = TransferAgentParams {
+        payer: ctx.accounts.authority.to_account_info(),
+        sender_nft_token_account: ctx.accounts.escrow_agent_ta.to_account_info(),
+        authority: ctx.accounts.transfer_authority.to_account_info(),
+        receiver_token_account: ctx.accounts.seller_agent_ta.to_account_info(),
+        receiver: ctx.accounts.authority.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+        metadata_program: ctx.accounts.metadata_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+    };
+    ctx.accounts
+        .t_yield
+        .transfer_agent(transfer_agent_params)
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    emit_cpi!(CancelListingEvent {
+        agent: listing.agent,
+        seller: listing.seller,
+        master_agent: listing.master_agent,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -22,6 +22,7 @@ pub struct ClaimReferralRewards<'info> {
 
     /// Referral registry PDA.
     #[account(
+        mut,
         seeds = [b"referral_registry", authority.key().as_ref()],
         bump =  referral_registry.bump
     )]
@@ -103,6 +104,9 @@ pub fn claim_referral_rewards<'info>(
     t_yield.protocol_total_balance_usd = t_yield
         .protocol_total_balance_usd
         .safe_sub(unclaimed_referral_earnings)?;
+    t_yield.protocol_referral_owed = t_yield
+        .protocol_referral_owed
+        .safe_sub(unclaimed_referral_earnings)?;
 
     user.history
         .add_referral_earnings(unclaimed_referral_earnings)?;
@@ -0,0 +1,471 @@
+//! Instruction: Claim Sunset Buyback
+//!
+//! Permissionless crank buying back an agent of a sunset master agent at the buyback price set by
+//! the multisig. Anyone can call it for any agent until the sunset deadline; the price is always
+//! paid to the agent's recorded owner, so no holder loses their buyback to `sweep_sunset`.
+//!
+//! - An open rental is force-ended first: the yield earned so far is settled to the holder of the
+//!   yield rights and the rental PDA is closed to the owner.
+//! - A fractionalized agent is owned by its vault: the price is paid to the vault and split over
+//!   the shares like yield. The shares still reference the agent, so its NFT stays held in
+//!   protocol custody whatever the relist policy, and the agent goes to the protocol when the
+//!   shares are reassembled.
+//! - Otherwise the price is transferred to the owner's Y-mint token account.
+//!
+//! The bought back agent follows the master agent relist policy, except that a sunset master
+//! agent never sells again: under `Burn` the agent is retired from the supply and its NFT burned,
+//! under every other policy it is held unlisted by the protocol. The NFT can only be moved out of
+//! a wallet with the holder's signature, so when the holder cranks their own agent (passing
+//! `owner_agent_ta`) it is burned or taken into custody; when someone else cranks it, the agent
+//! PDA is closed to the owner and the NFT left in the wallet no longer backs an agent.
+//!
+//! Accounts:
+//! - authority: The caller (anyone can call; the holder to burn or hand over the NFT)
+//! - owner: Recorded owner of the agent (receives the rent of closed accounts)
+//! - system_program: Solana system program
+//! - token_program: SPL token program
+//! - associated_token_program: SPL associated token program
+//! - sysvar_instructions: Instructions sysvar (for Metaplex CPI)
+//! - metadata_program: Metaplex token metadata program
+//! - metadata: Metadata account for the agent NFT (Metaplex, closed by a burn)
+//! - master_edition: Master edition account for the agent NFT (Metaplex, closed by a burn)
+//! - owner_user: User account PDA of the owner (optional, required unless the agent is fractionalized)
+//! - agent: Agent account PDA (closed to the owner unless held by the protocol or a vault)
+//! - master_agent: Master agent account PDA (parent/master of the agent)
+//! - sunset: Sunset PDA of the master agent
+//! - rental: Rental PDA of the agent (optional, required while the agent is rented)
+//! - renter_user: User account PDA of the renter (optional, required when the renter is owed yield)
+//! - agent_vault: Vault PDA of the agent (optional, required when the agent is fractionalized)
+//! - mint: Mint account for the agent NFT
+//! - t_yield: Protocol global state/config PDA
+//! - transfer_authority: Transfer authority PDA (protocol authority for token transfers)
+//! - owner_agent_ta: Owner's token account holding the agent NFT (optional, burned or sender)
+//! - transfer_authority_ta: Protocol's token account for the agent NFT (optional, receiver)
+//! - owner_y_mint_ta: Owner's Y-mint token account (optional, receiver of the buyback price)
+//! - transfer_authority_y_mint_ta: Protocol's Y-mint token account (payer of the buyback price)
+//! - y_mint: Y-mint SPL token mint (payment token for protocol)
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{
+        Agent, AgentRental, AgentSunset, AgentVault, BurnAgentParams, MasterAgent, RelistPolicy,
+        RentalClosedEvent, RentalYieldEvent, SunsetBuybackEvent, TYield, TransferAgentParams, User,
+        UserStatus,
+    },
+    try_from,
+};
+
+#[derive(Accounts)]
+pub struct ClaimSunsetBuyback<'info> {
+    /// The caller. Anyone can crank the buyback; the holder must sign to burn or hand over the NFT.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Recorded owner of the agent (a wallet or the vault PDA of a fractionalized agent).
+    /// Receives the rent of the accounts closed by the buyback.
+    #[account(
+        mut,
+        address = agent.owner
+    )]
+    pub owner: AccountInfo<'info>,
+
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Instructions sysvar (required for Metaplex CPI).
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+
+    /// CHECK: Metaplex token metadata program.
+    #[account(address = METADATA_PROGRAM_ID)]
+    pub metadata_program: AccountInfo<'info>,
+
+    /// CHECK: Metadata account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    /// CHECK: Master edition account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint, "edition"]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+            b"edition",
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub master_edition: AccountInfo<'info>,
+
+    // --- Protocol/User/Agent State ---
+    /// User account PDA of the owner. Required unless the agent is fractionalized.
+    /// PDA: ["user", agent.owner]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), agent.owner.as_ref()],
+        bump = owner_user.bump
+    )]
+    pub owner_user: Option<Box<Account<'info, User>>>,
+
+    /// Agent account PDA (the agent being bought back).
+    /// PDA: ["agent", mint]
+    #[account(
+        mut,
+        seeds = [b"agent".as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Master agent account PDA (parent/master of the agent).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+        constraint = agent.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Sunset PDA of the master agent.
+    /// PDA: ["sunset", master_agent]
+    #[account(
+        mut,
+        seeds = [b"sunset".as_ref(), master_agent.key().as_ref()],
+        bump = sunset.bump,
+    )]
+    pub sunset: Box<Account<'info, AgentSunset>>,
+
+    /// Rental of the agent's yield. Required while the agent is rented; force-ended and closed
+    /// to the owner.
+    /// PDA: ["agent_rental", agent]
+    #[account(
+        mut,
+        seeds = [b"agent_rental".as_ref(), agent.key().as_ref()],
+        bump = rental.bump,
+    )]
+    pub rental: Option<Box<Account<'info, AgentRental>>>,
+
+    /// User account PDA of the renter. Required when the renter is owed yield.
+    /// PDA: ["user", rental.renter]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), renter_user.authority.as_ref()],
+        bump = renter_user.bump,
+    )]
+    pub renter_user: Option<Box<Account<'info, User>>>,
+
+    /// Vault PDA of the agent. Required when the agent is fractionalized.
+    /// PDA: ["agent_vault", agent]
+    #[account(
+        mut,
+        seeds = [b"agent_vault".as_ref(), agent.key().as_ref()],
+        bump = agent_vault.bump,
+    )]
+    pub agent_vault: Option<Box<Account<'info, AgentVault>>>,
+
+    /// Mint account for the agent NFT being bought back.
+    #[account(mut)]
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// CHECK: Transfer authority PDA (protocol authority for token transfers).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    // --- Token Accounts ---
+    /// Owner's token account holding the agent NFT. Passed by a holder cranking their own agent
+    /// to have the NFT burned or taken into custody.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_agent_ta: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Protocol's token account for the agent NFT. Required when the NFT is taken into custody.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = transfer_authority,
+    )]
+    pub transfer_authority_ta: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Owner's Y-mint token account (receiver of the buyback price). Required unless the agent
+    /// is fractionalized.
+    #[account(
+        mut,
+        constraint = owner_y_mint_ta.mint == t_yield.y_mint,
+        constraint = owner_y_mint_ta.owner == agent.owner @ ErrorCode::InvalidAccount,
+    )]
+    pub owner_y_mint_ta: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Protocol's Y-mint token account (payer of the buyback price).
+    /// Must have mint == t_yield.y_mint.
+    #[account(
+        mut,
+        constraint = transfer_authority_y_mint_ta.mint == t_yield.y_mint
+    )]
+    pub transfer_authority_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Y-mint SPL token mint (payment token for protocol).
+    #[account(address = t_yield.y_mint)]
+    pub y_mint: AccountInfo<'info>,
+
+    // --- Misc ---
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Claim Sunset Buyback
+///
+/// Force-ends an open rental, settles the agent's yield, pays the buyback price to the recorded
+/// owner (or its vault) and retires the agent under the relist policy. Rejected once the sunset
+/// deadline has passed.
+pub fn claim_sunset_buyback<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimSunsetBuyback<'info>>,
+) -> TYieldResult<()> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let sunset = ctx.accounts.sunset.as_mut();
+    let agents = ctx.accounts.agent.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+    let transfer_authority = ctx.accounts.transfer_authority.key();
+
+    // Agents already held by the protocol were bought back or never sold
+    if agents.is_owned_by(&transfer_authority) {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    let mut vault = ctx
+        .accounts
+        .agent_vault
+        .as_deref_mut()
+        .filter(|vault| agents.is_owned_by(&vault.key()));
+    let mut owner_user = match vault {
+        Some(_) => None,
+        None => {
+            let owner_user = ctx
+                .accounts
+                .owner_user
+                .as_deref_mut()
+                .ok_or(ErrorCode::CannotPerformAction)?;
+            if owner_user.has_status(UserStatus::Banned) {
+                return Err(ErrorCode::CannotPerformAction);
+            }
+            Some(owner_user)
+        }
+    };
+
+    // --- Force-end an open rental and settle the agent's yield ---
+    let rental_ended = agents.is_rented;
+    let owner_yield = if rental_ended {
+        let rental = ctx
+            .accounts
+            .rental
+            .as_deref_mut()
+            .ok_or(ErrorCode::CannotPerformAction)?;
+        let (owner_amount, renter_amount) =
+            rental.settle_yield(agents, master_agent, current_time)?;
+        if renter_amount > 0 {
+            let renter_user = ctx
+                .accounts
+                .renter_user
+                .as_deref_mut()
+                .ok_or(ErrorCode::CannotPerformAction)?;
+            if renter_user.authority != rental.renter {
+                return Err(ErrorCode::CannotPerformAction);
+            }
+            renter_user.add_unclaimed_yield(renter_amount)?;
+            t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(renter_amount)?;
+
+            emit_cpi!(RentalYieldEvent {
+                agent: agents.key(),
+                renter: rental.renter,
+                amount: renter_amount,
+                timestamp: current_time,
+            });
+        }
+        agents.is_rented = false;
+
+        emit_cpi!(RentalClosedEvent {
+            agent: agents.key(),
+            owner: rental.owner,
+            renter: rental.renter,
+            renter_yield: rental.renter_yield,
+            timestamp: current_time,
+        });
+        owner_amount
+    } else {
+        agents.settle_yield(master_agent, current_time)?
+    };
+
+    // --- Buyback payment ---
+    let price = sunset.record_buyback(current_time)?;
+    let relist_policy = master_agent.record_sunset_buyback(current_time)?;
+
+    if let Some(vault) = vault.as_deref_mut() {
+        // Yield and price are paid out to the share holders through the vault
+        vault.accrue_yield(owner_yield, current_time)?;
+        vault.record_buyback(price, current_time)?;
+    } else if let Some(owner_user) = owner_user.as_deref_mut() {
+        if owner_yield > 0 {
+            owner_user.add_unclaimed_yield(owner_yield)?;
+            t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(owner_yield)?;
+        }
+
+        let owner_y_mint_ta = ctx
+            .accounts
+            .owner_y_mint_ta
+            .as_ref()
+            .ok_or(ErrorCode::CannotPerformAction)?;
+        let mint = try_from!(Account<Mint>, ctx.accounts.y_mint)
+            .map_err(|_| ErrorCode::AccountFromError)?;
+        TYield::transfer_tokens(
+            ctx.accounts.transfer_authority_y_mint_ta.to_account_info(),
+            mint.to_account_info(),
+            owner_y_mint_ta.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            price,
+            mint.decimals,
+        )
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+    }
+    t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_sub(price)?;
+
+    // --- Retire the agent under the relist policy ---
+    let holder_ta = ctx
+        .accounts
+        .owner_agent_ta
+        .as_ref()
+        .filter(|_| ctx.accounts.authority.key() == agents.owner);
+    let close_agent = if vault.is_some() {
+        // The NFT is already in custody and the vault keeps the agent PDA until reassembly
+        false
+    } else if let Some(holder_ta) = holder_ta {
+        if relist_policy == RelistPolicy::Burn {
+            let burn_agent_params = BurnAgentParams {
+                owner: ctx.accounts.authority.to_account_info(),
+                token_account: holder_ta.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                metadata: ctx.accounts.metadata.to_account_info(),
+                master_edition: ctx.accounts.master_edition.to_account_info(),
+                metadata_program: ctx.accounts.metadata_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+            };
+            t_yield
+                .burn_agent(burn_agent_params)
+                .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+            true
+        } else {
+            let transfer_authority_ta = ctx
+                .accounts
+                .transfer_authority_ta
+                .as_ref()
+                .ok_or(ErrorCode::CannotPerformAction)?;
+            let transfer_agent_params = TransferAgentParams {
+                payer: ctx.accounts.authority.to_account_info(),
+                sender_nft_token_account: holder_ta.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+                receiver_token_account: transfer_authority_ta.to_account_info(),
+                receiver: ctx.accounts.transfer_authority.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                metadata: ctx.accounts.metadata.to_account_info(),
+                metadata_program: ctx.accounts.metadata_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+            };
+            t_yield
+                .transfer_agent(transfer_agent_params)
+                .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+            // A sunset master agent never sells again, so the agent is held unlisted
+            agents.return_to_protocol(transfer_authority, None, current_time)?;
+            false
+        }
+    } else {
+        // The NFT cannot leave the wallet without the holder, so only the agent is retired
+        true
+    };
+
+    // --- Update owner state ---
+    if let Some(owner_user) = owner_user {
+        owner_user.remove_agent(1)?;
+        owner_user.validate_user()?;
+    }
+
+    emit_cpi!(SunsetBuybackEvent {
+        master_agent: master_agent.key(),
+        agent: agents.key(),
+        owner: ctx.accounts.owner.key(),
+        caller: ctx.accounts.authority.key(),
+        price,
+        outstanding: sunset.outstanding,
+        relist_policy: relist_policy as u8,
+        timestamp: current_time,
+    });
+
+    if rental_ended {
+        if let Some(rental) = ctx.accounts.rental.as_ref() {
+            rental
+                .close(ctx.accounts.owner.to_account_info())
+                .map_err(|_| ErrorCode::AccountFromError)?;
+        }
+    }
+    if close_agent {
+        ctx.accounts
+            .agent
+            .close(ctx.accounts.owner.to_account_info())
+            .map_err(|_| ErrorCode::AccountFromError)?;
+    }
+
+    Ok(())
+}
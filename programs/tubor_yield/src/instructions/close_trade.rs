@@ -4,7 +4,10 @@ use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, TwapUpdate};
 use crate::{
     error::{ErrorCode, TYieldResult},
     math::SafeMath,
-    state::{trade::Trade, MasterAgent, OraclePrice, TYield},
+    state::{
+        trade::{Trade, TradeEvent, TradeStatus},
+        MasterAgent, OraclePrice, TYield,
+    },
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -14,12 +17,13 @@ pub struct CloseTradeParams {
 
 #[derive(Accounts)]
 pub struct CloseTrade<'info> {
-    /// User closing the trade
+    /// The trade authority or master agent authority closing the trade
     #[account(mut)]
     pub authority: Signer<'info>,
 
-    /// The t_yield config PDA (protocol global state).
+    /// The t_yield config PDA (protocol global state, receives the performance fee).
     #[account(
+        mut,
         seeds = [b"t_yield"],
         bump = t_yield.t_yield_bump
     )]
@@ -28,11 +32,31 @@ pub struct CloseTrade<'info> {
     pub pair_oracle_account: Account<'info, PriceUpdateV2>,
     pub pair_twap_account: Option<Account<'info, TwapUpdate>>,
 
-    #[account(mut)]
+    /// Trade account to close (mut). Must have been opened by `master_agent`.
+    /// PDA: ["trade", master_agent, trade number]
+    #[account(
+        mut,
+        constraint = trade.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::InvalidAccount,
+    )]
     pub trade: Box<Account<'info, Trade>>,
 
-    #[account(mut)]
+    /// Master agent account for the trade (mut).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+    )]
     pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
 }
 
 pub fn close_trade<'info>(
@@ -40,10 +64,14 @@ pub fn close_trade<'info>(
     _params: CloseTradeParams,
 ) -> TYieldResult<u8> {
     let current_time = ctx.accounts.t_yield.get_time()?;
+    let authority = ctx.accounts.authority.key();
     let trade = ctx.accounts.trade.as_mut();
     let master_agent = ctx.accounts.master_agent.as_mut();
 
-    // 1. Check trade is active (ownership enforcement can be added here if needed)
+    // 1. Check the caller may close the trade and it is active
+    if authority != trade.authority && authority != master_agent.authority {
+        return Err(ErrorCode::InvalidAuthority);
+    }
     if !trade.is_active() {
         msg!("Trade is not active (already completed or cancelled)");
         return Err(ErrorCode::CannotPerformAction);
@@ -60,6 +88,7 @@ pub fn close_trade<'info>(
     )
     .map_err(|_| ErrorCode::InvalidOraclePrice)?;
     let current_price = token_price.scale_to_exponent(0)?.price;
+    let t_yield = &mut ctx.accounts.t_yield;
 
     // 3. Complete the trade (set status, result, updated_at)
     let pnl = trade.calculate_pnl_safe(current_price)?;
@@ -72,12 +101,30 @@ pub fn close_trade<'info>(
     trade.complete(result);
     trade.updated_at = current_time;
 
-    // 4. Update master agent stats
+    // 4. Update master agent trade count
     master_agent.completed_trades = master_agent.completed_trades.safe_add(1)?;
-    master_agent.total_pnl = master_agent.total_pnl.safe_add(pnl.unsigned_abs())?;
 
-    // (Optional) Emit event here if needed
-    // emit!(crate::state::trade::TradeEvent { ... });
+    // 5. Route realized PnL to holders, protocol and NAV and record it in the performance ledger
+    let settlement =
+        master_agent.settle_realized_pnl(pnl, t_yield.performance_fee, current_time)?;
+    t_yield.protocol_total_fees = t_yield
+        .protocol_total_fees
+        .safe_add(settlement.protocol_fee)?;
+    t_yield.protocol_total_earnings = t_yield
+        .protocol_total_earnings
+        .safe_add(settlement.protocol_fee)?;
+
+    emit_cpi!(TradeEvent {
+        trade: trade.key(),
+        status: TradeStatus::Completed,
+        trade_type: trade.get_trade_type(),
+        result,
+        pnl,
+        protocol_fee: settlement.protocol_fee,
+        holder_yield: settlement.holder_yield,
+        nav_delta: settlement.nav_delta,
+        created_at: current_time,
+    });
 
     Ok(0)
 }
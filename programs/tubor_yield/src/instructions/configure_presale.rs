@@ -0,0 +1,154 @@
+//! Instruction: Configure Presale
+//!
+//! Configures a presale window for a master agent (with multisig approval). Between `start_time` and
+//! `end_time` whitelisted users buy through `buy_presale_agent` at `whitelist_price`, up to `max_per_user`
+//! agents each. The master agent switches to public mode on the first purchase after the window closes.
+//!
+//! Accounts:
+//! - admin: The signer proposing/signing the presale (must be a multisig signer, pays for the presale account)
+//! - multisig: Protocol multisig PDA (controls admin actions)
+//! - t_yield: Protocol global state/config PDA
+//! - master_agent: Master agent whose supply is sold (mut)
+//! - presale: Presale PDA for the master agent (created if needed)
+//! - system_program: Solana system program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{
+        AdminInstruction, MasterAgent, Multisig, PresaleConfig, PresaleConfiguredEvent, Size,
+        TYield,
+    },
+};
+
+/// Parameters for configuring a presale window.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ConfigurePresaleParams {
+    /// Start of the presale window
+    pub start_time: i64,
+    /// End of the presale window
+    pub end_time: i64,
+    /// Base price of an agent during the presale in y_mint base units
+    pub whitelist_price: u64,
+    /// Maximum number of agents a user can buy during the presale
+    pub max_per_user: u64,
+}
+
+#[derive(Accounts)]
+pub struct ConfigurePresale<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Master agent whose supply is sold.
+    /// Seeds: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent", master_agent.mint.as_ref()],
+        bump = master_agent.bump
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Presale PDA. Reused by later presales of the same master agent.
+    /// Seeds: ["presale", master_agent]
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = PresaleConfig::SIZE,
+        seeds = [b"presale", master_agent.key().as_ref()],
+        bump
+    )]
+    pub presale: Box<Account<'info, PresaleConfig>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn configure_presale<'info>(
+    ctx: Context<'_, '_, '_, 'info, ConfigurePresale<'info>>,
+    params: ConfigurePresaleParams,
+) -> TYieldResult<u8> {
+    let mut multisig = ctx
+        .accounts
+        .multisig
+        .load_mut()
+        .map_err(|_| ErrorCode::InvalidBump)?;
+
+    let instruction_data =
+        Multisig::get_instruction_data(AdminInstruction::ConfigurePresale, &params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let nonce = current_time as u64;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &instruction_data,
+        nonce,
+        current_time,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    if !master_agent.is_active() || master_agent.auction_live || master_agent.is_sunset() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    let presale = ctx.accounts.presale.as_mut();
+    presale.configure(
+        master_agent.key(),
+        params.start_time,
+        params.end_time,
+        params.whitelist_price,
+        params.max_per_user,
+        current_time,
+        ctx.bumps.presale,
+    )?;
+    master_agent.start_presale(params.end_time, current_time)?;
+
+    msg!(
+        "Presale configured from {} to {} at {}",
+        params.start_time,
+        params.end_time,
+        params.whitelist_price
+    );
+
+    emit_cpi!(PresaleConfiguredEvent {
+        master_agent: master_agent.key(),
+        start_time: presale.start_time,
+        end_time: presale.end_time,
+        whitelist_price: presale.whitelist_price,
+        max_per_user: presale.max_per_user,
+    });
+
+    Ok(0)
+}
@@ -0,0 +1,208 @@
+//! Instruction: Crank Trades
+//!
+//! Permissionless keeper crank that checks many trades against their take profit (TP) and stop
+//! loss (SL) in one transaction. Each oracle is read once and its price is shared by every trade on
+//! its feed. Trades that hit TP or SL are completed, their PnL is settled exactly like in
+//! `update_trade`, and one `TradeEvent` is emitted per settled trade. Other trades are skipped.
+//!
+//! Accounts:
+//! - authority: The keeper (anyone can call)
+//! - t_yield: Protocol global state/config PDA (mut, receives the performance fees)
+//! - keeper_user: (Optional) User account PDA of the keeper, receives the keeper reward for the settled trades
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+//!
+//! The price updates, optional TWAP updates and `[trade, master_agent]` pairs are read from
+//! `remaining_accounts` (see `TradeCrankAccounts`). A feed is priced with its TWAP update when one
+//! is passed, so trades settle at the same price as through `update_trade`.
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::{SafeMath, MAX_TRADE_CRANK_SIZE},
+    state::{
+        trade::{TradeCrankAccounts, TradeCrankSummary, TradeEvent, TradeResult, TradeStatus},
+        KeeperAction, KeeperRewardEvent, OraclePrice, TYield, User,
+    },
+};
+
+/// Parameters for cranking a batch of trades.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CrankTradesParams {
+    /// Number of price update accounts, one per feed
+    pub oracle_count: u8,
+    /// Number of TWAP update accounts following the price updates, at most one per feed
+    pub twap_count: u8,
+    /// Number of `[trade, master_agent]` pairs following the price updates
+    pub trade_count: u8,
+}
+
+/// Accounts required for cranking trades.
+///
+/// Price updates, TWAP updates and trades are passed through `remaining_accounts`.
+#[derive(Accounts)]
+pub struct CrankTrades<'info> {
+    /// The keeper (anyone can call this instruction).
+    pub authority: Signer<'info>,
+
+    /// Protocol global state/config PDA (mut, receives the performance fees).
+    /// Seeds: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// User account PDA of the caller. Receives the keeper reward when provided.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = keeper_user.bump,
+    )]
+    pub keeper_user: Option<Box<Account<'info, User>>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Crank Trades
+///
+/// Settles every trade of the batch that has hit take profit or stop loss and returns a summary.
+pub fn crank_trades<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CrankTrades<'info>>,
+    params: CrankTradesParams,
+) -> TYieldResult<TradeCrankSummary> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let trade_count = params.trade_count as usize;
+    if trade_count > MAX_TRADE_CRANK_SIZE {
+        return Err(ErrorCode::InvalidAccount);
+    }
+
+    let mut batch = TradeCrankAccounts::load_batch(
+        ctx.remaining_accounts,
+        params.oracle_count as usize,
+        params.twap_count as usize,
+        trade_count,
+    )?;
+    let t_yield = ctx.accounts.t_yield.as_mut();
+
+    // One price read per feed
+    let mut prices: Vec<([u8; 32], u64)> = Vec::with_capacity(batch.oracles.len());
+    for oracle in batch.oracles.iter() {
+        let feed_id = oracle.price_message.feed_id;
+        let twap = batch.twaps.iter().find(|twap| twap.twap.feed_id == feed_id);
+        let token_price = OraclePrice::new_from_oracle(
+            oracle,
+            twap,
+            &t_yield.oracle_param,
+            current_time,
+            false,
+            feed_id,
+        )
+        .map_err(|_| ErrorCode::InvalidOraclePrice)?;
+        prices.push((feed_id, token_price.scale_to_exponent(0)?.price));
+    }
+
+    let mut summary = TradeCrankSummary {
+        checked: params.trade_count,
+        ..Default::default()
+    };
+    for (trade, index) in batch.trades.iter_mut() {
+        let current_price = match prices.iter().find(|(feed_id, _)| *feed_id == trade.feed_id) {
+            Some((_, price)) if trade.is_active() => *price,
+            _ => {
+                summary.skipped = summary.skipped.safe_add(1)?;
+                continue;
+            }
+        };
+
+        let result = if trade.has_hit_take_profit(current_price) {
+            summary.take_profit = summary.take_profit.safe_add(1)?;
+            TradeResult::Success
+        } else if trade.has_hit_stop_loss(current_price) {
+            summary.stop_loss = summary.stop_loss.safe_add(1)?;
+            TradeResult::Failed
+        } else {
+            summary.skipped = summary.skipped.safe_add(1)?;
+            continue;
+        };
+
+        let pnl = trade.calculate_pnl_safe(current_price)?;
+        trade.complete(result);
+
+        // Update master agent trade count
+        let master_agent = &mut batch.master_agents[*index];
+        master_agent.completed_trades = master_agent.completed_trades.safe_add(1)?;
+
+        // Route realized PnL to holders, protocol and NAV and record it in the performance ledger
+        let settlement =
+            master_agent.settle_realized_pnl(pnl, t_yield.performance_fee, current_time)?;
+        t_yield.protocol_total_fees = t_yield
+            .protocol_total_fees
+            .safe_add(settlement.protocol_fee)?;
+        t_yield.protocol_total_earnings = t_yield
+            .protocol_total_earnings
+            .safe_add(settlement.protocol_fee)?;
+
+        summary.pnl = summary.pnl.safe_add(pnl)?;
+        summary.protocol_fee = summary.protocol_fee.safe_add(settlement.protocol_fee)?;
+
+        emit_cpi!(TradeEvent {
+            trade: trade.key(),
+            status: TradeStatus::Completed,
+            trade_type: trade.get_trade_type(),
+            result,
+            pnl,
+            protocol_fee: settlement.protocol_fee,
+            holder_yield: settlement.holder_yield,
+            nav_delta: settlement.nav_delta,
+            created_at: current_time,
+        });
+    }
+
+    // Remaining accounts are not persisted by Anchor
+    for (trade, _) in batch.trades.iter() {
+        trade
+            .exit(&crate::ID)
+            .map_err(|_| ErrorCode::AccountFromError)?;
+    }
+    for master_agent in batch.master_agents.iter() {
+        master_agent
+            .exit(&crate::ID)
+            .map_err(|_| ErrorCode::AccountFromError)?;
+    }
+
+    // Reward the keeper for the settled trades only
+    let settled = summary.take_profit.safe_add(summary.stop_loss)? as u64;
+    if let Some(keeper_user) = ctx.accounts.keeper_user.as_mut() {
+        let reward = t_yield.reward_keeper(keeper_user, settled, current_time)?;
+        summary.keeper_reward = reward;
+        if reward > 0 {
+            emit_cpi!(KeeperRewardEvent {
+                keeper: keeper_user.authority,
+                action: KeeperAction::TradeSettlement,
+                actions: settled,
+                amount: reward,
+                timestamp: current_time,
+            });
+        }
+    }
+
+    msg!(
+        "Cranked {} trades: {} take profit, {} stop loss, {} skipped",
+        summary.checked,
+        summary.take_profit,
+        summary.stop_loss,
+        summary.skipped
+    );
+
+    Ok(summary)
+}
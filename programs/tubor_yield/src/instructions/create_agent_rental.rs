@@ -0,0 +1,155 @@
+//! Instruction: Create Agent Rental
+//!
+//! Lets an agent owner offer the agent's yield stream to a renter for a fixed term and upfront price.
+//! The owner keeps the agent, but it cannot be transferred, sold or listed until the rental is
+//! cancelled or closed after its term. Agents with an open listing cannot be rented. The rental
+//! starts once the renter accepts and pays.
+//!
+//! Accounts:
+//! - authority: The agent owner (signer, payer)
+//! - user: User account PDA of the owner
+//! - agent: Agent account PDA (the agent being rented)
+//! - owner_agent_ta: Owner's token account holding the agent NFT
+//! - listing: Listing PDA of the agent (must not exist)
+//! - rental: The new rental PDA
+//! - t_yield: Protocol global state/config PDA
+//! - system_program: Solana system program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{Agent, AgentRental, RentalCreatedEvent, Size, TYield, User},
+};
+
+/// Parameters for offering an agent's yield for rent.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateAgentRentalParams {
+    /// User receiving the yield during the term
+    pub renter: Pubkey,
+    /// Upfront payment the renter owes the owner (in Y-mint units)
+    pub price: u64,
+    /// Start of the rental term
+    pub start_time: i64,
+    /// End of the rental term
+    pub end_time: i64,
+}
+
+#[derive(Accounts)]
+pub struct CreateAgentRental<'info> {
+    /// The agent owner. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// User account PDA of the owner.
+    /// PDA: ["user", authority]
+    #[account(
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Agent account PDA (the agent being rented).
+    /// PDA: ["agent", agent.mint]
+    #[account(
+        mut,
+        seeds = [b"agent".as_ref(), agent.mint.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Owner's token account holding the agent NFT. Agents escrowed in a listing cannot be rented.
+    #[account(
+        constraint = owner_agent_ta.mint == agent.mint,
+        constraint = owner_agent_ta.owner == authority.key() @ ErrorCode::InvalidAccount,
+        constraint = owner_agent_ta.amount == 1 @ ErrorCode::CannotPerformAction,
+    )]
+    pub owner_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Listing PDA of the agent. Must be empty: listed agents cannot be rented.
+    /// PDA: ["listing", agent.mint]
+    #[account(
+        seeds = [b"listing".as_ref(), agent.mint.as_ref()],
+        bump,
+        constraint = listing.data_is_empty() @ ErrorCode::CannotPerformAction,
+    )]
+    pub listing: AccountInfo<'info>,
+
+    /// The new rental PDA.
+    /// PDA: ["agent_rental", agent]
+    #[account(
+        init,
+        payer = authority,
+        space = AgentRental::SIZE,
+        seeds = [b"agent_rental".as_ref(), agent.key().as_ref()],
+        bump,
+    )]
+    pub rental: Box<Account<'info, AgentRental>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Create Agent Rental
+///
+/// Records the rental offer and locks the agent against transfers and sales.
+pub fn create_agent_rental<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateAgentRental<'info>>,
+    params: CreateAgentRentalParams,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let user = &ctx.accounts.user;
+    let agent = ctx.accounts.agent.as_mut();
+    let rental = ctx.accounts.rental.as_mut();
+
+    if !user.can_perform_actions() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if !agent.is_owned_by(&user.authority) || !agent.is_transferable() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    rental.initialize(
+        agent.key(),
+        user.authority,
+        params.renter,
+        params.price,
+        params.start_time,
+        params.end_time,
+        current_time,
+        ctx.bumps.rental,
+    )?;
+
+    agent.is_rented = true;
+    agent.last_updated = current_time;
+
+    emit_cpi!(RentalCreatedEvent {
+        agent: agent.key(),
+        owner: user.authority,
+        renter: params.renter,
+        price: params.price,
+        start_time: params.start_time,
+        end_time: params.end_time,
+    });
+
+    Ok(0)
+}
@@ -0,0 +1,125 @@
+//! Instruction: Create Referral Code
+//!
+//! Reserves a short, human-readable referral code for a registered user. The code PDA points at the
+//! user's `ReferralRegistry` (created if needed) and can be passed to `register_user` instead of a pubkey.
+//!
+//! Accounts:
+//! - authority: The referrer reserving the code (signer, payer)
+//! - user: User account PDA of the referrer
+//! - referral_registry: Referral registry PDA of the referrer (created if needed)
+//! - referral_code: The new referral code PDA
+//! - t_yield: Protocol global state/config
+//! - event_authority: Used for event emission
+//! - system_program: Solana system program
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{ReferralCode, ReferralCodeEvent, ReferralRegistry, Size, TYield, User},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CreateReferralCodeParams {
+    /// Referral code (4-16 characters of a-z, 0-9 and _)
+    pub code: String,
+}
+
+#[derive(Accounts)]
+#[instruction(params: CreateReferralCodeParams)]
+pub struct CreateReferralCode<'info> {
+    /// The referrer reserving the code. Pays for account creation.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// User account PDA of the referrer.
+    /// Seeds: ["user", authority]
+    #[account(
+        seeds = [b"user", authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Referral registry of the referrer.
+    /// Seeds: ["referral_registry", authority]
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = ReferralRegistry::SIZE,
+        seeds = [b"referral_registry", authority.key().as_ref()],
+        bump
+    )]
+    pub referral_registry: Box<Account<'info, ReferralRegistry>>,
+
+    /// The referral code PDA. Fails if the code is already taken.
+    /// Seeds: ["ref_code", code]
+    #[account(
+        init,
+        payer = authority,
+        space = ReferralCode::SIZE,
+        seeds = [b"ref_code", params.code.as_bytes()],
+        bump
+    )]
+    pub referral_code: Box<Account<'info, ReferralCode>>,
+
+    /// The t_yield config PDA (protocol global state).
+    /// Seeds: ["t_yield"]
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+
+    /// The Solana system program.
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_referral_code(
+    ctx: Context<CreateReferralCode>,
+    params: CreateReferralCodeParams,
+) -> TYieldResult<()> {
+    if !ctx.accounts.user.can_perform_actions() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let authority = ctx.accounts.authority.key();
+
+    // Initialize referral registry if it's new
+    let referral_registry = &mut ctx.accounts.referral_registry;
+    if referral_registry.referrer == Pubkey::default() {
+        referral_registry.referrer = authority;
+        referral_registry.created_at = current_time;
+        referral_registry.updated_at = current_time;
+        referral_registry.bump = ctx.bumps.referral_registry;
+    }
+
+    let referral_code = &mut ctx.accounts.referral_code;
+    referral_code.initialize(
+        &params.code,
+        authority,
+        referral_registry.key(),
+        current_time,
+        ctx.bumps.referral_code,
+    )?;
+
+    msg!("Referral code {} created for {}", params.code, authority);
+
+    emit_cpi!(ReferralCodeEvent {
+        referrer: authority,
+        code: referral_code.code,
+        revoked: false,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,290 @@
+//! Instruction: Fractionalize Agent
+//!
+//! Locks an agent NFT in protocol custody behind a vault PDA and mints a fixed supply of SPL
+//! share tokens to its owner. The owner's pending yield is settled first; from then on the agent's
+//! yield accrues to the vault and is split pro-rata over the full share supply. Staked shares earn
+//! their part; the part of unstaked shares is retained by the vault and paid to the caller of
+//! `reassemble_agent`, which burns every share and unlocks the NFT again.
+//!
+//! Accounts:
+//! - authority: The owner fractionalizing the agent (signer)
+//! - system_program: Solana system program
+//! - token_program: SPL token program
+//! - associated_token_program: SPL associated token program
+//! - sysvar_instructions: Instructions sysvar (for Metaplex CPI)
+//! - metadata_program: Metaplex token metadata program
+//! - metadata: Metadata account for the agent NFT (Metaplex)
+//! - user: User account PDA
+//! - agent: Agent account PDA (the agent being fractionalized)
+//! - master_agent: Master agent account PDA (parent/master of the agent)
+//! - mint: Mint account for the agent NFT
+//! - agent_vault: Vault PDA created for the agent
+//! - share_mint: Share mint PDA created for the agent
+//! - t_yield: Protocol global state/config PDA
+//! - transfer_authority: Transfer authority PDA (custodian of the NFT and share mint authority)
+//! - transfer_authority_ta: Protocol's token account receiving the agent NFT
+//! - user_agent_ta: User's token account holding the agent NFT (sender)
+//! - user_share_ta: User's token account receiving the shares
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{
+        Agent, AgentVault, FractionalizeAgentEvent, MasterAgent, Size, TYield, TransferAgentParams,
+        User, UserStatus,
+    },
+};
+
+/// Parameters for fractionalizing an agent.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct FractionalizeAgentParams {
+    /// Number of shares minted for the agent (fixed supply, no decimals)
+    pub total_shares: u64,
+}
+
+#[derive(Accounts)]
+pub struct FractionalizeAgent<'info> {
+    /// The owner fractionalizing the agent. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Instructions sysvar (required for Metaplex CPI).
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+
+    /// CHECK: Metaplex token metadata program.
+    #[account(address = METADATA_PROGRAM_ID)]
+    pub metadata_program: AccountInfo<'info>,
+
+    /// CHECK: Metadata account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    // --- Protocol/User/Agent State ---
+    /// User account PDA.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Agent account PDA (the agent being fractionalized).
+    /// PDA: ["agent", mint]
+    #[account(
+        mut,
+        seeds = [b"agent".as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Master agent account PDA (parent/master of the agent).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+        constraint = agent.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Mint account for the agent NFT.
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// Vault PDA owning the agent while it is fractionalized.
+    /// PDA: ["agent_vault", agent]
+    #[account(
+        init,
+        payer = authority,
+        space = AgentVault::SIZE,
+        seeds = [b"agent_vault".as_ref(), agent.key().as_ref()],
+        bump,
+    )]
+    pub agent_vault: Box<Account<'info, AgentVault>>,
+
+    /// Share mint of the agent. Minted once; the supply never changes until reassembly.
+    /// PDA: ["share_mint", agent]
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"share_mint".as_ref(), agent.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = transfer_authority,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// CHECK: Transfer authority PDA (custodian of the NFT and share mint authority).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    // --- Token Accounts ---
+    /// Protocol's token account to receive the agent NFT.
+    /// Must have mint == agent mint.
+    #[account(
+        mut,
+        constraint = transfer_authority_ta.mint == mint.key()
+    )]
+    pub transfer_authority_ta: Box<Account<'info, TokenAccount>>,
+
+    /// User's token account holding the agent NFT (sender).
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = authority,
+    )]
+    pub user_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// User's token account receiving the shares.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = share_mint,
+        associated_token::authority = authority,
+    )]
+    pub user_share_ta: Box<Account<'info, TokenAccount>>,
+
+    // --- Misc ---
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Fractionalize Agent
+///
+/// Settles the owner's pending yield, moves the agent NFT into protocol custody under the new
+/// vault and mints `params.total_shares` shares to the owner.
+pub fn fractionalize_agent<'info>(
+    ctx: Context<'_, '_, '_, 'info, FractionalizeAgent<'info>>,
+    params: FractionalizeAgentParams,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let user = ctx.accounts.user.as_mut();
+    let agent = ctx.accounts.agent.as_mut();
+    let agent_vault = ctx.accounts.agent_vault.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+
+    if user.has_status(UserStatus::Banned) {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    if !agent.is_owned_by(&ctx.accounts.authority.key()) || !agent.is_transferable() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    // Holders of a sunset master agent exit through `claim_sunset_buyback`
+    if master_agent.is_sunset() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    // Settle yield earned by the owner before the vault starts earning
+    let accrued_yield = agent.settle_yield(master_agent, current_time)?;
+    if accrued_yield > 0 {
+        user.add_unclaimed_yield(accrued_yield)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(accrued_yield)?;
+    }
+
+    agent_vault.initialize(
+        agent.key(),
+        ctx.accounts.share_mint.key(),
+        ctx.accounts.authority.key(),
+        params.total_shares,
+        current_time,
+        ctx.bumps.agent_vault,
+    )?;
+
+    // --- Lock the agent NFT ---
+    let transfer_agent_params = TransferAgentParams {
+        payer: ctx.accounts.authority.to_account_info(),
+        sender_nft_token_account: ctx.accounts.user_agent_ta.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+        receiver_token_account: ctx.accounts.transfer_authority_ta.to_account_info(),
+        receiver: ctx.accounts.transfer_authority.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+        metadata_program: ctx.accounts.metadata_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+    };
+
+    t_yield
+        .transfer_agent(transfer_agent_params)
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    agent.transfer_ownership(agent_vault.key(), current_time)?;
+
+    // --- Mint the shares ---
+    TYield::mint_tokens(
+        ctx.accounts.share_mint.to_account_info(),
+        ctx.accounts.user_share_ta.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        params.total_shares,
+        ctx.accounts.share_mint.decimals,
+    )
+    .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    user.remove_agent(1)?;
+    user.update_last_activity(current_time)?;
+    user.validate_user()?;
+
+    emit_cpi!(FractionalizeAgentEvent {
+        agent: agent.key(),
+        vault: agent_vault.key(),
+        share_mint: ctx.accounts.share_mint.key(),
+        owner: ctx.accounts.authority.key(),
+        total_shares: params.total_shares,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -0,0 +1,125 @@
+//! Instruction: Fund Keeper Rewards
+//!
+//! Sets withdrawable protocol fees aside for keeper rewards (with multisig approval).
+//! The tokens stay in the protocol token account; the funded amount stops counting as
+//! withdrawable fees and is reserved until paid out to keepers.
+//! Emits a `FundKeeperRewardsEvent` on success.
+//!
+//! Accounts:
+//! - admin: The signer proposing/signing the funding (must be a multisig signer)
+//! - multisig: Protocol multisig PDA (controls admin actions)
+//! - t_yield: Protocol global state/config PDA (mut, tracks the keeper reward pool)
+//! - transfer_authority: PDA that owns the protocol token account
+//! - protocol_token_account: Protocol token account holding the fees
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{AdminInstruction, FundKeeperRewardsEvent, Multisig, TYield},
+};
+
+/// Parameters for funding the keeper reward pool.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct FundKeeperRewardsParams {
+    pub amount: Option<u64>, // None = fund with all withdrawable fees
+}
+
+#[derive(Accounts)]
+pub struct FundKeeperRewards<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        constraint = protocol_token_account.mint == t_yield.y_mint,
+        constraint = protocol_token_account.owner == transfer_authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub protocol_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn fund_keeper_rewards<'info>(
+    ctx: Context<'_, '_, '_, 'info, FundKeeperRewards<'info>>,
+    params: FundKeeperRewardsParams,
+) -> TYieldResult<u8> {
+    let mut multisig = ctx
+        .accounts
+        .multisig
+        .load_mut()
+        .map_err(|_| ErrorCode::InvalidBump)?;
+
+    let instruction_data =
+        Multisig::get_instruction_data(AdminInstruction::FundKeeperRewards, &params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let nonce = current_time as u64;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &instruction_data,
+        nonce,
+        current_time,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    let t_yield = ctx.accounts.t_yield.as_mut();
+    let vault_balance = ctx.accounts.protocol_token_account.amount;
+
+    let amount = match params.amount {
+        Some(a) => a,
+        None => t_yield.get_withdrawable_fees(vault_balance)?,
+    };
+    t_yield.fund_keeper_rewards(amount, vault_balance)?;
+
+    msg!(
+        "Moved {} in protocol fees to the keeper reward pool",
+        amount
+    );
+
+    emit_cpi!(FundKeeperRewardsEvent {
+        amount,
+        keeper_reward_pool: t_yield.keeper_reward_pool,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
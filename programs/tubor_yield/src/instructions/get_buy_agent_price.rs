@@ -1,6 +1,6 @@
 //! Instruction: Get Buy Agent Price
 //!
-//! Returns the price (including tax) to buy an agent NFT from a master agent.
+//! Returns the price (including tax) to buy one or more agent NFTs from a master agent.
 //! This is a read-only query; no state is mutated.
 //!
 //! Accounts:
@@ -14,10 +14,17 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::Mint;
 
 use crate::{
-    error::TYieldResult,
+    error::{ErrorCode, TYieldResult},
     state::{Agent, AgentPrice, MasterAgent, TYield},
 };
 
+/// Parameters for querying the buy price.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct GetBuyAgentPriceParams {
+    /// Number of agents to quote (prices follow the master agent's pricing curve)
+    pub count: u64,
+}
+
 /// Accounts required to query the buy price for an agent NFT from a master agent.
 ///
 /// This instruction does not mutate any state and can be called by anyone.
@@ -58,14 +65,22 @@ pub struct GetBuyAgentPrice<'info> {
 ///
 /// # Arguments
 /// * `ctx` - Context with the required accounts.
+/// * `params` - Number of agents to quote.
 ///
 /// # Returns
-/// * `AgentPrice` - Struct containing total price, tax amount, and base price.
-pub fn get_buy_agent_price(ctx: Context<GetBuyAgentPrice>) -> TYieldResult<AgentPrice> {
+/// * `AgentPrice` - Struct containing total price, tax amount, and base price for buying `count` agents.
+pub fn get_buy_agent_price(
+    ctx: Context<GetBuyAgentPrice>,
+    params: GetBuyAgentPriceParams,
+) -> TYieldResult<AgentPrice> {
     let master_agent = ctx.accounts.master_agent.as_ref();
     let _t_yield = ctx.accounts.t_yield.as_ref();
 
-    let result = master_agent.calculate_buy_price_with_tax()?;
+    if params.count == 0 {
+        return Err(ErrorCode::MathError);
+    }
+
+    let result = master_agent.calculate_buy_quote(params.count)?;
 
     Ok(AgentPrice {
         total_price: result.0,
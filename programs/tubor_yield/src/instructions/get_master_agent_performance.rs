@@ -0,0 +1,53 @@
+//! Instruction: Get Master Agent Performance
+//!
+//! Returns the realized trading performance of a master agent: gross profit and loss, net signed
+//! PnL, win and loss counts, largest win and loss, peak equity and drawdowns, together with its
+//! value locked and yield statistics.
+//! This is a read-only query; no state is mutated.
+//!
+//! Accounts:
+//! - Master agent account (PDA: ["master_agent", master_agent.mint])
+//! - Protocol global state (t_yield, PDA: ["t_yield"])
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::TYieldResult,
+    state::{MasterAgent, MasterAgentPerformance, TYield},
+};
+
+/// Accounts required to query the performance of a master agent.
+///
+/// This instruction does not mutate any state and can be called by anyone.
+#[derive(Accounts)]
+pub struct GetMasterAgentPerformance<'info> {
+    /// Master agent account being queried.
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Protocol global state.
+    /// PDA: ["t_yield"]
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+}
+
+/// Returns the performance report of a master agent.
+///
+/// # Arguments
+/// * `ctx` - Context with the required accounts.
+///
+/// # Returns
+/// * `MasterAgentPerformance` - Realized PnL ledger and derived performance statistics.
+pub fn get_master_agent_performance(
+    ctx: Context<GetMasterAgentPerformance>,
+) -> TYieldResult<MasterAgentPerformance> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    ctx.accounts.master_agent.get_performance(current_time)
+}
@@ -7,7 +7,7 @@
 //! - Protocol global state (t_yield, PDA: ["t_yield"])
 //! - Oracle price account (PDA: per Pyth)
 //! - Optional TWAP account (PDA: per Pyth)
-//! - Trade account (PDA: ["trade", ...])
+//! - Trade account (PDA: ["trade", master_agent, ...])
 //! - Master agent account (PDA: ["master_agent", ...])
 
 use anchor_lang::prelude::*;
@@ -43,7 +43,7 @@ pub struct GetPairPrice<'info> {
     /// Optional TWAP (Time-Weighted Average Price) account for the trading pair.
     pub pair_twap_account: Option<Account<'info, TwapUpdate>>,
 
-    /// Trade account for the current trade (PDA: ["trade", ...]).
+    /// Trade account for the current trade (PDA: ["trade", master_agent, ...]).
     #[account()]
     pub trade: Box<Account<'info, Trade>>,
 
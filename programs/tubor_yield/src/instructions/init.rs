@@ -14,14 +14,17 @@
 //! - System, Token, Associated Token programs
 //! - Remaining: 1 to Multisig::MAX_SIGNERS admin signers (read-only, unsigned)
 
-use crate::{error::TYieldResult, program::Tuboryield, state::InitProtocolEvent};
+use crate::{
+    error::TYieldResult, math::PERCENTAGE_PRECISION_U64, program::Tuboryield,
+    state::InitProtocolEvent,
+};
 use anchor_spl::{
     associated_token::AssociatedToken,
     token::{Token, TokenAccount},
 };
 
 use {
-    crate::state::{Multisig, Size, TYield},
+    crate::state::{Multisig, Size, TYield, MAX_REFERRAL_DEPTH},
     anchor_lang::prelude::*,
 };
 
@@ -46,8 +49,14 @@ pub struct InitParams {
     pub max_tax_percentage: u64,
     /// Referral earnings percentage.
     pub ref_earn_percentage: u64,
+    /// Referral share for levels 2..=MAX_REFERRAL_DEPTH (optional, default 0).
+    pub ref_tier_percentages: Option<[u64; MAX_REFERRAL_DEPTH - 1]>,
+    /// Performance fee on realized trade profits (optional, default 0).
+    pub performance_fee: Option<u64>,
     /// Supported SPL token mint for protocol operations.
     pub supported_mint: Pubkey,
+    /// Owner of the treasury token account for protocol fees (optional, default upgrade authority).
+    pub treasury: Option<Pubkey>,
     /// Whether protocol is paused at initialization (optional, default false)
     pub paused: Option<bool>,
     /// Initial circuit breaker state (optional)
@@ -195,11 +204,23 @@ pub fn init(ctx: Context<Init>, params: &InitParams) -> TYieldResult<()> {
     t_yield.t_yield_bump = ctx.bumps.t_yield;
 
     t_yield.y_mint = ctx.accounts.supported_mint.key();
+    t_yield.treasury = params
+        .treasury
+        .unwrap_or_else(|| ctx.accounts.upgrade_authority.key());
 
     t_yield.buy_tax = params.buy_tax;
     t_yield.sell_tax = params.sell_tax;
     t_yield.max_tax_percentage = params.max_tax_percentage;
     t_yield.ref_earn_percentage = params.ref_earn_percentage;
+    t_yield.ref_tier_percentages = params.ref_tier_percentages.unwrap_or_default();
+    TYield::validate_referral_percentages(
+        t_yield.ref_earn_percentage,
+        &t_yield.ref_tier_percentages,
+    )?;
+    t_yield.performance_fee = params.performance_fee.unwrap_or(0);
+    if t_yield.performance_fee > PERCENTAGE_PRECISION_U64 {
+        return Err(crate::error::ErrorCode::MathError);
+    }
 
     t_yield.inception_time = t_yield.get_time()?;
 
@@ -0,0 +1,224 @@
+//! Instruction: List Agent
+//!
+//! Lists an agent for peer-to-peer sale at an ask price. The agent NFT is escrowed in the
+//! transfer authority's token account until the listing is filled or cancelled.
+//! The seller stays the agent owner (and keeps earning yield) while the listing is open.
+//!
+//! Accounts:
+//! - authority: The agent owner creating the listing (signer, payer)
+//! - user: User account PDA of the seller
+//! - agent: Agent account PDA (the agent being listed)
+//! - master_agent: Master agent account PDA (parent/master of the agent)
+//! - listing: The new listing PDA
+//! - mint: Mint account for the agent NFT
+//! - metadata: Metadata account for the agent NFT (Metaplex)
+//! - seller_agent_ta: Seller's token account holding the agent NFT
+//! - transfer_authority: Transfer authority PDA (escrow owner)
+//! - escrow_agent_ta: Transfer authority token account for the agent NFT (created if needed)
+//! - t_yield: Protocol global state/config PDA
+//! - system_program, token_program, associated_token_program: Solana programs
+//! - sysvar_instructions: Instructions sysvar (for Metaplex CPI)
+//! - metadata_program: Metaplex token metadata program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{
+        Agent, AgentListing, ListAgentEvent, MasterAgent, Size, TYield, TransferAgentParams, User,
+    },
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ListAgentParams {
+    /// Ask price in y_mint base units
+    pub price: u64,
+}
+
+#[derive(Accounts)]
+pub struct ListAgent<'info> {
+    /// The current owner of the agent. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// User account PDA of the seller.
+    /// PDA: ["user", authority]
+    #[account(
+        seeds = [b"user", authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Agent account PDA (the agent being listed).
+    /// PDA: ["agent", mint]
+    #[account(
+        seeds = [b"agent".as_ref(), mint.key().as_ref()],
+        bump = agent.bump
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Master agent account PDA (the parent/master of the agent).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+        constraint = agent.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// The listing PDA. Only one listing can exist per agent.
+    /// PDA: ["listing", mint]
+    #[account(
+        init,
+        payer = authority,
+        space = AgentListing::SIZE,
+        seeds = [b"listing".as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub listing: Box<Account<'info, AgentListing>>,
+
+    /// Mint account for the agent NFT.
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Metadata account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    /// Seller's token account holding the agent NFT.
+    #[account(
+        mut,
+        constraint = seller_agent_ta.mint == mint.key(),
+        constraint = seller_agent_ta.owner == authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub seller_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Transfer authority PDA (owner of the escrow token account).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    /// Escrow token account for the agent NFT.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = transfer_authority,
+    )]
+    pub escrow_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Instructions sysvar (required for Metaplex CPI).
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+
+    /// CHECK: Metaplex token metadata program.
+    #[account(address = METADATA_PROGRAM_ID)]
+    pub metadata_program: AccountInfo<'info>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: List Agent
+///
+/// Creates a listing at `params.price` and moves the agent NFT into escrow.
+pub fn list_agent<'info>(
+    ctx: Context<'_, '_, '_, 'info, ListAgent<'info>>,
+    params: ListAgentParams,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let user = &ctx.accounts.user;
+    let agent = &ctx.accounts.agent;
+    let master_agent = &ctx.accounts.master_agent;
+
+    // 1. Access control checks
+    if !user.can_perform_actions() || !master_agent.can_perform_actions() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if !agent.is_owned_by(&user.authority) || !agent.is_transferable() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    // 2. Escrow the agent NFT with the transfer authority
+    let transfer_agent_params = TransferAgentParams {
+        payer: ctx.accounts.authority.to_account_info(),
+        sender_nft_token_account: ctx.accounts.seller_agent_ta.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+        receiver_token_account: ctx.accounts.escrow_agent_ta.to_account_info(),
+        receiver: ctx.accounts.transfer_authority.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+        metadata_program: ctx.accounts.metadata_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+    };
+    ctx.accounts
+        .t_yield
+        .transfer_agent(transfer_agent_params)
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    // 3. Record the listing
+    let listing = ctx.accounts.listing.as_mut();
+    listing.initialize(
+        agent.key(),
+        ctx.accounts.mint.key(),
+        user.authority,
+        master_agent.key(),
+        params.price,
+        current_time,
+        ctx.bumps.listing,
+    )?;
+
+    emit_cpi!(ListAgentEvent {
+        agent: agent.key(),
+        seller: user.authority,
+        master_agent: master_agent.key(),
+        price: params.price,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -0,0 +1,90 @@
+//! Instruction: Migrate Agent
+//!
+//! Grows an agent account created before the yield accrual, rental and relist fields were added to
+//! the current `Agent` layout. The appended fields start zeroed and yield accrues to the owner from
+//! the migration on. The master agent must be migrated first. Can be called by anyone; the payer
+//! funds the extra rent.
+//!
+//! Accounts:
+//! - payer: Funds the extra rent (signer)
+//! - agent: Agent account with the legacy layout (mut)
+//! - master_agent: Master agent account PDA the agent belongs to (mut)
+//! - system_program: Solana system program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{AccountMigratedEvent, Agent, MasterAgent, Size, TYield},
+};
+
+#[derive(Accounts)]
+pub struct MigrateAgent<'info> {
+    /// Funds the extra rent. Must sign the transaction.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Agent with the legacy layout, validated by owner and discriminator on load.
+    #[account(mut)]
+    pub agent: UncheckedAccount<'info>,
+
+    /// Master agent account PDA the agent belongs to.
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Migrate Agent
+///
+/// Reallocates the agent to `Agent::SIZE` and initializes the appended fields.
+pub fn migrate_agent<'info>(
+    ctx: Context<'_, '_, '_, 'info, MigrateAgent<'info>>,
+) -> TYieldResult<u8> {
+    let agent_info = ctx.accounts.agent.to_account_info();
+    let old_size = agent_info.data_len();
+
+    let mut agent = TYield::load_legacy_account::<Agent>(
+        ctx.accounts.payer.to_account_info(),
+        agent_info.clone(),
+        ctx.accounts.system_program.to_account_info(),
+        Agent::SIZE,
+    )?;
+
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    if agent.master_agent != master_agent.key() {
+        return Err(ErrorCode::InvalidAccount);
+    }
+
+    let current_time = Clock::get()
+        .map_err(|_| ErrorCode::MathError)?
+        .unix_timestamp;
+    agent.migrate(master_agent, current_time)?;
+    TYield::store_migrated_account(&agent, &agent_info)?;
+
+    emit_cpi!(AccountMigratedEvent {
+        account: agent_info.key(),
+        payer: ctx.accounts.payer.key(),
+        old_size: old_size as u64,
+        new_size: Agent::SIZE as u64,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
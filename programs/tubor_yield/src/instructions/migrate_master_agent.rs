@@ -0,0 +1,132 @@
+//! Instruction: Migrate Master Agent
+//!
+//! Grows a master agent account created before the whitelist root, performance ledger, pricing
+//! curve, NAV, yield index, presale, relist and royalty fields were added to the current
+//! `MasterAgent` layout (with multisig approval). The holder count, NAV and royalty cannot be
+//! derived on-chain and are supplied by the admins; every other appended field starts zeroed. The
+//! protocol state must be migrated first.
+//!
+//! Accounts:
+//! - admin: The signer proposing/signing the migration (must be a multisig signer), funds the extra rent
+//! - multisig: Protocol multisig PDA (controls admin actions)
+//! - t_yield: Protocol global state/config PDA
+//! - master_agent: Master agent account with the legacy layout (mut)
+//! - system_program: Solana system program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{AccountMigratedEvent, AdminInstruction, MasterAgent, Multisig, Size, TYield},
+};
+
+/// Parameters for migrating a master agent.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MigrateMasterAgentParams {
+    /// Agents currently held by users
+    pub held_agents: u64,
+    /// Holder capital backing the held agents
+    /// QUOTE PRECISION
+    pub nav: u64,
+    /// Royalty recorded in the agent NFTs' metadata
+    /// PRECISION: PERCENTAGE_PRECISION
+    pub seller_fee_basis_points: u16,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMasterAgent<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// CHECK: Master agent with the legacy layout, validated by owner and discriminator on load.
+    #[account(mut)]
+    pub master_agent: UncheckedAccount<'info>,
+
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn migrate_master_agent<'info>(
+    ctx: Context<'_, '_, '_, 'info, MigrateMasterAgent<'info>>,
+    params: MigrateMasterAgentParams,
+) -> TYieldResult<u8> {
+    let mut multisig = ctx
+        .accounts
+        .multisig
+        .load_mut()
+        .map_err(|_| ErrorCode::InvalidBump)?;
+
+    let instruction_data =
+        Multisig::get_instruction_data(AdminInstruction::MigrateMasterAgent, &params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let nonce = current_time as u64;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &instruction_data,
+        nonce,
+        current_time,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    let master_agent_info = ctx.accounts.master_agent.to_account_info();
+    let old_size = master_agent_info.data_len();
+
+    let mut master_agent = TYield::load_legacy_account::<MasterAgent>(
+        ctx.accounts.admin.to_account_info(),
+        master_agent_info.clone(),
+        ctx.accounts.system_program.to_account_info(),
+        MasterAgent::SIZE,
+    )?;
+    master_agent.migrate(
+        params.held_agents,
+        params.nav,
+        params.seller_fee_basis_points,
+        current_time,
+    )?;
+    TYield::store_migrated_account(&master_agent, &master_agent_info)?;
+
+    msg!("Master agent {} migrated", master_agent_info.key());
+
+    emit_cpi!(AccountMigratedEvent {
+        account: master_agent_info.key(),
+        payer: ctx.accounts.admin.key(),
+        old_size: old_size as u64,
+        new_size: MasterAgent::SIZE as u64,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
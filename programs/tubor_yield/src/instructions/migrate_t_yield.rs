@@ -0,0 +1,120 @@
+//! Instruction: Migrate TYield
+//!
+//! Grows the protocol state created before the treasury, referral tier, performance fee and
+//! keeper reward fields were added to the current `TYield` layout (with multisig approval). The
+//! treasury is supplied by the admins; referral tiers, the performance fee and keeper rewards stay
+//! disabled until configured through `update_protocol_config`. Must run before any master agent is
+//! migrated.
+//!
+//! Accounts:
+//! - admin: The signer proposing/signing the migration (must be a multisig signer), funds the extra rent
+//! - multisig: Protocol multisig PDA (controls admin actions)
+//! - t_yield: Protocol global state/config PDA with the legacy layout (mut)
+//! - system_program: Solana system program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{AccountMigratedEvent, AdminInstruction, Multisig, Size, TYield},
+};
+
+/// Parameters for migrating the protocol state.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MigrateTYieldParams {
+    /// Owner of the treasury token account for protocol fees
+    pub treasury: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct MigrateTYield<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    /// CHECK: Protocol state with the legacy layout, validated by seeds, owner and discriminator.
+    /// Seeds: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump
+    )]
+    pub t_yield: UncheckedAccount<'info>,
+
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn migrate_t_yield<'info>(
+    ctx: Context<'_, '_, '_, 'info, MigrateTYield<'info>>,
+    params: MigrateTYieldParams,
+) -> TYieldResult<u8> {
+    let mut multisig = ctx
+        .accounts
+        .multisig
+        .load_mut()
+        .map_err(|_| ErrorCode::InvalidBump)?;
+
+    let instruction_data = Multisig::get_instruction_data(AdminInstruction::MigrateTYield, &params)
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    let current_time = Clock::get()
+        .map_err(|_| ErrorCode::MathError)?
+        .unix_timestamp;
+    let nonce = current_time as u64;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &instruction_data,
+        nonce,
+        current_time,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    let t_yield_info = ctx.accounts.t_yield.to_account_info();
+    let old_size = t_yield_info.data_len();
+
+    let mut t_yield = TYield::load_legacy_account::<TYield>(
+        ctx.accounts.admin.to_account_info(),
+        t_yield_info.clone(),
+        ctx.accounts.system_program.to_account_info(),
+        TYield::SIZE,
+    )?;
+    t_yield.migrate(params.treasury, current_time)?;
+    TYield::store_migrated_account(&t_yield, &t_yield_info)?;
+
+    msg!("Protocol state migrated, treasury: {}", params.treasury);
+
+    emit_cpi!(AccountMigratedEvent {
+        account: t_yield_info.key(),
+        payer: ctx.accounts.admin.key(),
+        old_size: old_size as u64,
+        new_size: TYield::SIZE as u64,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -0,0 +1,114 @@
+//! Instruction: Migrate Trade
+//!
+//! Moves a trade opened before trade PDAs were namespaced by master agent from its legacy address
+//! `["trade", trade number]` to `["trade", master_agent, trade number]`. The trade number is kept,
+//! so it stays within the master agent's own numbering and cannot collide with trades opened later.
+//! Can be called by anyone; the legacy account is closed and its rent returned to the trade authority.
+//!
+//! Accounts:
+//! - payer: Pays for the new trade account (signer)
+//! - trade_authority: Authority recorded on the trade, receives the legacy account rent
+//! - master_agent: Master agent account PDA that opened the trade
+//! - legacy_trade: Trade at the legacy address (closed)
+//! - trade: Trade at the namespaced address (created)
+//! - system_program: Solana system program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{
+        trade::{Trade, TradeMigratedEvent},
+        MasterAgent, Size,
+    },
+};
+
+/// Parameters for migrating a legacy trade.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MigrateTradeParams {
+    /// Number the trade was opened with (the master agent's trade count after opening it)
+    pub trade_number: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: MigrateTradeParams)]
+pub struct MigrateTrade<'info> {
+    /// Pays for the new trade account. Must sign the transaction.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Authority recorded on the trade, validated against it. Receives the legacy account rent.
+    #[account(
+        mut,
+        address = legacy_trade.authority
+    )]
+    pub trade_authority: AccountInfo<'info>,
+
+    /// Master agent account PDA that opened the trade.
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+        constraint = params.trade_number <= master_agent.trade_count @ ErrorCode::InvalidAccount,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Trade at the legacy address. Closed to the trade authority.
+    /// PDA: ["trade", trade number]
+    #[account(
+        mut,
+        seeds = [b"trade".as_ref(), params.trade_number.to_le_bytes().as_ref()],
+        bump = legacy_trade.bump,
+        constraint = legacy_trade.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::InvalidAccount,
+        close = trade_authority,
+    )]
+    pub legacy_trade: Box<Account<'info, Trade>>,
+
+    /// Trade at the namespaced address.
+    /// PDA: ["trade", master_agent, trade number]
+    #[account(
+        init,
+        payer = payer,
+        space = Trade::SIZE,
+        seeds = [
+            b"trade".as_ref(),
+            master_agent.key().as_ref(),
+            params.trade_number.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub trade: Box<Account<'info, Trade>>,
+
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Migrate Trade
+///
+/// Copies the legacy trade to its namespaced address and closes the legacy account.
+pub fn migrate_trade<'info>(
+    ctx: Context<'_, '_, '_, 'info, MigrateTrade<'info>>,
+    params: MigrateTradeParams,
+) -> TYieldResult<u8> {
+    let trade = ctx.accounts.trade.as_mut();
+    trade.migrate_from(&ctx.accounts.legacy_trade, ctx.bumps.trade);
+
+    emit_cpi!(TradeMigratedEvent {
+        legacy_trade: ctx.accounts.legacy_trade.key(),
+        trade: trade.key(),
+        master_agent: trade.master_agent,
+        trade_number: params.trade_number,
+    });
+
+    Ok(0)
+}
@@ -27,6 +27,7 @@ use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
 
 use crate::{
     error::{ErrorCode, TYieldResult},
+    math::PERCENTAGE_PRECISION_U64,
     state::{AdminInstruction, Agent, MasterAgent, MintAgentEvent, Multisig, Size, TYield},
 };
 
@@ -226,8 +227,8 @@ pub fn mint_agent<'info>(
         return Err(ErrorCode::CannotPerformAction);
     }
 
-    // Validate master agent has available supply
-    if ctx.accounts.master_agent.is_supply_full() {
+    // Validate master agent has available supply and is not being wound down
+    if ctx.accounts.master_agent.is_supply_full() || ctx.accounts.master_agent.is_sunset() {
         return Err(ErrorCode::CannotPerformAction);
     }
 
@@ -240,7 +241,7 @@ pub fn mint_agent<'info>(
             master_agent.key(),
             ctx.accounts.mint.key(),
             ctx.accounts.authority.key(), // Protocol authority owns the agent initially
-            10,
+            PERCENTAGE_PRECISION_U64,     // 1x booster (basis points)
             current_time,
             ctx.bumps.agent,
         )?;
@@ -28,8 +28,8 @@ use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
 use crate::{
     error::{ErrorCode, TYieldResult},
     state::{
-        AdminInstruction, MasterAgent, MasterAgentInitParams, Multisig, Size, TYield, TaxConfig,
-        TradingStatus,
+        AdminInstruction, MasterAgent, MasterAgentInitParams, Multisig, PricingCurve, RelistPolicy,
+        Size, TYield, TaxConfig, TradingStatus,
     },
 };
 
@@ -54,6 +54,14 @@ pub struct MintMasterAgentParams {
     pub trading_status: TradingStatus,
     /// Whether to auto-relist the master agent
     pub auto_relist: bool,
+    /// What happens to agents returned to the protocol (relist, cooldown or burn)
+    pub relist_policy: RelistPolicy,
+    /// Seconds agents returned to the protocol stay unlisted before being relisted
+    pub relist_cooldown: i64,
+    /// Pricing curve for buying and selling agents (flat, linear or exponential)
+    pub pricing_curve: PricingCurve,
+    /// Curve steepness in basis points (ignored for a flat curve)
+    pub curve_factor: u64,
 }
 
 /// Accounts required for minting a new master agent NFT.
@@ -258,9 +266,14 @@ pub fn mint_master_agent<'info>(
         current_time,
         bump: ctx.bumps.master_agent,
         tax_config: TaxConfig::default(), // SECURITY: Use default tax config
+        seller_fee_basis_points: params.seller_fee_basis_points,
+        pricing_curve: params.pricing_curve,
+        curve_factor: params.curve_factor,
     };
 
     master_agent.initialize(master_agent_init_params)?;
+    master_agent.set_relist_policy(params.relist_policy, current_time)?;
+    master_agent.set_relist_cooldown(params.relist_cooldown, current_time)?;
 
     // SECURITY: Validate the initialized master agent
     master_agent.validate()?;
@@ -1,47 +1,121 @@
+pub mod accept_agent_rental;
+pub mod accrue_yield;
 pub mod ban_user;
+pub mod bid_auction;
 pub mod buy_agent;
+pub mod buy_agents;
+pub mod buy_listing;
+pub mod buy_presale_agent;
+pub mod cancel_agent_rental;
+pub mod cancel_listing;
 pub mod claim_referral_rewards;
+pub mod claim_sunset_buyback;
 pub mod close_trade;
+pub mod configure_presale;
+pub mod crank_trades;
+pub mod create_agent_rental;
+pub mod create_referral_code;
+pub mod fractionalize_agent;
+pub mod fund_keeper_rewards;
 pub mod get_buy_agent_price;
+pub mod get_master_agent_performance;
 pub mod get_pair_price;
 pub mod get_sell_agent_price;
 pub mod init;
+pub mod list_agent;
+pub mod migrate_agent;
+pub mod migrate_master_agent;
+pub mod migrate_t_yield;
+pub mod migrate_trade;
 pub mod mint_agent;
 pub mod mint_master_agent;
+pub mod modify_trade;
 pub mod open_trade;
 pub mod pause_protocol;
+pub mod reassemble_agent;
+pub mod redeem_agent;
+pub mod refund_auction_bid;
 pub mod register_user;
+pub mod revoke_referral_code;
 pub mod secure_oracle_update;
 pub mod sell_agent;
+pub mod sell_agents;
+pub mod set_relist_policy;
+pub mod set_whitelist_root;
+pub mod settle_auction;
+pub mod stake_agent_shares;
+pub mod start_auction;
+pub mod start_sunset;
+pub mod sweep_sunset;
 pub mod transfer_agent;
 pub mod unpause_protocol;
+pub mod unstake_agent_shares;
+pub mod update_listing_price;
 pub mod update_price;
 pub mod update_protocol_config;
 pub mod update_status;
 pub mod update_trade;
 pub mod update_yield;
+pub mod withdraw_protocol_fees;
 pub mod withdraw_yield;
 
+pub use accept_agent_rental::*;
+pub use accrue_yield::*;
 pub use ban_user::*;
+pub use bid_auction::*;
 pub use buy_agent::*;
+pub use buy_agents::*;
+pub use buy_listing::*;
+pub use buy_presale_agent::*;
+pub use cancel_agent_rental::*;
+pub use cancel_listing::*;
 pub use claim_referral_rewards::*;
+pub use claim_sunset_buyback::*;
 pub use close_trade::*;
+pub use configure_presale::*;
+pub use crank_trades::*;
+pub use create_agent_rental::*;
+pub use create_referral_code::*;
+pub use fractionalize_agent::*;
+pub use fund_keeper_rewards::*;
 pub use get_buy_agent_price::*;
+pub use get_master_agent_performance::*;
 pub use get_pair_price::*;
 pub use get_sell_agent_price::*;
 pub use init::*;
+pub use list_agent::*;
+pub use migrate_agent::*;
+pub use migrate_master_agent::*;
+pub use migrate_t_yield::*;
+pub use migrate_trade::*;
 pub use mint_agent::*;
 pub use mint_master_agent::*;
+pub use modify_trade::*;
 pub use open_trade::*;
 pub use pause_protocol::*;
+pub use reassemble_agent::*;
+pub use redeem_agent::*;
+pub use refund_auction_bid::*;
 pub use register_user::*;
+pub use revoke_referral_code::*;
 pub use secure_oracle_update::*;
 pub use sell_agent::*;
+pub use sell_agents::*;
+pub use set_relist_policy::*;
+pub use set_whitelist_root::*;
+pub use settle_auction::*;
+pub use stake_agent_shares::*;
+pub use start_auction::*;
+pub use start_sunset::*;
+pub use sweep_sunset::*;
 pub use transfer_agent::*;
 pub use unpause_protocol::*;
+pub use unstake_agent_shares::*;
+pub use update_listing_price::*;
 pub use update_price::*;
 pub use update_protocol_config::*;
 pub use update_status::*;
 pub use update_trade::*;
 pub use update_yield::*;
+pub use withdraw_protocol_fees::*;
 pub use withdraw_yield::*;
@@ -0,0 +1,147 @@
+//! Instruction: Modify Trade
+//!
+//! Moves the take profit (TP) and/or stop loss (SL) of an active trade. Can be called by the trade
+//! authority or the master agent authority. The new levels are validated against the current oracle
+//! price: neither may already be hit, both must keep the minimum distance from the price and the
+//! risk-reward ratio from there must meet the minimum.
+//!
+//! Accounts:
+//! - authority: The trade authority or master agent authority (signer)
+//! - t_yield: Protocol global state/config PDA
+//! - pair_oracle_account: Oracle price account for the trading pair
+//! - pair_twap_account: (Optional) TWAP oracle account for the trading pair
+//! - trade: Trade account to modify (mut)
+//! - master_agent: Master agent account for the trade
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, TwapUpdate};
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{
+        trade::{PriceValidationConfig, Trade, TradeModifiedEvent},
+        MasterAgent, OraclePrice, TYield,
+    },
+};
+
+/// Parameters for modifying a trade. Levels left as `None` are kept.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ModifyTradeParams {
+    /// The new take profit price (scaled integer)
+    pub take_profit: Option<u64>,
+    /// The new stop loss price (scaled integer)
+    pub stop_loss: Option<u64>,
+}
+
+#[derive(Accounts)]
+pub struct ModifyTrade<'info> {
+    /// The trade authority or master agent authority.
+    pub authority: Signer<'info>,
+
+    /// Protocol global state/config PDA.
+    /// Seeds: ["t_yield"]
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Account<'info, TYield>,
+
+    /// Oracle price account for the trading pair (Pyth V2 price account).
+    pub pair_oracle_account: Account<'info, PriceUpdateV2>,
+
+    /// Optional TWAP (Time-Weighted Average Price) account for the trading pair.
+    pub pair_twap_account: Option<Account<'info, TwapUpdate>>,
+
+    /// Trade account to modify (mut). Must have been opened by `master_agent`.
+    /// PDA: ["trade", master_agent, trade number]
+    #[account(
+        mut,
+        constraint = trade.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::InvalidAccount,
+    )]
+    pub trade: Box<Account<'info, Trade>>,
+
+    /// Master agent account for the trade.
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Modify Trade
+///
+/// Validates the new TP/SL levels against the current oracle price and stores them.
+pub fn modify_trade<'info>(
+    ctx: Context<'_, '_, '_, 'info, ModifyTrade<'info>>,
+    params: ModifyTradeParams,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let authority = ctx.accounts.authority.key();
+    let trade = ctx.accounts.trade.as_mut();
+
+    if authority != trade.authority && authority != ctx.accounts.master_agent.authority {
+        return Err(ErrorCode::InvalidAuthority);
+    }
+    if !trade.is_active() || trade.circuit_breaker_triggered {
+        msg!("Only active trades without a triggered circuit breaker can be modified");
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if params.take_profit.is_none() && params.stop_loss.is_none() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    // Get current price from oracle
+    let token_price = OraclePrice::new_from_oracle(
+        &ctx.accounts.pair_oracle_account,
+        ctx.accounts.pair_twap_account.as_ref(),
+        &ctx.accounts.t_yield.oracle_param,
+        current_time,
+        false,
+        trade.feed_id,
+    )
+    .map_err(|_| ErrorCode::InvalidOraclePrice)?;
+    let current_price = token_price.scale_to_exponent(0)?.price;
+
+    let old_take_profit = trade.take_profit;
+    let old_stop_loss = trade.stop_loss;
+    let take_profit = params.take_profit.unwrap_or(old_take_profit);
+    let stop_loss = params.stop_loss.unwrap_or(old_stop_loss);
+
+    let validation_config = PriceValidationConfig::default();
+    trade.validate_modified_levels(take_profit, stop_loss, current_price, &validation_config)?;
+
+    let (size, status, result) = (trade.size, trade.get_status(), trade.get_result());
+    trade.update_trade(size, take_profit, stop_loss, status, result, current_time);
+
+    msg!(
+        "Trade modified: take profit {} -> {}, stop loss {} -> {}",
+        old_take_profit,
+        take_profit,
+        old_stop_loss,
+        stop_loss
+    );
+
+    emit_cpi!(TradeModifiedEvent {
+        trade: trade.key(),
+        authority,
+        old_take_profit,
+        new_take_profit: take_profit,
+        old_stop_loss,
+        new_stop_loss: stop_loss,
+        price: current_price,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -77,11 +77,14 @@ pub struct OpenTrade<'info> {
     pub master_agent_mint: AccountInfo<'info>,
 
     /// The trade account to be created (PDA, initialized here).
+    /// Namespaced by master agent so trade numbers of different master agents never collide.
+    ///
+    /// Seeds: ["trade", master_agent, master_agent.trade_count + 1]
     #[account(
         init,
         payer = authority, 
         space = Trade::SIZE,
-        seeds = [b"trade".as_ref(), master_agent.trade_count.saturating_add(1).to_le_bytes().as_ref()],
+        seeds = [b"trade".as_ref(), master_agent.key().as_ref(), master_agent.trade_count.saturating_add(1).to_le_bytes().as_ref()],
         bump,
     )]
     pub trade: Box<Account<'info, Trade>>,
@@ -122,6 +125,10 @@ if signatures_left > 0 {
     return Ok(signatures_left);
 }
 
+// Sunset master agents open no new trades
+if ctx.accounts.master_agent.is_sunset() {
+    return Err(ErrorCode::CannotPerformAction);
+}
 
 let token_price = OraclePrice::new_from_oracle(
     &ctx.accounts.pair_oracle_account,
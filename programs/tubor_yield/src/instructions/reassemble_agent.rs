@@ -0,0 +1,277 @@
+//! Instruction: Reassemble Agent
+//!
+//! Burns every share of a fractionalized agent to unlock its NFT. The caller must hold the full
+//! share supply, so no shares can still be staked. The agent NFT is released to the caller, the
+//! vault PDA is closed to the caller and the agent earns yield for its new owner from then on. Yield
+//! the vault retained for unstaked shares is credited to the caller's unclaimed yield. An agent
+//! bought back by a master agent sunset is not released: it stays held by the protocol and the
+//! caller only receives the retained yield and buyback price.
+//!
+//! Accounts:
+//! - authority: The holder of every share (signer)
+//! - system_program: Solana system program
+//! - token_program: SPL token program
+//! - associated_token_program: SPL associated token program
+//! - sysvar_instructions: Instructions sysvar (for Metaplex CPI)
+//! - metadata_program: Metaplex token metadata program
+//! - metadata: Metadata account for the agent NFT (Metaplex)
+//! - user: User account PDA
+//! - agent: Agent account PDA (the fractionalized agent)
+//! - master_agent: Master agent account PDA (parent/master of the agent)
+//! - mint: Mint account for the agent NFT
+//! - agent_vault: Vault PDA of the agent (closed to the authority)
+//! - share_mint: Share mint of the agent
+//! - t_yield: Protocol global state/config PDA
+//! - transfer_authority: Transfer authority PDA (custodian of the NFT)
+//! - transfer_authority_ta: Protocol's token account holding the agent NFT (sender)
+//! - user_agent_ta: User's token account receiving the agent NFT
+//! - user_share_ta: User's token account holding the shares (burned)
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{
+        Agent, AgentVault, MasterAgent, ReassembleAgentEvent, TYield, TransferAgentParams, User,
+        UserStatus,
+    },
+};
+
+#[derive(Accounts)]
+pub struct ReassembleAgent<'info> {
+    /// The holder of every share. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Instructions sysvar (required for Metaplex CPI).
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+
+    /// CHECK: Metaplex token metadata program.
+    #[account(address = METADATA_PROGRAM_ID)]
+    pub metadata_program: AccountInfo<'info>,
+
+    /// CHECK: Metadata account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    // --- Protocol/User/Agent State ---
+    /// User account PDA.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Agent account PDA (the fractionalized agent).
+    /// PDA: ["agent", mint]
+    #[account(
+        mut,
+        seeds = [b"agent".as_ref(), mint.key().as_ref()],
+        bump,
+        constraint = agent.is_owned_by(&agent_vault.key()) @ ErrorCode::InvalidAccount,
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Master agent account PDA (parent/master of the agent).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+        constraint = agent.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Mint account for the agent NFT.
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// Vault PDA of the agent. Closed to the authority.
+    /// PDA: ["agent_vault", agent]
+    #[account(
+        mut,
+        seeds = [b"agent_vault".as_ref(), agent.key().as_ref()],
+        bump = agent_vault.bump,
+        close = authority,
+    )]
+    pub agent_vault: Box<Account<'info, AgentVault>>,
+
+    /// Share mint of the agent.
+    #[account(
+        mut,
+        address = agent_vault.share_mint
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// CHECK: Transfer authority PDA (custodian of the NFT).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    // --- Token Accounts ---
+    /// Protocol's token account holding the agent NFT (sender).
+    /// Must have mint == agent mint.
+    #[account(
+        mut,
+        constraint = transfer_authority_ta.mint == mint.key()
+    )]
+    pub transfer_authority_ta: Box<Account<'info, TokenAccount>>,
+
+    /// User's token account receiving the agent NFT.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = authority,
+    )]
+    pub user_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// User's token account holding the shares (burned).
+    #[account(
+        mut,
+        constraint = user_share_ta.mint == share_mint.key(),
+        constraint = user_share_ta.owner == authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub user_share_ta: Box<Account<'info, TokenAccount>>,
+
+    // --- Misc ---
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Reassemble Agent
+///
+/// Burns the full share supply held by the caller and releases the agent NFT to them. Yield the
+/// agent earned since the last share settlement is passed to the vault first and, with everything
+/// the vault retained for unstaked shares, credited to the caller.
+pub fn reassemble_agent<'info>(
+    ctx: Context<'_, '_, '_, 'info, ReassembleAgent<'info>>,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let user = ctx.accounts.user.as_mut();
+    let agent = ctx.accounts.agent.as_mut();
+    let agent_vault = ctx.accounts.agent_vault.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+
+    if user.has_status(UserStatus::Banned) {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    // Staked shares must be unstaked before the agent can be reassembled
+    if agent_vault.staked_shares > 0 {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    // Close the vault's yield history; the caller earns from now on
+    if !agent_vault.is_bought_back {
+        let accrued_yield = agent.settle_yield(master_agent, current_time)?;
+        agent_vault.accrue_yield(accrued_yield, current_time)?;
+    }
+    let retained_yield = agent_vault.take_retained_yield()?;
+    if retained_yield > 0 {
+        user.add_unclaimed_yield(retained_yield)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(retained_yield)?;
+    }
+
+    // --- Burn every share ---
+    t_yield
+        .burn_tokens(
+            ctx.accounts.share_mint.to_account_info(),
+            ctx.accounts.user_share_ta.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            agent_vault.total_shares,
+            ctx.accounts.share_mint.decimals,
+        )
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    // --- Release the agent NFT ---
+    if agent_vault.is_bought_back {
+        // Bought back by a sunset: the agent stays held by the protocol
+        agent.return_to_protocol(ctx.accounts.transfer_authority.key(), None, current_time)?;
+    } else {
+        let transfer_agent_params = TransferAgentParams {
+            payer: ctx.accounts.authority.to_account_info(),
+            sender_nft_token_account: ctx.accounts.transfer_authority_ta.to_account_info(),
+            authority: ctx.accounts.transfer_authority.to_account_info(),
+            receiver_token_account: ctx.accounts.user_agent_ta.to_account_info(),
+            receiver: ctx.accounts.authority.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            metadata_program: ctx.accounts.metadata_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+        };
+
+        t_yield
+            .transfer_agent(transfer_agent_params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+        agent.transfer_ownership(ctx.accounts.authority.key(), current_time)?;
+
+        user.receive_agent()?;
+    }
+
+    user.update_last_activity(current_time)?;
+    user.validate_user()?;
+
+    emit_cpi!(ReassembleAgentEvent {
+        agent: agent.key(),
+        vault: agent_vault.key(),
+        owner: ctx.accounts.authority.key(),
+        total_shares: agent_vault.total_shares,
+        total_yield: agent_vault.total_yield,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -0,0 +1,343 @@
+//! Instruction: Redeem Agent
+//!
+//! Allows a user to exit an agent at its share of the master agent NAV instead of the admin-set price.
+//! Redemption originally always burned the agent; the relist policy (which governs every agent
+//! returned to the protocol, including redeemed ones) now decides instead, and burning is opt-in.
+//! The returned agent is relisted (immediately or after the relist cooldown), held unlisted by the
+//! protocol when auto-relist is disabled, or, when the master agent opted into burning, its NFT is
+//! burned through Metaplex, the agent PDA is closed to the user, and the agent is retired from the
+//! master agent supply. The payout is the NAV (holder capital plus
+//! realized PnL) divided by the outstanding agents, bounded by a minimum payout and an expiry timestamp.
+//!
+//! Accounts:
+//! - authority: The user redeeming the agent (signer)
+//! - system_program: Solana system program
+//! - token_program: SPL token program
+//! - associated_token_program: SPL associated token program
+//! - sysvar_instructions: Instructions sysvar (for Metaplex CPI)
+//! - metadata_program: Metaplex token metadata program
+//! - metadata: Metadata account for the agent NFT (Metaplex, closed by the burn)
+//! - master_edition: Master edition account for the agent NFT (Metaplex, closed by the burn)
+//! - user: User account PDA
+//! - agent: Agent account PDA (closed to the authority when burned)
+//! - master_agent: Master agent account PDA (parent/master of the agent)
+//! - mint: Mint account for the agent NFT
+//! - t_yield: Protocol global state/config PDA
+//! - transfer_authority: Transfer authority PDA (protocol authority for token transfers)
+//! - transfer_authority_ta: Protocol's token account receiving a relisted agent NFT
+//! - user_agent_ta: User's token account holding the agent NFT (sender or burned)
+//! - user_y_mint_ta: User's Y-mint token account (receiver of the payout)
+//! - transfer_authority_y_mint_ta: Protocol's Y-mint token account (payer of the payout)
+//! - y_mint: Y-mint SPL token mint (payment token for protocol)
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{
+        Agent, BurnAgentParams, MasterAgent, RedeemAgentEvent, RelistPolicy, TYield,
+        TransferAgentParams, User, UserStatus,
+    },
+    try_from,
+};
+
+/// Parameters for redeeming an agent.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RedeemAgentParams {
+    /// Minimum NAV share the holder accepts
+    pub min_payout: u64,
+    /// Unix timestamp after which the redemption is rejected
+    pub expiry: i64,
+}
+
+#[derive(Accounts)]
+pub struct RedeemAgent<'info> {
+    /// The user redeeming the agent. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Instructions sysvar (required for Metaplex CPI).
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+
+    /// CHECK: Metaplex token metadata program.
+    #[account(address = METADATA_PROGRAM_ID)]
+    pub metadata_program: AccountInfo<'info>,
+
+    /// CHECK: Metadata account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    /// CHECK: Master edition account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint, "edition"]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+            b"edition",
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub master_edition: AccountInfo<'info>,
+
+    // --- Protocol/User/Agent State ---
+    /// User account PDA.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Agent account PDA (the agent being redeemed). Closed to the authority when burned.
+    /// PDA: ["agent", mint]
+    #[account(
+        mut,
+        seeds = [b"agent".as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Master agent account PDA (parent/master of the agent).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Mint account for the agent NFT being redeemed.
+    #[account(mut)]
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// CHECK: Transfer authority PDA (protocol authority for token transfers).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    // --- Token Accounts ---
+    /// Protocol's token account to receive a relisted agent NFT.
+    /// Must have mint == agent mint.
+    #[account(
+        mut,
+        constraint = transfer_authority_ta.mint == mint.key()
+    )]
+    pub transfer_authority_ta: Box<Account<'info, TokenAccount>>,
+
+    /// User's token account holding the agent NFT (sender or burned).
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = authority,
+    )]
+    pub user_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// User's Y-mint token account (receiver of the payout).
+    /// Must have mint == t_yield.y_mint.
+    #[account(
+        mut,
+        constraint = user_y_mint_ta.mint == t_yield.y_mint
+    )]
+    pub user_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol's Y-mint token account (payer of the payout).
+    /// Must have mint == t_yield.y_mint.
+    #[account(
+        mut,
+        constraint = transfer_authority_y_mint_ta.mint == t_yield.y_mint
+    )]
+    pub transfer_authority_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Y-mint SPL token mint (payment token for protocol).
+    #[account(address = t_yield.y_mint)]
+    pub y_mint: AccountInfo<'info>,
+
+    // --- Misc ---
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Redeem Agent
+///
+/// Pays the holder the agent's NAV share and returns the agent according to the master agent
+/// relist policy, burning it and closing the agent PDA under the burn policy. Rejects the redemption if the payout is below
+/// `params.min_payout` or it lands after `params.expiry`.
+pub fn redeem_agent<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RedeemAgent<'info>>,
+    params: RedeemAgentParams,
+) -> TYieldResult<()> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    if current_time > params.expiry {
+        return Err(ErrorCode::TransactionExpired);
+    }
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let user = ctx.accounts.user.as_mut();
+    let agents = ctx.accounts.agent.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+
+    if user.has_status(UserStatus::Banned) {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if !agents.is_owned_by(&ctx.accounts.authority.key()) || !agents.is_transferable() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    if !agents.belongs_to_master_agent(&master_agent.key()) {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    // Holders of a sunset master agent exit through `claim_sunset_buyback`
+    if master_agent.is_sunset() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    // Settle yield earned while held before the agent returns to the protocol
+    let accrued_yield = agents.settle_yield(master_agent, current_time)?;
+    if accrued_yield > 0 {
+        user.add_unclaimed_yield(accrued_yield)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(accrued_yield)?;
+    }
+
+    // --- NAV share ---
+    let payout = master_agent.record_agent_sale()?;
+    let relist_policy = master_agent.take_back_agent(current_time)?;
+    if payout < params.min_payout {
+        msg!(
+            "Redemption payout {} below minimum {}",
+            payout,
+            params.min_payout
+        );
+        return Err(ErrorCode::MaxPriceSlippage);
+    }
+
+    if payout > 0 {
+        let mint = try_from!(Account<Mint>, ctx.accounts.y_mint)
+            .map_err(|_| ErrorCode::AccountFromError)?;
+        TYield::transfer_tokens(
+            ctx.accounts.transfer_authority_y_mint_ta.to_account_info(),
+            mint.to_account_info(),
+            ctx.accounts.user_y_mint_ta.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            payout,
+            mint.decimals,
+        )
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+        t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_sub(payout)?;
+    }
+
+    // --- Return the agent NFT ---
+    if relist_policy == RelistPolicy::Burn {
+        let burn_agent_params = BurnAgentParams {
+            owner: ctx.accounts.authority.to_account_info(),
+            token_account: ctx.accounts.user_agent_ta.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            master_edition: ctx.accounts.master_edition.to_account_info(),
+            metadata_program: ctx.accounts.metadata_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+        };
+
+        t_yield
+            .burn_agent(burn_agent_params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+    } else {
+        let transfer_agent_params = TransferAgentParams {
+            payer: ctx.accounts.authority.to_account_info(),
+            sender_nft_token_account: ctx.accounts.user_agent_ta.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+            receiver_token_account: ctx.accounts.transfer_authority_ta.to_account_info(),
+            receiver: ctx.accounts.transfer_authority.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            metadata_program: ctx.accounts.metadata_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+        };
+
+        t_yield
+            .transfer_agent(transfer_agent_params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+        agents.return_to_protocol(
+            ctx.accounts.transfer_authority.key(),
+            master_agent.get_relist_time(current_time)?,
+            current_time,
+        )?;
+    }
+
+    // --- Update user state ---
+    user.remove_agent(1)?;
+    user.validate_user()?;
+
+    master_agent.validate_security(current_time)?;
+
+    emit_cpi!(RedeemAgentEvent {
+        agent: agents.key(),
+        owner: ctx.accounts.authority.key(),
+        master_agent: master_agent.key(),
+        payout,
+        relist_policy: relist_policy as u8,
+        timestamp: current_time,
+    });
+
+    if relist_policy == RelistPolicy::Burn {
+        ctx.accounts
+            .agent
+            .close(ctx.accounts.authority.to_account_info())
+            .map_err(|_| ErrorCode::AccountFromError)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! Instruction: Refund Auction Bid
+//!
+//! Refunds a bidder of a settled Dutch auction down to the clearing price and closes their bid receipt.
+//!
+//! Accounts:
+//! - authority: The bidder (signer, receives the bid account rent)
+//! - user: User account PDA of the bidder (mut)
+//! - master_agent: Master agent the auction belongs to
+//! - auction: Auction PDA for the master agent (mut)
+//! - bid: Bid receipt PDA of the bidder (closed)
+//! - t_yield: Protocol global state/config PDA (mut)
+//! - y_mint: Protocol yield token mint
+//! - transfer_authority: PDA that owns the protocol token account
+//! - user_token_account: Bidder's y_mint token account (mut)
+//! - protocol_token_account: Protocol token account holding the bids (mut)
+//! - token_program: SPL Token program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{AgentAuction, AuctionBid, AuctionRefundEvent, MasterAgent, TYield, User},
+    try_from,
+};
+
+#[derive(Accounts)]
+pub struct RefundAuctionBid<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Master agent the auction belongs to.
+    /// Seeds: ["master_agent", master_agent.mint]
+    #[account(
+        seeds = [b"master_agent", master_agent.mint.as_ref()],
+        bump = master_agent.bump
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Auction PDA for the master agent.
+    /// Seeds: ["auction", master_agent]
+    #[account(
+        mut,
+        seeds = [b"auction", master_agent.key().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, AgentAuction>>,
+
+    /// Bid receipt of the bidder.
+    /// Seeds: ["auction_bid", auction, authority]
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"auction_bid", auction.key().as_ref(), authority.key().as_ref()],
+        bump = bid.bump,
+        constraint = bid.buyer == authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub bid: Box<Account<'info, AuctionBid>>,
+
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    ///CHECK: y_mint
+    #[account(address = t_yield.y_mint)]
+    pub y_mint: AccountInfo<'info>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == t_yield.y_mint,
+        constraint = user_token_account.owner == authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = protocol_token_account.mint == t_yield.y_mint,
+        constraint = protocol_token_account.owner == transfer_authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub protocol_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn refund_auction_bid<'info>(
+    ctx: Context<'_, '_, '_, 'info, RefundAuctionBid<'info>>,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let auction = ctx.accounts.auction.as_mut();
+    let bid = &ctx.accounts.bid;
+
+    // 1. Price the bid at the clearing price
+    let (refund, _, final_tax) = auction.settle_bid(bid)?;
+
+    // 2. Transfer the refund from protocol to bidder
+    if refund > 0 {
+        let mint = try_from!(Account<Mint>, ctx.accounts.y_mint)
+            .map_err(|_| ErrorCode::AccountFromError)?;
+
+        TYield::transfer_tokens(
+            ctx.accounts.protocol_token_account.to_account_info(),
+            mint.to_account_info(),
+            ctx.accounts.user_token_account.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            refund,
+            mint.decimals,
+        )
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+    }
+
+    // 3. Update protocol/user balances
+    let t_yield = ctx.accounts.t_yield.as_mut();
+    t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_sub(refund)?;
+
+    let user = ctx.accounts.user.as_mut();
+    if final_tax > 0 {
+        user.history.add_fees_spent(final_tax)?;
+    }
+
+    msg!("Refunded {} for {} auction agents", refund, bid.quantity);
+
+    emit_cpi!(AuctionRefundEvent {
+        master_agent: auction.master_agent,
+        buyer: bid.buyer,
+        quantity: bid.quantity,
+        refund,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
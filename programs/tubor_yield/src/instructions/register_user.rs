@@ -7,8 +7,10 @@
 //! - authority: The wallet that will own the new user account
 //! - user: The new user account PDA
 //! - referrer_user: (Optional) The user account of the referrer
-//! - referral_registry: (Optional) Registry tracking all users referred by the referrer
-//! - referral_link: (Optional) Link between referrer and referred user
+//! - referral_code: (Optional) Referral code PDA, when registering with a code
+//! - referral_registry: (Optional) Registry tracking all users referred by the referrer, required with a referrer
+//! - referral_link: (Optional) Link between referrer and referred user, required with a referrer
+//! - referrer_link: (Optional) The referrer's own link, required when the referrer was referred
 //! - t_yield: Protocol global state/config
 //! - event_authority: Used for event emission
 //! - system_program: Solana system program
@@ -18,16 +20,35 @@ use anchor_lang::prelude::*;
 use crate::{
     error::{ErrorCode, TYieldResult},
     math::SafeMath,
-    state::{ReferralLink, ReferralRegistry, RegisterUserEvent, Size, TYield, User, UserStatus},
+    state::{
+        ReferralCode, ReferralLink, ReferralRegistry, RegisterUserEvent, Size, TYield, User,
+        UserStatus,
+    },
 };
 
-#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 
 pub struct RegisterUserParams {
     /// Display name for the user (max 15 bytes, UTF-8 encoded)
     pub name: [u8; 15],
     /// Optional referrer (must be an existing user)
     pub referrer: Option<Pubkey>,
+    /// Optional referral code, used instead of `referrer`
+    pub referral_code: Option<String>,
+}
+
+impl RegisterUserParams {
+    /// Resolves the referrer from the pubkey or, when a code is used, the referral code account.
+    pub fn get_referrer(
+        &self,
+        referral_code: &Option<Box<Account<'_, ReferralCode>>>,
+    ) -> Option<Pubkey> {
+        match (self.referral_code.as_ref(), referral_code) {
+            (Some(_), Some(referral_code)) => Some(referral_code.referrer),
+            (Some(_), None) => None,
+            (None, _) => self.referrer,
+        }
+    }
 }
 
 /// Accounts required for registering a new user.
@@ -37,8 +58,10 @@ pub struct RegisterUserParams {
 /// - authority: The wallet that will own the new user account
 /// - user: The new user account PDA
 /// - referrer_user: (Optional) The user account of the referrer
-/// - referral_registry: (Optional) Registry tracking all users referred by the referrer
-/// - referral_link: (Optional) Link between referrer and referred user
+/// - referral_code: (Optional) Referral code PDA, when registering with a code
+/// - referral_registry: (Optional) Registry tracking all users referred by the referrer, required with a referrer
+/// - referral_link: (Optional) Link between referrer and referred user, required with a referrer
+/// - referrer_link: (Optional) The referrer's own link, required when the referrer was referred
 /// - t_yield: Protocol global state/config
 /// - event_authority: Used for event emission
 /// - system_program: Solana system program
@@ -71,28 +94,43 @@ pub struct RegisterUser<'info> {
     #[account()]
     pub referrer_user: Option<Box<Account<'info, User>>>,
 
-    /// Registry tracking all users referred by the referrer (if provided).
+    /// The referral code used to register (if provided).
+    /// Seeds: ["ref_code", code]
+    #[account(
+        seeds = [b"ref_code", params.referral_code.as_deref().unwrap_or_default().as_bytes()],
+        bump = referral_code.bump
+    )]
+    pub referral_code: Option<Box<Account<'info, ReferralCode>>>,
+
+    /// Registry tracking all users referred by the referrer. Required with a referrer, since
+    /// buys and sells of referred users pay the referrer through it.
     /// Seeds: ["referral_registry", referrer]
     #[account(
         init_if_needed,
         payer = payer,
         space = ReferralRegistry::SIZE,
-        seeds = [b"referral_registry", params.referrer.unwrap_or(Pubkey::default()).as_ref()],
+        seeds = [b"referral_registry", params.get_referrer(&referral_code).unwrap_or_default().as_ref()],
         bump
     )]
     pub referral_registry: Option<Box<Account<'info, ReferralRegistry>>>,
 
-    /// Link between referrer and referred user (if provided).
+    /// Link between referrer and referred user (required with a referrer).
     /// Seeds: ["referral_link", referrer, authority]
     #[account(
         init_if_needed,
         payer = payer,
         space = ReferralLink::SIZE,
-        seeds = [b"referral_link", params.referrer.unwrap_or(Pubkey::default()).as_ref(), authority.key().as_ref()],
+        seeds = [b"referral_link", params.get_referrer(&referral_code).unwrap_or_default().as_ref(), authority.key().as_ref()],
         bump,
     )]
     pub referral_link: Option<Box<Account<'info, ReferralLink>>>,
 
+    /// The referrer's own referral link (if the referrer was referred).
+    /// Used to extend the new link's referral chain up to `MAX_REFERRAL_DEPTH` levels.
+    /// Seeds: ["referral_link", referrer_user.referrer, referrer]
+    #[account()]
+    pub referrer_link: Option<Box<Account<'info, ReferralLink>>>,
+
     /// The t_yield config PDA (protocol global state).
     /// Seeds: ["t_yield"]
     #[account(
@@ -115,15 +153,34 @@ pub struct RegisterUser<'info> {
 }
 
 pub fn register_user(ctx: Context<RegisterUser>, params: RegisterUserParams) -> TYieldResult<()> {
+    // A referral code is an alternative to the referrer pubkey, not an addition
+    if params.referral_code.is_some() {
+        if params.referrer.is_some() {
+            return Err(ErrorCode::InvalidReferralCode);
+        }
+        let referral_code = ctx
+            .accounts
+            .referral_code
+            .as_ref()
+            .ok_or(ErrorCode::InvalidReferralCode)?;
+        if !referral_code.is_active() {
+            return Err(ErrorCode::ReferralCodeRevoked);
+        }
+    } else if ctx.accounts.referral_code.is_some() {
+        return Err(ErrorCode::InvalidReferralCode);
+    }
+    let referrer = params.get_referrer(&ctx.accounts.referral_code);
+
     // Check if optional referral accounts are provided when no referrer is specified
-    if params.referrer.is_none()
+    if referrer.is_none()
         && (ctx.accounts.referral_registry.is_some() || ctx.accounts.referral_link.is_some())
     {
         return Err(ErrorCode::ReferrerNotAUser);
     }
 
     // Validate referrer if provided
-    if let Some(referrer_pubkey) = params.referrer {
+    let mut referrer_ancestors = Vec::new();
+    if let Some(referrer_pubkey) = referrer {
         if referrer_pubkey != Pubkey::default() {
             // Check if referrer user account exists and is valid
             let referrer_user = ctx
@@ -148,6 +205,34 @@ pub fn register_user(ctx: Context<RegisterUser>, params: RegisterUserParams) ->
             if !referrer_user.is_active() {
                 return Err(ErrorCode::ReferrerNotAUser);
             }
+
+            // Buys and sells of referred users require the referrer's registry and link
+            if ctx.accounts.referral_registry.is_none() || ctx.accounts.referral_link.is_none() {
+                return Err(ErrorCode::CannotPerformAction);
+            }
+
+            // Load the referrer's own chain so the new link can extend it
+            if referrer_user.has_referrer() && ctx.accounts.referral_link.is_some() {
+                let referrer_link = ctx
+                    .accounts
+                    .referrer_link
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidAccount)?;
+
+                let (expected_referrer_link_pda, _) = Pubkey::find_program_address(
+                    &[
+                        b"referral_link",
+                        referrer_user.referrer.as_ref(),
+                        referrer_pubkey.as_ref(),
+                    ],
+                    ctx.program_id,
+                );
+                if referrer_link.key() != expected_referrer_link_pda {
+                    return Err(ErrorCode::InvalidAccount);
+                }
+
+                referrer_ancestors = referrer_link.get_ancestors().to_vec();
+            }
         }
     }
 
@@ -158,13 +243,13 @@ pub fn register_user(ctx: Context<RegisterUser>, params: RegisterUserParams) ->
     user.authority = ctx.accounts.authority.key();
     user.name = params.name;
     let _ = user.add_user_status(UserStatus::Active);
-    user.referrer = params.referrer.unwrap_or_default();
+    user.referrer = referrer.unwrap_or_default();
     user.updated_at = current_time;
     user.created_at = current_time;
     user.bump = ctx.bumps.user;
 
     // Handle referral logic if referrer is provided
-    if let Some(referrer_pubkey) = params.referrer {
+    if let Some(referrer_pubkey) = referrer {
         if referrer_pubkey != Pubkey::default() {
             // Update referral registry
             if let Some(ref mut referral_registry) = ctx.accounts.referral_registry {
@@ -189,8 +274,11 @@ pub fn register_user(ctx: Context<RegisterUser>, params: RegisterUserParams) ->
                 {
                     referral_link.referrer = referrer_pubkey;
                     referral_link.referred_user = ctx.accounts.authority.key();
+                    referral_link.ancestors[0] = referrer_pubkey;
                     referral_link.created_at = current_time;
                     referral_link.bump = ctx.bumps.referral_link.ok_or(ErrorCode::InvalidBump)?;
+                    // Enforces the maximum depth and rejects cycles
+                    referral_link.set_ancestors(&referrer_ancestors)?;
                 }
             }
         }
@@ -200,7 +288,7 @@ pub fn register_user(ctx: Context<RegisterUser>, params: RegisterUserParams) ->
         "User registered successfully with authority: {}",
         ctx.accounts.authority.key()
     );
-    if let Some(ref_pubkey) = params.referrer {
+    if let Some(ref_pubkey) = referrer {
         if ref_pubkey != Pubkey::default() {
             msg!("Referrer: {}", ref_pubkey);
         }
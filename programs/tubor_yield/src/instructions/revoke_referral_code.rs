@@ -0,0 +1,101 @@
+//! Instruction: Revoke Referral Code
+//!
+//! Revokes a referral code (with multisig approval). The code PDA is kept so the code stays reserved,
+//! but it can no longer be used to register. Existing referral links are not affected.
+//!
+//! Accounts:
+//! - admin: The signer proposing/signing the revocation (must be a multisig signer)
+//! - multisig: Protocol multisig PDA (controls admin actions)
+//! - t_yield: Protocol global state/config PDA
+//! - referral_code: The referral code PDA being revoked
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{AdminInstruction, Multisig, ReferralCode, ReferralCodeEvent, TYield},
+};
+
+#[derive(Accounts)]
+pub struct RevokeReferralCode<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// The referral code PDA being revoked.
+    /// Seeds: ["ref_code", code]
+    #[account(
+        mut,
+        seeds = [b"ref_code", referral_code.get_code_string().as_bytes()],
+        bump = referral_code.bump
+    )]
+    pub referral_code: Box<Account<'info, ReferralCode>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn revoke_referral_code<'info>(
+    ctx: Context<'_, '_, '_, 'info, RevokeReferralCode<'info>>,
+) -> TYieldResult<u8> {
+    let mut multisig = ctx
+        .accounts
+        .multisig
+        .load_mut()
+        .map_err(|_| ErrorCode::InvalidBump)?;
+
+    let instruction_data =
+        Multisig::get_instruction_data(AdminInstruction::RevokeReferralCode, &())
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let nonce = current_time as u64;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &instruction_data,
+        nonce,
+        current_time,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    let referral_code = ctx.accounts.referral_code.as_mut();
+    referral_code.revoke()?;
+
+    msg!("Referral code {} revoked", referral_code.get_code_string());
+
+    emit_cpi!(ReferralCodeEvent {
+        referrer: referral_code.referrer,
+        code: referral_code.code,
+        revoked: true,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -1,7 +1,11 @@
 //! Instruction: Sell Agent
 //!
 //! Allows a user to sell an agent NFT back to the protocol (or master agent), transferring ownership and updating protocol/user state.
+//! The returned agent follows the master agent relist policy: relisted at the current price, held unlisted
+//! for the relist cooldown, held unlisted by the protocol when auto-relist is disabled, or burned to shrink
+//! the supply when the master agent opted into burning.
 //! Handles price calculation, tax, and all token/NFT transfers. Enforces ban and protocol constraints.
+//! The seller bounds the fill with minimum net proceeds and an expiry timestamp.
 //!
 //! Accounts:
 //! - authority: The user selling the agent (signer)
@@ -11,8 +15,9 @@
 //! - sysvar_instructions: Instructions sysvar (for Metaplex CPI)
 //! - metadata_program: Metaplex token metadata program
 //! - metadata: Metadata account for the agent NFT (Metaplex)
+//! - master_edition: Master edition account for the agent NFT (Metaplex, closed when burned)
 //! - user: User account PDA
-//! - agent: Agent account PDA (the agent being sold)
+//! - agent: Agent account PDA (the agent being sold, closed to the authority when burned)
 //! - master_agent: Master agent account PDA (parent/master of the agent)
 //! - mint: Mint account for the agent NFT
 //! - t_yield: Protocol global state/config PDA
@@ -35,10 +40,22 @@ use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
 use crate::{
     error::{ErrorCode, TYieldResult},
     math::SafeMath,
-    state::{Agent, MasterAgent, SellAgentEvent, TYield, TransferAgentParams, User},
+    state::{
+        Agent, BurnAgentParams, MasterAgent, ReferralEarningEvent, ReferralLink, ReferralRegistry,
+        RelistPolicy, SellAgentEvent, TYield, TransferAgentParams, User,
+    },
     try_from,
 };
 
+/// Parameters for selling an agent.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SellAgentParams {
+    /// Minimum net proceeds (after tax) the seller accepts
+    pub min_net_proceeds: u64,
+    /// Unix timestamp after which the sale is rejected
+    pub expiry: i64,
+}
+
 #[derive(Accounts)]
 pub struct SellAgent<'info> {
     /// The user selling the agent. Must sign the transaction.
@@ -75,6 +92,21 @@ pub struct SellAgent<'info> {
     )]
     pub metadata: AccountInfo<'info>,
 
+    /// CHECK: Master edition account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint, "edition"]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+            b"edition",
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub master_edition: AccountInfo<'info>,
+
     // --- Protocol/User/Agent State ---
     /// User account PDA.
     /// PDA: ["user", authority]
@@ -85,7 +117,27 @@ pub struct SellAgent<'info> {
     )]
     pub user: Box<Account<'info, User>>,
 
-    /// Agent account PDA (the agent being sold).
+    /// Referral registry of the seller's direct referrer. Required when the seller has a
+    /// referrer, who is credited `ref_earn_percentage` of the tax.
+    /// PDA: ["referral_registry", user.referrer]
+    #[account(
+        mut,
+        seeds = [b"referral_registry".as_ref(), user.referrer.as_ref()],
+        bump = referral_registry.bump
+    )]
+    pub referral_registry: Option<Box<Account<'info, ReferralRegistry>>>,
+
+    /// Referral link between the seller and their referrer. Required when the seller has a
+    /// referrer. The registries of all higher referral levels are read from `remaining_accounts`
+    /// (nearest first) and credited `ref_tier_percentages` of the tax.
+    /// PDA: ["referral_link", user.referrer, authority]
+    #[account(
+        seeds = [b"referral_link".as_ref(), user.referrer.as_ref(), authority.key().as_ref()],
+        bump = referral_link.bump
+    )]
+    pub referral_link: Option<Box<Account<'info, ReferralLink>>>,
+
+    /// Agent account PDA (the agent being sold). Closed to the authority when burned.
     /// PDA: ["agent", mint]
     #[account(
         mut,
@@ -110,6 +162,7 @@ pub struct SellAgent<'info> {
     /// Protocol global state PDA.
     /// PDA: ["t_yield"]
     #[account(
+        mut,
         seeds = [b"t_yield"],
         bump = t_yield.t_yield_bump
     )]
@@ -173,8 +226,14 @@ pub struct SellAgent<'info> {
     pub event_authority: AccountInfo<'info>,
 }
 
-pub fn sell_agent<'info>(ctx: Context<'_, '_, '_, 'info, SellAgent<'info>>) -> TYieldResult<()> {
+pub fn sell_agent<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SellAgent<'info>>,
+    params: SellAgentParams,
+) -> TYieldResult<()> {
     let current_time = ctx.accounts.t_yield.get_time()?;
+    if current_time > params.expiry {
+        return Err(ErrorCode::TransactionExpired);
+    }
     let master_agent = ctx.accounts.master_agent.as_mut();
     let user = ctx.accounts.user.as_mut();
     let agents = ctx.accounts.agent.as_mut();
@@ -185,7 +244,7 @@ pub fn sell_agent<'info>(ctx: Context<'_, '_, '_, 'info, SellAgent<'info>>) -> T
     }
 
     // Validate agent ownership
-    if !agents.is_owned_by(&ctx.accounts.authority.key()) {
+    if !agents.is_owned_by(&ctx.accounts.authority.key()) || !agents.is_transferable() {
         return Err(ErrorCode::CannotPerformAction);
     }
 
@@ -193,7 +252,19 @@ pub fn sell_agent<'info>(ctx: Context<'_, '_, '_, 'info, SellAgent<'info>>) -> T
         return Err(ErrorCode::CannotPerformAction);
     }
 
-    let price = master_agent.calculate_sell_price_with_tax()?;
+    // Holders of a sunset master agent exit through `claim_sunset_buyback`
+    if master_agent.is_sunset() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    // Settle yield earned while held before the agent returns to the protocol
+    let accrued_yield = agents.settle_yield(master_agent, current_time)?;
+    if accrued_yield > 0 {
+        user.add_unclaimed_yield(accrued_yield)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(accrued_yield)?;
+    }
+
+    let price = master_agent.calculate_sell_price_with_limit(params.min_net_proceeds)?;
 
     let mint =
         try_from!(Account<Mint>, ctx.accounts.y_mint).map_err(|_| ErrorCode::AccountFromError)?;
@@ -209,34 +280,82 @@ pub fn sell_agent<'info>(ctx: Context<'_, '_, '_, 'info, SellAgent<'info>>) -> T
     )
     .map_err(|_| ErrorCode::InvalidInstructionHash)?;
 
-    t_yield.protocol_total_fees = t_yield.protocol_total_fees.safe_add(price.1)?;
+    // --- Referral shares of the tax ---
+    let referrals = t_yield.credit_referrals(
+        user,
+        ctx.accounts
+            .referral_registry
+            .as_deref_mut()
+            .map(|registry| &mut **registry),
+        ctx.accounts.referral_link.as_deref().map(|link| &**link),
+        ctx.remaining_accounts,
+        price.1,
+        current_time,
+    )?;
+    let mut referral_fee = 0;
+    for (referrer, level, fee) in referrals {
+        referral_fee = referral_fee.safe_add(fee)?;
+        emit_cpi!(ReferralEarningEvent {
+            referrer,
+            referred_user: user.authority,
+            level,
+            amount: fee,
+            tax: price.1,
+            timestamp: current_time,
+        });
+    }
+    t_yield.protocol_total_fees = t_yield
+        .protocol_total_fees
+        .safe_add(price.1.safe_sub(referral_fee)?)?;
     t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_sub(price.0)?; // Use net_price instead of base_price
     t_yield.protocol_total_balance_usd = t_yield.protocol_total_balance_usd.safe_sub(price.1)?;
     t_yield.protocol_total_earnings = t_yield.protocol_total_earnings.safe_add(price.1)?;
 
-    let transfer_agent_params = TransferAgentParams {
-        payer: ctx.accounts.authority.to_account_info(),
-        sender_nft_token_account: ctx.accounts.user_agent_ta.to_account_info(),
-        authority: ctx.accounts.authority.to_account_info(),
-        receiver_token_account: ctx.accounts.transfer_authority_ta.to_account_info(),
-        receiver: ctx.accounts.transfer_authority.to_account_info(),
-        mint: ctx.accounts.mint.to_account_info(),
-        metadata: ctx.accounts.metadata.to_account_info(),
-        metadata_program: ctx.accounts.metadata_program.to_account_info(),
-        system_program: ctx.accounts.system_program.to_account_info(),
-        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
-        token_program: ctx.accounts.token_program.to_account_info(),
-        sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
-    };
-
-    t_yield
-        .transfer_agent(transfer_agent_params)
-        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
-
-    master_agent.add_agent(current_time)?;
-
-    agents.transfer_ownership(ctx.accounts.transfer_authority.key(), current_time)?;
-    agents.list(current_time)?;
+    master_agent.record_agent_sale()?;
+    let relist_policy = master_agent.take_back_agent(current_time)?;
+
+    if relist_policy == RelistPolicy::Burn {
+        let burn_agent_params = BurnAgentParams {
+            owner: ctx.accounts.authority.to_account_info(),
+            token_account: ctx.accounts.user_agent_ta.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            master_edition: ctx.accounts.master_edition.to_account_info(),
+            metadata_program: ctx.accounts.metadata_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+        };
+
+        t_yield
+            .burn_agent(burn_agent_params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+    } else {
+        let transfer_agent_params = TransferAgentParams {
+            payer: ctx.accounts.authority.to_account_info(),
+            sender_nft_token_account: ctx.accounts.user_agent_ta.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+            receiver_token_account: ctx.accounts.transfer_authority_ta.to_account_info(),
+            receiver: ctx.accounts.transfer_authority.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            metadata_program: ctx.accounts.metadata_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+        };
+
+        t_yield
+            .transfer_agent(transfer_agent_params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+        agents.return_to_protocol(
+            ctx.accounts.transfer_authority.key(),
+            master_agent.get_relist_time(current_time)?,
+            current_time,
+        )?;
+    }
 
     user.remove_agent(1)?;
     user.history.add_fees_spent(price.1)?;
@@ -245,10 +364,18 @@ pub fn sell_agent<'info>(ctx: Context<'_, '_, '_, 'info, SellAgent<'info>>) -> T
 
     emit_cpi!(SellAgentEvent {
         agent: agents.key(),
-        owner: agents.owner,
+        owner: ctx.accounts.authority.key(),
         master_agent: agents.master_agent,
+        relist_policy: relist_policy as u8,
         timestamp: current_time
     });
 
+    if relist_policy == RelistPolicy::Burn {
+        ctx.accounts
+            .agent
+            .close(ctx.accounts.authority.to_account_info())
+            .map_err(|_| ErrorCode::AccountFromError)?;
+    }
+
     Ok(())
 }
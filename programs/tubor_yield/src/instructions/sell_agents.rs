@@ -0,0 +1,333 @@
+//! Instruction: Sell Agents
+//!
+//! Allows a user to sell several agent NFTs of the same master agent back to the protocol in one instruction.
+//! The agents are read from `remaining_accounts` in groups of `AgentBatchAccounts::SELL_LEN`
+//! (`[agent, mint, metadata, master_edition, transfer_authority_ta, user_agent_ta]`), followed by the
+//! upline referral registries. Proceeds are paid with a single Y-mint transfer and the user,
+//! master agent and protocol counters are updated once. The agents follow the master agent relist
+//! policy like in `sell_agent`: relisted, held unlisted, or burned with their agent PDAs closed to the
+//! seller when the master agent opted into burning.
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::{SafeMath, MAX_AGENT_BATCH_SIZE},
+    state::{
+        AgentBatchAccounts, BurnAgentParams, MasterAgent, ReferralEarningEvent, ReferralLink,
+        ReferralRegistry, RelistPolicy, SellAgentsEvent, TYield, TransferAgentParams, User,
+        UserStatus,
+    },
+    try_from,
+};
+
+/// Parameters for selling several agents.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SellAgentsParams {
+    /// Number of agents to sell (at most `MAX_AGENT_BATCH_SIZE`)
+    pub count: u8,
+    /// Minimum net proceeds (after tax) the seller accepts for the whole batch
+    pub min_net_proceeds: u64,
+    /// Unix timestamp after which the sale is rejected
+    pub expiry: i64,
+}
+
+/// Accounts required for selling several agent NFTs back to the protocol.
+///
+/// Per-agent accounts are passed through `remaining_accounts`.
+#[derive(Accounts)]
+pub struct SellAgents<'info> {
+    /// The user selling the agents. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Instructions sysvar (required for Metaplex CPI).
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+
+    /// CHECK: Metaplex token metadata program.
+    #[account(address = METADATA_PROGRAM_ID)]
+    pub metadata_program: AccountInfo<'info>,
+
+    // --- Protocol/User State ---
+    /// User account PDA.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Referral registry of the seller's direct referrer. Required when the seller has a
+    /// referrer, who is credited `ref_earn_percentage` of the tax.
+    /// PDA: ["referral_registry", user.referrer]
+    #[account(
+        mut,
+        seeds = [b"referral_registry".as_ref(), user.referrer.as_ref()],
+        bump = referral_registry.bump
+    )]
+    pub referral_registry: Option<Box<Account<'info, ReferralRegistry>>>,
+
+    /// Referral link between the seller and their referrer. Required when the seller has a
+    /// referrer. The registries of all higher referral levels are read from `remaining_accounts`
+    /// after the agent groups (nearest first).
+    /// PDA: ["referral_link", user.referrer, authority]
+    #[account(
+        seeds = [b"referral_link".as_ref(), user.referrer.as_ref(), authority.key().as_ref()],
+        bump = referral_link.bump
+    )]
+    pub referral_link: Option<Box<Account<'info, ReferralLink>>>,
+
+    /// Master agent account PDA (the parent/master of the agents).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// CHECK: Transfer authority PDA (protocol authority for token/NFT transfers).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    // --- Token Accounts ---
+    /// User's Y-mint token account (receiver of payment).
+    /// Must have mint == t_yield.y_mint.
+    #[account(
+        mut,
+        constraint = user_y_mint_ta.mint == t_yield.y_mint
+    )]
+    pub user_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol's Y-mint token account (payer of payment).
+    /// Must have mint == t_yield.y_mint.
+    #[account(
+        mut,
+        constraint = transfer_authority_y_mint_ta.mint == t_yield.y_mint
+    )]
+    pub transfer_authority_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Y-mint SPL token mint (payment token for protocol).
+    #[account(address = t_yield.y_mint)]
+    pub y_mint: AccountInfo<'info>,
+
+    // --- Misc ---
+    /// CHECK: Event authority for CPI event logs (used for event emission; not written to).
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Sell Agents
+///
+/// Returns `params.count` agent NFTs to the protocol for a single payout, settles their accrued
+/// yield, updates protocol/user state once, and emits one aggregated event. Rejects the batch if
+/// its net proceeds are below `params.min_net_proceeds` or it lands after `params.expiry`.
+pub fn sell_agents<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SellAgents<'info>>,
+    params: SellAgentsParams,
+) -> TYieldResult<()> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    if current_time > params.expiry {
+        return Err(ErrorCode::TransactionExpired);
+    }
+    let count = params.count as usize;
+    if count > MAX_AGENT_BATCH_SIZE {
+        return Err(ErrorCode::InvalidAccount);
+    }
+
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let user = ctx.accounts.user.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+
+    if user.has_status(UserStatus::Banned) {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    // Holders of a sunset master agent exit through `claim_sunset_buyback`
+    if master_agent.is_sunset() {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+    let mut batch = AgentBatchAccounts::load_sell_batch(
+        ctx.remaining_accounts,
+        count,
+        &master_agent.key(),
+        &ctx.accounts.transfer_authority.key(),
+        &ctx.accounts.authority.key(),
+    )?;
+
+    // Settle yield earned while held before the agents return to the protocol
+    let mut accrued_yield: u64 = 0;
+    for accounts in batch.iter_mut() {
+        if !accounts.agent.is_owned_by(&ctx.accounts.authority.key())
+            || !accounts.agent.is_transferable()
+        {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        accrued_yield =
+            accrued_yield.safe_add(accounts.agent.settle_yield(master_agent, current_time)?)?;
+    }
+    if accrued_yield > 0 {
+        user.add_unclaimed_yield(accrued_yield)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(accrued_yield)?;
+    }
+
+    let price =
+        master_agent.calculate_sell_quote_with_limit(count as u64, params.min_net_proceeds)?;
+
+    let mint =
+        try_from!(Account<Mint>, ctx.accounts.y_mint).map_err(|_| ErrorCode::AccountFromError)?;
+
+    TYield::transfer_tokens(
+        ctx.accounts.transfer_authority_y_mint_ta.to_account_info(),
+        mint.to_account_info(),
+        ctx.accounts.user_y_mint_ta.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        price.0, // net_price (what user receives)
+        mint.decimals,
+    )
+    .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    // --- Referral shares of the tax ---
+    let referrals = t_yield.credit_referrals(
+        user,
+        ctx.accounts
+            .referral_registry
+            .as_deref_mut()
+            .map(|registry| &mut **registry),
+        ctx.accounts.referral_link.as_deref().map(|link| &**link),
+        &ctx.remaining_accounts[count * AgentBatchAccounts::SELL_LEN..],
+        price.1,
+        current_time,
+    )?;
+    let mut referral_fee = 0;
+    for (referrer, level, fee) in referrals {
+        referral_fee = referral_fee.safe_add(fee)?;
+        emit_cpi!(ReferralEarningEvent {
+            referrer,
+            referred_user: user.authority,
+            level,
+            amount: fee,
+            tax: price.1,
+            timestamp: current_time,
+        });
+    }
+    t_yield.protocol_total_fees = t_yield
+        .protocol_total_fees
+        .safe_add(price.1.safe_sub(referral_fee)?)?;
+    t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_sub(price.0)?;
+    t_yield.protocol_total_balance_usd = t_yield.protocol_total_balance_usd.safe_sub(price.1)?;
+    t_yield.protocol_total_earnings = t_yield.protocol_total_earnings.safe_add(price.1)?;
+
+    // --- Return agent NFTs to the protocol ---
+    let relist_policy = master_agent.get_relist_policy();
+    let relist_at = master_agent.get_relist_time(current_time)?;
+    let mut agents = Vec::with_capacity(count);
+    for accounts in batch.iter_mut() {
+        agents.push(accounts.agent.key());
+
+        if relist_policy == RelistPolicy::Burn {
+            let burn_agent_params = BurnAgentParams {
+                owner: ctx.accounts.authority.to_account_info(),
+                token_account: accounts.user_agent_ta.to_account_info(),
+                mint: accounts.mint.clone(),
+                metadata: accounts.metadata.clone(),
+                master_edition: accounts
+                    .master_edition
+                    .clone()
+                    .ok_or(ErrorCode::InvalidAccount)?,
+                metadata_program: ctx.accounts.metadata_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+            };
+            t_yield
+                .burn_agent(burn_agent_params)
+                .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+            accounts
+                .agent
+                .close(ctx.accounts.authority.to_account_info())
+                .map_err(|_| ErrorCode::AccountFromError)?;
+            continue;
+        }
+
+        let transfer_agent_params = TransferAgentParams {
+            payer: ctx.accounts.authority.to_account_info(),
+            sender_nft_token_account: accounts.user_agent_ta.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+            receiver_token_account: accounts.transfer_authority_ta.to_account_info(),
+            receiver: ctx.accounts.transfer_authority.to_account_info(),
+            mint: accounts.mint.clone(),
+            metadata: accounts.metadata.clone(),
+            metadata_program: ctx.accounts.metadata_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+        };
+        t_yield
+            .transfer_agent(transfer_agent_params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+        let agent = &mut accounts.agent;
+        agent.return_to_protocol(
+            ctx.accounts.transfer_authority.key(),
+            relist_at,
+            current_time,
+        )?;
+        agent
+            .exit(&crate::ID)
+            .map_err(|_| ErrorCode::AccountFromError)?;
+    }
+
+    master_agent.take_back_agents(count as u64, current_time)?;
+    master_agent.record_agent_sales(count as u64)?;
+
+    user.remove_agents(params.count as u32)?;
+    user.history.add_fees_spent(price.1)?;
+    user.validate_user()?;
+
+    emit_cpi!(SellAgentsEvent {
+        agents,
+        owner: ctx.accounts.authority.key(),
+        master_agent: master_agent.key(),
+        net_price: price.0,
+        tax_amount: price.1,
+        relist_policy: relist_policy as u8,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
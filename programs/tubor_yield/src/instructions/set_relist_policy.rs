@@ -0,0 +1,121 @@
+//! Instruction: Set Relist Policy
+//!
+//! Sets what happens to agents returned to a master agent by a sale, redemption or sunset buyback
+//! (with multisig approval): relisted immediately, relisted after a cooldown, or burned. Burning
+//! permanently shrinks the supply, so it only applies once opted into here or at mint time. While
+//! `auto_relist` is disabled, agents that would be relisted are held unlisted by the protocol.
+//!
+//! Accounts:
+//! - admin: The signer proposing/signing the update (must be a multisig signer)
+//! - multisig: Protocol multisig PDA (controls admin actions)
+//! - t_yield: Protocol global state/config PDA
+//! - master_agent: Master agent whose relist policy is updated (mut)
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{
+        AdminInstruction, MasterAgent, Multisig, RelistPolicy, RelistPolicyUpdatedEvent, TYield,
+    },
+};
+
+/// Parameters for setting the relist policy.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetRelistPolicyParams {
+    /// What happens to agents returned to the protocol
+    pub relist_policy: RelistPolicy,
+    /// Seconds returned agents stay unlisted under the cooldown policy
+    pub relist_cooldown: i64,
+}
+
+#[derive(Accounts)]
+pub struct SetRelistPolicy<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Master agent whose relist policy is updated.
+    /// Seeds: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent", master_agent.mint.as_ref()],
+        bump = master_agent.bump
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn set_relist_policy<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetRelistPolicy<'info>>,
+    params: SetRelistPolicyParams,
+) -> TYieldResult<u8> {
+    let mut multisig = ctx
+        .accounts
+        .multisig
+        .load_mut()
+        .map_err(|_| ErrorCode::InvalidBump)?;
+
+    let instruction_data =
+        Multisig::get_instruction_data(AdminInstruction::SetRelistPolicy, &params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let nonce = current_time as u64;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &instruction_data,
+        nonce,
+        current_time,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let old_policy = master_agent.relist_policy;
+    master_agent.set_relist_cooldown(params.relist_cooldown, current_time)?;
+    master_agent.set_relist_policy(params.relist_policy, current_time)?;
+
+    msg!(
+        "Relist policy updated for master agent {}",
+        master_agent.key()
+    );
+
+    emit_cpi!(RelistPolicyUpdatedEvent {
+        master_agent: master_agent.key(),
+        old_policy,
+        new_policy: params.relist_policy as u8,
+        relist_cooldown: params.relist_cooldown,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -0,0 +1,115 @@
+//! Instruction: Set Whitelist Root
+//!
+//! Sets the Merkle root of the wallets allowed to buy from a master agent in whitelist mode
+//! (with multisig approval). Buyers prove their membership in `buy_agent`/`buy_agents`, so a
+//! large allowlist costs a single multisig round. An all-zero root falls back to the user
+//! whitelist flag.
+//!
+//! Accounts:
+//! - admin: The signer proposing/signing the update (must be a multisig signer)
+//! - multisig: Protocol multisig PDA (controls admin actions)
+//! - t_yield: Protocol global state/config PDA
+//! - master_agent: Master agent whose whitelist is updated (mut)
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{AdminInstruction, MasterAgent, Multisig, TYield, WhitelistRootUpdatedEvent},
+};
+
+/// Parameters for setting the whitelist Merkle root.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetWhitelistRootParams {
+    /// Merkle root of the whitelisted wallets (all zeros = use the user whitelist flag)
+    pub root: [u8; 32],
+}
+
+#[derive(Accounts)]
+pub struct SetWhitelistRoot<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Master agent whose whitelist is updated.
+    /// Seeds: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent", master_agent.mint.as_ref()],
+        bump = master_agent.bump
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn set_whitelist_root<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetWhitelistRoot<'info>>,
+    params: SetWhitelistRootParams,
+) -> TYieldResult<u8> {
+    let mut multisig = ctx
+        .accounts
+        .multisig
+        .load_mut()
+        .map_err(|_| ErrorCode::InvalidBump)?;
+
+    let instruction_data =
+        Multisig::get_instruction_data(AdminInstruction::SetWhitelistRoot, &params)
+            .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let nonce = current_time as u64;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &instruction_data,
+        nonce,
+        current_time,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let old_root = master_agent.whitelist_root;
+    master_agent.set_whitelist_root(params.root, current_time);
+
+    msg!(
+        "Whitelist root updated for master agent {}",
+        master_agent.key()
+    );
+
+    emit_cpi!(WhitelistRootUpdatedEvent {
+        master_agent: master_agent.key(),
+        old_root,
+        new_root: params.root,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -0,0 +1,106 @@
+//! Instruction: Settle Auction
+//!
+//! Finalizes the clearing price of a Dutch auction once its window has closed or the supply is sold out.
+//! Anyone can settle. Settlement re-enables fixed-price `buy_agent`, books the tax owed at the clearing
+//! price as protocol fees and tops up the master agent NAV from the floor price to the clearing price.
+//!
+//! Accounts:
+//! - authority: Any signer
+//! - t_yield: Protocol global state/config PDA (mut, books fees)
+//! - master_agent: Master agent whose supply was auctioned (mut)
+//! - auction: Auction PDA for the master agent (mut)
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::TYieldResult,
+    math::SafeMath,
+    state::{AgentAuction, AuctionSettledEvent, MasterAgent, TYield},
+};
+
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Master agent whose supply was auctioned.
+    /// Seeds: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent", master_agent.mint.as_ref()],
+        bump = master_agent.bump
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Auction PDA for the master agent.
+    /// Seeds: ["auction", master_agent]
+    #[account(
+        mut,
+        seeds = [b"auction", master_agent.key().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, AgentAuction>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn settle_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleAuction<'info>>,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let t_yield = ctx.accounts.t_yield.as_mut();
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let auction = ctx.accounts.auction.as_mut();
+
+    // 1. Finalize the clearing price
+    let clearing_price = auction.settle(master_agent.agent_count == 0, current_time)?;
+    master_agent.auction_live = false;
+
+    // 2. Book the tax owed at the clearing price; refunds stay reserved until claimed
+    let (total_price, tax_amount, _) = auction.calculate_price_with_tax(clearing_price)?;
+    let total_tax = tax_amount.safe_mul(auction.total_sold)?;
+    t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_sub(total_tax)?;
+    t_yield.protocol_total_fees = t_yield.protocol_total_fees.safe_add(total_tax)?;
+    t_yield.protocol_total_balance_usd = t_yield.protocol_total_balance_usd.safe_add(total_tax)?;
+    t_yield.protocol_total_earnings = t_yield
+        .protocol_total_earnings
+        .safe_add(total_price.safe_mul(auction.total_sold)?)?;
+
+    // 3. Agents were booked at the floor price; top the NAV up to the clearing price
+    master_agent.nav = master_agent.nav.safe_add(
+        clearing_price
+            .safe_sub(auction.floor_price)?
+            .safe_mul(auction.total_sold)?,
+    )?;
+    master_agent.last_updated = current_time;
+
+    msg!(
+        "Auction settled at {} with {} agents sold",
+        clearing_price,
+        auction.total_sold
+    );
+
+    emit_cpi!(AuctionSettledEvent {
+        master_agent: master_agent.key(),
+        clearing_price,
+        total_sold: auction.total_sold,
+        total_raised: auction.total_raised,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
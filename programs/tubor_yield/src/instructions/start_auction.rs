@@ -0,0 +1,158 @@
+//! Instruction: Start Auction
+//!
+//! Starts a Dutch auction for the unsold supply of a master agent (with multisig approval).
+//! The price decays from `start_price` to the current master agent price over `duration` seconds,
+//! linearly or in steps of `step_duration` seconds. Fixed-price `buy_agent` is disabled until the
+//! auction is settled.
+//!
+//! Accounts:
+//! - admin: The signer proposing/signing the auction (must be a multisig signer, pays for the auction account)
+//! - multisig: Protocol multisig PDA (controls admin actions)
+//! - t_yield: Protocol global state/config PDA
+//! - master_agent: Master agent whose supply is auctioned (mut)
+//! - auction: Auction PDA for the master agent (created if needed)
+//! - system_program: Solana system program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{
+        AdminInstruction, AgentAuction, AuctionStartedEvent, MasterAgent, Multisig, Size, TYield,
+    },
+};
+
+/// Parameters for starting a Dutch auction.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct StartAuctionParams {
+    /// Price of the first bid in y_mint base units (must be above the master agent price)
+    pub start_price: u64,
+    /// Length of the auction window in seconds
+    pub duration: i64,
+    /// Length of each price step in seconds (0 = linear decay)
+    pub step_duration: i64,
+}
+
+#[derive(Accounts)]
+pub struct StartAuction<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Master agent whose supply is auctioned.
+    /// Seeds: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent", master_agent.mint.as_ref()],
+        bump = master_agent.bump
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Auction PDA. Reused by later auctions of the same master agent.
+    /// Seeds: ["auction", master_agent]
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = AgentAuction::SIZE,
+        seeds = [b"auction", master_agent.key().as_ref()],
+        bump
+    )]
+    pub auction: Box<Account<'info, AgentAuction>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn start_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, StartAuction<'info>>,
+    params: StartAuctionParams,
+) -> TYieldResult<u8> {
+    let mut multisig = ctx
+        .accounts
+        .multisig
+        .load_mut()
+        .map_err(|_| ErrorCode::InvalidBump)?;
+
+    let instruction_data = Multisig::get_instruction_data(AdminInstruction::StartAuction, &params)
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let nonce = current_time as u64;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &instruction_data,
+        nonce,
+        current_time,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    if !master_agent.is_active()
+        || master_agent.agent_count == 0
+        || master_agent.auction_live
+        || master_agent.is_presale_active(current_time)
+        || master_agent.is_sunset()
+    {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    let auction = ctx.accounts.auction.as_mut();
+    auction.start(
+        master_agent.key(),
+        params.start_price,
+        master_agent.price,
+        master_agent.tax_config.buy_tax_percentage,
+        current_time,
+        params.duration,
+        params.step_duration,
+        ctx.bumps.auction,
+    )?;
+    master_agent.auction_live = true;
+
+    msg!(
+        "Auction started: {} -> {} over {}s",
+        auction.start_price,
+        auction.floor_price,
+        params.duration
+    );
+
+    emit_cpi!(AuctionStartedEvent {
+        master_agent: master_agent.key(),
+        start_price: auction.start_price,
+        floor_price: auction.floor_price,
+        start_time: auction.start_time,
+        end_time: auction.end_time,
+        step_duration: auction.step_duration,
+    });
+
+    Ok(0)
+}
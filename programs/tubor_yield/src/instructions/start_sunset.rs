@@ -0,0 +1,189 @@
+//! Instruction: Start Sunset
+//!
+//! Retires a master agent (with multisig approval). The master agent switches to sunset mode, which
+//! blocks new buys and trades for good, and every outstanding agent can be redeemed at
+//! `buyback_price` until `deadline` through `claim_sunset_buyback`. The NAV backing the outstanding
+//! agents is replaced by the buyback reserve: a shortfall is covered from withdrawable protocol fees
+//! and a surplus is booked as protocol fees.
+//!
+//! Accounts:
+//! - admin: The signer proposing/signing the sunset (must be a multisig signer, pays for the sunset account)
+//! - multisig: Protocol multisig PDA (controls admin actions)
+//! - t_yield: Protocol global state/config PDA (mut, tracks the buyback reserve)
+//! - master_agent: Master agent being retired (mut)
+//! - sunset: Sunset PDA for the master agent (created)
+//! - transfer_authority: Transfer authority PDA (owner of the protocol vault)
+//! - transfer_authority_y_mint_ta: Protocol's Y-mint vault funding the buyback
+//! - system_program: Solana system program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{
+        AdminInstruction, AgentSunset, MasterAgent, Multisig, Size, SunsetStartedEvent, TYield,
+    },
+};
+
+/// Parameters for sunsetting a master agent.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct StartSunsetParams {
+    /// Price paid for every redeemed agent in y_mint base units
+    pub buyback_price: u64,
+    /// Unix timestamp after which agents can no longer be redeemed
+    pub deadline: i64,
+}
+
+#[derive(Accounts)]
+pub struct StartSunset<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Master agent being retired.
+    /// Seeds: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent", master_agent.mint.as_ref()],
+        bump = master_agent.bump
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Sunset PDA. A master agent can only be sunset once.
+    /// Seeds: ["sunset", master_agent]
+    #[account(
+        init,
+        payer = admin,
+        space = AgentSunset::SIZE,
+        seeds = [b"sunset", master_agent.key().as_ref()],
+        bump
+    )]
+    pub sunset: Box<Account<'info, AgentSunset>>,
+
+    /// CHECK: Transfer authority PDA (owner of the protocol vault).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    /// Protocol's Y-mint vault funding the buyback.
+    #[account(
+        constraint = transfer_authority_y_mint_ta.mint == t_yield.y_mint,
+        constraint = transfer_authority_y_mint_ta.owner == transfer_authority.key()
+    )]
+    pub transfer_authority_y_mint_ta: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn start_sunset<'info>(
+    ctx: Context<'_, '_, '_, 'info, StartSunset<'info>>,
+    params: StartSunsetParams,
+) -> TYieldResult<u8> {
+    let mut multisig = ctx
+        .accounts
+        .multisig
+        .load_mut()
+        .map_err(|_| ErrorCode::InvalidBump)?;
+
+    let instruction_data = Multisig::get_instruction_data(AdminInstruction::StartSunset, &params)
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let nonce = current_time as u64;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &instruction_data,
+        nonce,
+        current_time,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+    let sunset = ctx.accounts.sunset.as_mut();
+
+    let released_nav = master_agent.start_sunset(current_time)?;
+    sunset.start(
+        master_agent.key(),
+        params.buyback_price,
+        master_agent.held_agents,
+        current_time,
+        params.deadline,
+        ctx.bumps.sunset,
+    )?;
+
+    // --- Swap the released NAV for the buyback reserve ---
+    if sunset.reserved > released_nav {
+        let shortfall = sunset.reserved.safe_sub(released_nav)?;
+        let withdrawable =
+            t_yield.get_withdrawable_fees(ctx.accounts.transfer_authority_y_mint_ta.amount)?;
+        if shortfall > withdrawable {
+            msg!(
+                "Buyback shortfall {} exceeds withdrawable fees {}",
+                shortfall,
+                withdrawable
+            );
+            return Err(ErrorCode::InsufficientFunds);
+        }
+        t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_add(shortfall)?;
+        t_yield.protocol_total_fees = t_yield.protocol_total_fees.safe_sub(shortfall)?;
+    } else {
+        let surplus = released_nav.safe_sub(sunset.reserved)?;
+        t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_sub(surplus)?;
+        t_yield.protocol_total_fees = t_yield.protocol_total_fees.safe_add(surplus)?;
+    }
+
+    msg!(
+        "Master agent sunset: {} agents redeemable at {} until {}",
+        sunset.outstanding,
+        sunset.buyback_price,
+        sunset.deadline
+    );
+
+    emit_cpi!(SunsetStartedEvent {
+        master_agent: master_agent.key(),
+        buyback_price: sunset.buyback_price,
+        outstanding: sunset.outstanding,
+        reserved: sunset.reserved,
+        deadline: sunset.deadline,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -0,0 +1,132 @@
+//! Instruction: Sweep Sunset
+//!
+//! Closes the buyback of a sunset master agent once its deadline has passed and moves the funds
+//! reserved for agents that were never redeemed to the treasury. Anyone can sweep.
+//!
+//! Accounts:
+//! - authority: Any signer
+//! - t_yield: Protocol global state/config PDA (mut, releases the buyback reserve)
+//! - master_agent: Sunset master agent
+//! - sunset: Sunset PDA for the master agent (mut)
+//! - transfer_authority: PDA that owns the protocol token account
+//! - y_mint: Protocol yield token mint
+//! - protocol_token_account: Protocol token account holding the buyback reserve (mut)
+//! - treasury_token_account: Token account owned by `t_yield.treasury` (mut)
+//! - token_program: SPL Token program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{AgentSunset, MasterAgent, SunsetSweptEvent, TYield},
+    try_from,
+};
+
+#[derive(Accounts)]
+pub struct SweepSunset<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Sunset master agent.
+    /// Seeds: ["master_agent", master_agent.mint]
+    #[account(
+        seeds = [b"master_agent", master_agent.mint.as_ref()],
+        bump = master_agent.bump
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Sunset PDA for the master agent.
+    /// Seeds: ["sunset", master_agent]
+    #[account(
+        mut,
+        seeds = [b"sunset", master_agent.key().as_ref()],
+        bump = sunset.bump
+    )]
+    pub sunset: Box<Account<'info, AgentSunset>>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    ///CHECK: y_mint
+    #[account(address = t_yield.y_mint)]
+    pub y_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = protocol_token_account.mint == t_yield.y_mint,
+        constraint = protocol_token_account.owner == transfer_authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub protocol_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.mint == t_yield.y_mint,
+        constraint = treasury_token_account.owner == t_yield.treasury @ ErrorCode::InvalidAccount,
+    )]
+    pub treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn sweep_sunset<'info>(
+    ctx: Context<'_, '_, '_, 'info, SweepSunset<'info>>,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let t_yield = ctx.accounts.t_yield.as_mut();
+    let sunset = ctx.accounts.sunset.as_mut();
+
+    let amount = sunset.sweep(current_time)?;
+    if amount > 0 {
+        let mint = try_from!(Account<Mint>, ctx.accounts.y_mint)
+            .map_err(|_| ErrorCode::AccountFromError)?;
+        TYield::transfer_tokens(
+            ctx.accounts.protocol_token_account.to_account_info(),
+            mint.to_account_info(),
+            ctx.accounts.treasury_token_account.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            amount,
+            mint.decimals,
+        )
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+        t_yield.protocol_current_holding = t_yield.protocol_current_holding.safe_sub(amount)?;
+    }
+
+    msg!(
+        "Sunset swept: {} to treasury, {} agents unredeemed",
+        amount,
+        sunset.outstanding
+    );
+
+    emit_cpi!(SunsetSweptEvent {
+        master_agent: ctx.accounts.master_agent.key(),
+        treasury: t_yield.treasury,
+        amount,
+        unredeemed_agents: sunset.outstanding,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -1,21 +1,60 @@
+//! Instruction: Transfer Agent
+//!
+//! Transfers an agent NFT from its current owner to another registered user.
+//! Settles the sender's pending yield first, moves the NFT, updates both `User` accounts and the agent owner.
+//! Enforces bans on both sides and the master agent's whitelist mode.
+//!
+//! Accounts:
+//! - authority: The current owner of the agent (signer)
+//! - new_owner: Wallet receiving the agent
+//! - user: User account PDA of the sender
+//! - new_owner_user: User account PDA of the receiver
+//! - agent: Agent account PDA (the agent being transferred)
+//! - master_agent: Master agent account PDA (parent/master of the agent)
+//! - mint: Mint account for the agent NFT
+//! - metadata: Metadata account for the agent NFT (Metaplex)
+//! - sender_agent_ta: Sender's token account holding the agent NFT
+//! - receiver_agent_ta: Receiver's token account for the agent NFT (created if needed)
+//! - t_yield: Protocol global state/config PDA
+//! - system_program, token_program, associated_token_program: Solana programs
+//! - sysvar_instructions: Instructions sysvar (for Metaplex CPI)
+//! - metadata_program: Metaplex token metadata program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
 use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
 
 use crate::{
     error::{ErrorCode, TYieldResult},
-    state::{Agent, TYield, User},
+    math::SafeMath,
+    state::{Agent, MasterAgent, TYield, TransferAgentEvent, User},
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct TransferAgentParams {
     pub new_owner: Pubkey,
+    /// Merkle proof of the new owner's wallet, required when the master agent has a whitelist root
+    pub whitelist_proof: Vec<[u8; 32]>,
 }
 
 #[derive(Accounts)]
 #[instruction(params: TransferAgentParams)]
 pub struct TransferAgent<'info> {
+    /// The current owner of the agent. Must sign the transaction.
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    /// CHECK: Wallet receiving the agent, validated against `params.new_owner`.
+    #[account(address = params.new_owner)]
+    pub new_owner: AccountInfo<'info>,
+
+    /// User account PDA of the sender.
+    /// PDA: ["user", authority]
     #[account(
         mut,
         seeds = [b"user", authority.key().as_ref()],
@@ -23,13 +62,70 @@ pub struct TransferAgent<'info> {
     )]
     pub user: Box<Account<'info, User>>,
 
+    /// User account PDA of the receiver.
+    /// PDA: ["user", new_owner]
+    #[account(
+        mut,
+        seeds = [b"user", params.new_owner.as_ref()],
+        bump = new_owner_user.bump
+    )]
+    pub new_owner_user: Box<Account<'info, User>>,
+
+    /// Agent account PDA (the agent being transferred).
+    /// PDA: ["agent", mint]
     #[account(
         mut,
-        seeds = [b"agent", agent.key().as_ref()],
+        seeds = [b"agent".as_ref(), mint.key().as_ref()],
         bump = agent.bump
     )]
     pub agent: Box<Account<'info, Agent>>,
 
+    /// Master agent account PDA (the parent/master of the agent).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+        constraint = agent.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Mint account for the agent NFT.
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Metadata account for the agent NFT (validated by Metaplex CPI).
+    /// PDA: ["metadata", METADATA_PROGRAM_ID, mint]
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            METADATA_PROGRAM_ID.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = METADATA_PROGRAM_ID,
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    /// Sender's token account holding the agent NFT.
+    #[account(
+        mut,
+        constraint = sender_agent_ta.mint == mint.key(),
+        constraint = sender_agent_ta.owner == authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub sender_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Receiver's token account for the agent NFT.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = new_owner,
+    )]
+    pub receiver_agent_ta: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
     #[account(
         mut,
         seeds = [b"t_yield"],
@@ -37,14 +133,36 @@ pub struct TransferAgent<'info> {
     )]
     pub t_yield: Box<Account<'info, TYield>>,
 
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Instructions sysvar (required for Metaplex CPI).
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+
+    /// CHECK: Metaplex token metadata program.
+    #[account(address = METADATA_PROGRAM_ID)]
+    pub metadata_program: AccountInfo<'info>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
     #[account(
-        mut,
-        seeds = [b"user", params.new_owner.as_ref()],
-        bump
+        seeds = [b"__event_authority"],
+        bump,
     )]
-    pub new_owner_user: Box<Account<'info, User>>,
+    pub event_authority: AccountInfo<'info>,
 }
 
+/// Instruction: Transfer Agent
+///
+/// Moves an agent from its owner to another user. Pending yield is settled to the sender
+/// so the receiver only earns from the moment of transfer.
 pub fn transfer_agent<'info>(
     ctx: Context<'_, '_, '_, 'info, TransferAgent<'info>>,
     params: TransferAgentParams,
@@ -53,26 +171,76 @@ pub fn transfer_agent<'info>(
     let sender = ctx.accounts.user.as_mut();
     let receiver = ctx.accounts.new_owner_user.as_mut();
     let agent = ctx.accounts.agent.as_mut();
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
 
-    // 1. Check sender is the current owner
-    if !agent.is_owned_by(&sender.authority) {
+    // 1. Check sender is the current owner, not sending to themselves and not renting the agent out
+    if !agent.is_owned_by(&sender.authority)
+        || params.new_owner == sender.authority
+        || !agent.is_transferable()
+    {
         return Err(ErrorCode::CannotPerformAction);
     }
-    // 2. Check sender is not banned
-    if sender.is_banned() {
+    // 2. Check neither side is banned or inactive
+    if !sender.can_perform_actions() || !receiver.can_perform_actions() {
         return Err(ErrorCode::CannotPerformAction);
     }
-    // 3. Check receiver is not banned
-    if receiver.is_banned() {
+    // 3. Whitelisted master agents can only be held by whitelisted users
+    if master_agent.is_whitelist_mode()
+        && !master_agent.is_wallet_whitelisted(
+            &params.new_owner,
+            &params.whitelist_proof,
+            receiver.is_whitelisted(),
+        )
+    {
         return Err(ErrorCode::CannotPerformAction);
     }
-    // 4. Update agent ownership
+
+    // 4. Settle yield earned by the sender up to now
+    let yield_settled = agent.settle_yield(master_agent, current_time)?;
+    if yield_settled > 0 {
+        sender.add_unclaimed_yield(yield_settled)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(yield_settled)?;
+    }
+
+    // 5. Move the agent NFT
+    let transfer_agent_params = crate::state::TransferAgentParams {
+        payer: ctx.accounts.authority.to_account_info(),
+        sender_nft_token_account: ctx.accounts.sender_agent_ta.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+        receiver_token_account: ctx.accounts.receiver_agent_ta.to_account_info(),
+        receiver: ctx.accounts.new_owner.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+        metadata_program: ctx.accounts.metadata_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+    };
+    t_yield
+        .transfer_agent(transfer_agent_params)
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    // 6. Update agent ownership and user stats
     agent.transfer_ownership(params.new_owner, current_time)?;
-    // 5. Update user stats
     sender.remove_agent(1)?;
-    receiver.add_agent(1)?;
-    // 6. Validate both users
+    receiver.receive_agent()?;
+    sender.update_last_activity(current_time)?;
+    receiver.update_last_activity(current_time)?;
+
+    // 7. Validate both users
     sender.validate_user()?;
     receiver.validate_user()?;
+
+    emit_cpi!(TransferAgentEvent {
+        agent: agent.key(),
+        from: sender.authority,
+        to: params.new_owner,
+        master_agent: agent.master_agent,
+        yield_settled,
+        timestamp: current_time,
+    });
+
     Ok(0)
 }
@@ -0,0 +1,211 @@
+//! Instruction: Unstake Agent Shares
+//!
+//! Returns staked shares of a fractionalized agent to their holder. The holder's pending share of
+//! the agent's yield is credited to their unclaimed yield first; from then on the part of the
+//! unstaked shares is retained by the vault until the agent is reassembled.
+//! Unstaking zero shares only settles the pending yield.
+//!
+//! Accounts:
+//! - authority: The share holder (signer)
+//! - system_program, token_program, associated_token_program: Solana programs
+//! - user: User account PDA
+//! - agent: Agent account PDA (the fractionalized agent)
+//! - master_agent: Master agent account PDA (parent/master of the agent)
+//! - agent_vault: Vault PDA of the agent
+//! - share_position: Share position PDA of the holder
+//! - share_mint: Share mint of the agent
+//! - t_yield: Protocol global state/config PDA
+//! - transfer_authority: Transfer authority PDA (custodian of staked shares)
+//! - vault_share_ta: Protocol's token account holding staked shares (sender)
+//! - user_share_ta: User's token account receiving the shares
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    math::SafeMath,
+    state::{
+        Agent, AgentVault, MasterAgent, SharePosition, SharePositionEvent, TYield, User, UserStatus,
+    },
+};
+
+/// Parameters for unstaking agent shares.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UnstakeAgentSharesParams {
+    /// Number of shares to unstake
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeAgentShares<'info> {
+    /// The share holder. Must sign the transaction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // --- System & Program Accounts ---
+    /// Solana system program.
+    pub system_program: Program<'info, System>,
+    /// SPL token program.
+    pub token_program: Program<'info, Token>,
+    /// SPL associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    // --- Protocol/User/Agent State ---
+    /// User account PDA.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = user.bump
+    )]
+    pub user: Box<Account<'info, User>>,
+
+    /// Agent account PDA (the fractionalized agent).
+    /// PDA: ["agent", agent.mint]
+    #[account(
+        mut,
+        seeds = [b"agent".as_ref(), agent.mint.as_ref()],
+        bump = agent.bump,
+        constraint = agent.is_owned_by(&agent_vault.key()) @ ErrorCode::InvalidAccount,
+    )]
+    pub agent: Box<Account<'info, Agent>>,
+
+    /// Master agent account PDA (parent/master of the agent).
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+        constraint = agent.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub master_agent: Box<Account<'info, MasterAgent>>,
+
+    /// Vault PDA of the agent.
+    /// PDA: ["agent_vault", agent]
+    #[account(
+        mut,
+        seeds = [b"agent_vault".as_ref(), agent.key().as_ref()],
+        bump = agent_vault.bump,
+    )]
+    pub agent_vault: Box<Account<'info, AgentVault>>,
+
+    /// Share position of the authority.
+    /// PDA: ["share_position", agent_vault, authority]
+    #[account(
+        mut,
+        seeds = [b"share_position".as_ref(), agent_vault.key().as_ref(), authority.key().as_ref()],
+        bump = share_position.bump,
+    )]
+    pub share_position: Box<Account<'info, SharePosition>>,
+
+    /// Share mint of the agent.
+    #[account(address = agent_vault.share_mint)]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// CHECK: Transfer authority PDA (custodian of staked shares).
+    /// PDA: ["transfer_authority"]
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    // --- Token Accounts ---
+    /// Protocol's token account holding staked shares.
+    #[account(
+        mut,
+        associated_token::mint = share_mint,
+        associated_token::authority = transfer_authority,
+    )]
+    pub vault_share_ta: Box<Account<'info, TokenAccount>>,
+
+    /// User's token account receiving the shares.
+    #[account(
+        mut,
+        constraint = user_share_ta.mint == share_mint.key(),
+        constraint = user_share_ta.owner == authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub user_share_ta: Box<Account<'info, TokenAccount>>,
+
+    // --- Misc ---
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+/// Instruction: Unstake Agent Shares
+///
+/// Settles the holder's pending yield and returns `params.amount` staked shares.
+pub fn unstake_agent_shares<'info>(
+    ctx: Context<'_, '_, '_, 'info, UnstakeAgentShares<'info>>,
+    params: UnstakeAgentSharesParams,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let master_agent = ctx.accounts.master_agent.as_mut();
+    let user = ctx.accounts.user.as_mut();
+    let agent = ctx.accounts.agent.as_mut();
+    let agent_vault = ctx.accounts.agent_vault.as_mut();
+    let share_position = ctx.accounts.share_position.as_mut();
+    let t_yield = ctx.accounts.t_yield.as_mut();
+
+    if user.has_status(UserStatus::Banned) {
+        return Err(ErrorCode::CannotPerformAction);
+    }
+
+    // Pass the agent's yield through to the shares before the stake changes,
+    // unless a sunset bought the agent back
+    if !agent_vault.is_bought_back {
+        let accrued_yield = agent.settle_yield(master_agent, current_time)?;
+        agent_vault.accrue_yield(accrued_yield, current_time)?;
+    }
+
+    let yield_settled = agent_vault.unstake(share_position, params.amount, current_time)?;
+    if yield_settled > 0 {
+        user.add_unclaimed_yield(yield_settled)?;
+        t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_add(yield_settled)?;
+    }
+
+    if params.amount > 0 {
+        TYield::transfer_tokens(
+            ctx.accounts.vault_share_ta.to_account_info(),
+            ctx.accounts.share_mint.to_account_info(),
+            ctx.accounts.user_share_ta.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            params.amount,
+            ctx.accounts.share_mint.decimals,
+        )
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+    }
+
+    user.update_last_activity(current_time)?;
+
+    emit_cpi!(SharePositionEvent {
+        vault: agent_vault.key(),
+        owner: ctx.accounts.authority.key(),
+        shares: share_position.shares,
+        yield_settled,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
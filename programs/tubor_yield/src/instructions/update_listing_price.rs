@@ -0,0 +1,75 @@
+//! Instruction: Update Listing Price
+//!
+//! Changes the ask price of an open peer-to-peer listing. Only the seller can update it.
+//!
+//! Accounts:
+//! - authority: The seller (signer)
+//! - listing: The listing PDA
+//! - t_yield: Protocol global state/config PDA
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{AgentListing, TYield, UpdateListingEvent},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateListingPriceParams {
+    /// New ask price in y_mint base units
+    pub price: u64,
+}
+
+#[derive(Accounts)]
+pub struct UpdateListingPrice<'info> {
+    /// The seller of the listing. Must sign the transaction.
+    pub authority: Signer<'info>,
+
+    /// The listing PDA.
+    /// PDA: ["listing", listing.mint]
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing.mint.as_ref()],
+        bump = listing.bump,
+        constraint = listing.is_seller(&authority.key()) @ ErrorCode::CannotPerformAction,
+    )]
+    pub listing: Box<Account<'info, AgentListing>>,
+
+    /// Protocol global state PDA.
+    /// PDA: ["t_yield"]
+    #[account(
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn update_listing_price<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateListingPrice<'info>>,
+    params: UpdateListingPriceParams,
+) -> TYieldResult<u8> {
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let listing = ctx.accounts.listing.as_mut();
+
+    let old_price = listing.update_price(params.price, current_time)?;
+
+    emit_cpi!(UpdateListingEvent {
+        agent: listing.agent,
+        seller: listing.seller,
+        old_price,
+        new_price: params.price,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
@@ -4,7 +4,7 @@ use crate::{
     error::{ErrorCode, TYieldResult},
     math::SafeMath,
     msg,
-    state::{AdminInstruction, Multisig, TYield, UpdateProtocolEvent},
+    state::{AdminInstruction, Multisig, TYield, UpdateProtocolEvent, MAX_REFERRAL_DEPTH},
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -12,10 +12,16 @@ pub struct UpdateProtocolConfigParams {
     pub buy_tax: Option<u64>,
     pub sell_tax: Option<u64>,
     pub max_tax_percentage: Option<u64>,
+    pub performance_fee: Option<u64>,
+    pub ref_earn_percentage: Option<u64>,
+    pub ref_tier_percentages: Option<[u64; MAX_REFERRAL_DEPTH - 1]>,
+    pub treasury: Option<Pubkey>,
     pub allow_agent_deploy: Option<bool>,
     pub allow_agent_buy: Option<bool>,
     pub allow_agent_sell: Option<bool>,
     pub allow_withdraw_yield: Option<bool>,
+    pub keeper_reward: Option<u64>,
+    pub keeper_epoch_cap: Option<u64>,
     // Add more config fields as needed
 }
 
@@ -110,6 +116,34 @@ pub fn update_protocol_config<'info>(
         t_yield.max_tax_percentage = max_tax_percentage;
     }
 
+    if let Some(performance_fee) = params.performance_fee {
+        if performance_fee > 10000 {
+            msg!("Performance fee cannot exceed 100%");
+            return Err(ErrorCode::MathError);
+        }
+        t_yield.performance_fee = performance_fee;
+    }
+
+    if params.ref_earn_percentage.is_some() || params.ref_tier_percentages.is_some() {
+        let ref_earn_percentage = params
+            .ref_earn_percentage
+            .unwrap_or(t_yield.ref_earn_percentage);
+        let ref_tier_percentages = params
+            .ref_tier_percentages
+            .unwrap_or(t_yield.ref_tier_percentages);
+        TYield::validate_referral_percentages(ref_earn_percentage, &ref_tier_percentages)?;
+        t_yield.ref_earn_percentage = ref_earn_percentage;
+        t_yield.ref_tier_percentages = ref_tier_percentages;
+    }
+
+    if let Some(treasury) = params.treasury {
+        if treasury == Pubkey::default() {
+            msg!("Treasury cannot be the default pubkey");
+            return Err(ErrorCode::InvalidAccount);
+        }
+        t_yield.treasury = treasury;
+    }
+
     if let Some(allow_agent_deploy) = params.allow_agent_deploy {
         t_yield.permissions.allow_agent_deploy = allow_agent_deploy;
     }
@@ -123,6 +157,14 @@ pub fn update_protocol_config<'info>(
         t_yield.permissions.allow_withdraw_yield = allow_withdraw_yield;
     }
 
+    if params.keeper_reward.is_some() || params.keeper_epoch_cap.is_some() {
+        let keeper_reward = params.keeper_reward.unwrap_or(t_yield.keeper_reward);
+        let keeper_epoch_cap = params.keeper_epoch_cap.unwrap_or(t_yield.keeper_epoch_cap);
+        TYield::validate_keeper_parameters(keeper_reward, keeper_epoch_cap)?;
+        t_yield.keeper_reward = keeper_reward;
+        t_yield.keeper_epoch_cap = keeper_epoch_cap;
+    }
+
     // Update rate limiter
     t_yield.rate_limiter.last_update_time = current_time;
     t_yield.rate_limiter.daily_update_count =
@@ -5,11 +5,12 @@
 //!
 //! Accounts:
 //! - authority: The caller (anyone can call)
-//! - t_yield: Protocol global state/config PDA
+//! - t_yield: Protocol global state/config PDA (mut)
 //! - pair_oracle_account: Oracle price account for the trading pair
 //! - pair_twap_account: (Optional) TWAP oracle account for the trading pair
 //! - trade: Trade account to update (mut)
 //! - master_agent: Master agent account for the trade (mut)
+//! - keeper_user: (Optional) User account PDA of the caller, receives the keeper reward for a settlement
 //! - event_authority: Event authority for CPI event logs (used for event emission)
 //! - system_program: Solana system program
 
@@ -21,7 +22,7 @@ use crate::{
     math::SafeMath,
     state::{
         trade::{Trade, TradeResult, TradeStatus},
-        MasterAgent, OraclePrice, TYield,
+        KeeperAction, KeeperRewardEvent, MasterAgent, OraclePrice, TYield, User,
     },
 };
 
@@ -37,11 +38,12 @@ pub struct UpdateTradeParams {
 ///
 /// # Account Ordering
 /// - `authority`: The caller (anyone can call)
-/// - `t_yield`: Protocol global state/config PDA
+/// - `t_yield`: Protocol global state/config PDA (mut)
 /// - `pair_oracle_account`: Oracle price account for the trading pair
 /// - `pair_twap_account`: (Optional) TWAP oracle account for the trading pair
 /// - `trade`: Trade account to update (mut)
 /// - `master_agent`: Master agent account for the trade (mut)
+/// - `keeper_user`: (Optional) User account PDA of the caller, receives the keeper reward for a settlement
 /// - `event_authority`: Event authority for CPI event logs (used for event emission)
 /// - `system_program`: Solana system program
 #[derive(Accounts)]
@@ -49,9 +51,10 @@ pub struct UpdateTrade<'info> {
     /// The caller (anyone can call this instruction).
     pub authority: Signer<'info>,
 
-    /// Protocol global state/config PDA.
+    /// Protocol global state/config PDA (mut, receives the performance fee).
     /// Seeds: ["t_yield"]
     #[account(
+        mut,
         seeds = [b"t_yield"],
         bump = t_yield.t_yield_bump
     )]
@@ -63,14 +66,32 @@ pub struct UpdateTrade<'info> {
     /// Optional TWAP (Time-Weighted Average Price) account for the trading pair.
     pub pair_twap_account: Option<Account<'info, TwapUpdate>>,
 
-    /// Trade account to update (mut).
-    #[account(mut)]
+    /// Trade account to update (mut). Must have been opened by `master_agent`.
+    /// PDA: ["trade", master_agent, trade number]
+    #[account(
+        mut,
+        constraint = trade.belongs_to_master_agent(&master_agent.key()) @ ErrorCode::InvalidAccount,
+    )]
     pub trade: Box<Account<'info, Trade>>,
 
     /// Master agent account for the trade (mut).
-    #[account(mut)]
+    /// PDA: ["master_agent", master_agent.mint]
+    #[account(
+        mut,
+        seeds = [b"master_agent".as_ref(), master_agent.mint.as_ref()],
+        bump = master_agent.bump,
+    )]
     pub master_agent: Box<Account<'info, MasterAgent>>,
 
+    /// User account PDA of the caller. Receives the keeper reward when provided.
+    /// PDA: ["user", authority]
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), authority.key().as_ref()],
+        bump = keeper_user.bump,
+    )]
+    pub keeper_user: Option<Box<Account<'info, User>>>,
+
     /// Event authority for CPI event logs (used for event emission; not written to).
     /// Seeds: ["__event_authority"]
     /// CHECK: Derived by Anchor for event emission.
@@ -117,6 +138,7 @@ pub fn update_trade<'info>(
     .map_err(|_| ErrorCode::InvalidOraclePrice)?;
 
     let current_price = token_price.scale_to_exponent(0)?.price;
+    let t_yield = &mut ctx.accounts.t_yield;
 
     msg!("Current price: {}", current_price);
     msg!("Trade entry price: {}", trade.entry_price);
@@ -134,9 +156,18 @@ pub fn update_trade<'info>(
         // Complete the trade with success result
         trade.complete(TradeResult::Success);
 
-        // Update master agent trade count and PnL
+        // Update master agent trade count
         master_agent.completed_trades = master_agent.completed_trades.safe_add(1)?;
-        master_agent.total_pnl = master_agent.total_pnl.safe_add(pnl.unsigned_abs())?;
+
+        // Route realized PnL to holders, protocol and NAV and record it in the performance ledger
+        let settlement =
+            master_agent.settle_realized_pnl(pnl, t_yield.performance_fee, current_time)?;
+        t_yield.protocol_total_fees = t_yield
+            .protocol_total_fees
+            .safe_add(settlement.protocol_fee)?;
+        t_yield.protocol_total_earnings = t_yield
+            .protocol_total_earnings
+            .safe_add(settlement.protocol_fee)?;
 
         // Emit trade event
         emit_cpi!(crate::state::trade::TradeEvent {
@@ -145,9 +176,26 @@ pub fn update_trade<'info>(
             trade_type: trade.get_trade_type(),
             result: TradeResult::Success,
             pnl,
+            protocol_fee: settlement.protocol_fee,
+            holder_yield: settlement.holder_yield,
+            nav_delta: settlement.nav_delta,
             created_at: current_time,
         });
 
+        // Reward the keeper for the settlement
+        if let Some(keeper_user) = ctx.accounts.keeper_user.as_mut() {
+            let reward = t_yield.reward_keeper(keeper_user, 1, current_time)?;
+            if reward > 0 {
+                emit_cpi!(KeeperRewardEvent {
+                    keeper: keeper_user.authority,
+                    action: KeeperAction::TradeSettlement,
+                    actions: 1,
+                    amount: reward,
+                    timestamp: current_time,
+                });
+            }
+        }
+
         msg!("Trade completed successfully with profit");
         return Ok(1); // Return 1 to indicate TP hit
     }
@@ -163,9 +211,18 @@ pub fn update_trade<'info>(
         // Complete the trade with failed result
         trade.complete(TradeResult::Failed);
 
-        // Update master agent trade count and PnL
+        // Update master agent trade count
         master_agent.completed_trades = master_agent.completed_trades.safe_add(1)?;
-        master_agent.total_pnl = master_agent.total_pnl.safe_add(pnl.unsigned_abs())?;
+
+        // Route realized PnL to holders, protocol and NAV and record it in the performance ledger
+        let settlement =
+            master_agent.settle_realized_pnl(pnl, t_yield.performance_fee, current_time)?;
+        t_yield.protocol_total_fees = t_yield
+            .protocol_total_fees
+            .safe_add(settlement.protocol_fee)?;
+        t_yield.protocol_total_earnings = t_yield
+            .protocol_total_earnings
+            .safe_add(settlement.protocol_fee)?;
 
         // Emit trade event
         emit_cpi!(crate::state::trade::TradeEvent {
@@ -174,9 +231,26 @@ pub fn update_trade<'info>(
             trade_type: trade.get_trade_type(),
             result: TradeResult::Failed,
             pnl,
+            protocol_fee: settlement.protocol_fee,
+            holder_yield: settlement.holder_yield,
+            nav_delta: settlement.nav_delta,
             created_at: current_time,
         });
 
+        // Reward the keeper for the settlement
+        if let Some(keeper_user) = ctx.accounts.keeper_user.as_mut() {
+            let reward = t_yield.reward_keeper(keeper_user, 1, current_time)?;
+            if reward > 0 {
+                emit_cpi!(KeeperRewardEvent {
+                    keeper: keeper_user.authority,
+                    action: KeeperAction::TradeSettlement,
+                    actions: 1,
+                    amount: reward,
+                    timestamp: current_time,
+                });
+            }
+        }
+
         msg!("Trade completed with stop loss");
         return Ok(2); // Return 2 to indicate SL hit
     }
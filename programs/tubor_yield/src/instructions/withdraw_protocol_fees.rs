@@ -0,0 +1,155 @@
+//! Instruction: Withdraw Protocol Fees
+//!
+//! Moves accrued protocol fees from the transfer authority token account to the treasury (with multisig approval).
+//! Withdrawals never dip into `protocol_current_holding` or yield owed to users.
+//! Emits a `WithdrawFeesEvent` on success.
+//!
+//! Accounts:
+//! - admin: The signer proposing/signing the withdrawal (must be a multisig signer)
+//! - multisig: Protocol multisig PDA (controls admin actions)
+//! - t_yield: Protocol global state/config PDA (mut, tracks withdrawn fees)
+//! - transfer_authority: PDA that owns the protocol token account
+//! - y_mint: Protocol yield token mint
+//! - protocol_token_account: Protocol token account holding the fees (mut)
+//! - treasury_token_account: Token account owned by `t_yield.treasury` (mut)
+//! - token_program: SPL Token program
+//! - event_authority: Event authority for CPI event logs (used for event emission)
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    error::{ErrorCode, TYieldResult},
+    state::{AdminInstruction, Multisig, TYield, WithdrawFeesEvent},
+    try_from,
+};
+
+/// Parameters for withdrawing protocol fees.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct WithdrawProtocolFeesParams {
+    pub amount: Option<u64>, // None = withdraw all withdrawable fees
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolFees<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"t_yield"],
+        bump = t_yield.t_yield_bump
+    )]
+    pub t_yield: Box<Account<'info, TYield>>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = t_yield.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    ///CHECK: y_mint
+    #[account(address = t_yield.y_mint)]
+    pub y_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = protocol_token_account.mint == t_yield.y_mint,
+        constraint = protocol_token_account.owner == transfer_authority.key() @ ErrorCode::InvalidAccount,
+    )]
+    pub protocol_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.mint == t_yield.y_mint,
+        constraint = treasury_token_account.owner == t_yield.treasury @ ErrorCode::InvalidAccount,
+    )]
+    pub treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// Event authority for CPI event logs (used for event emission; not written to).
+    /// Seeds: ["__event_authority"]
+    /// CHECK: Derived by Anchor for event emission.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+    )]
+    pub event_authority: AccountInfo<'info>,
+}
+
+pub fn withdraw_protocol_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawProtocolFees<'info>>,
+    params: WithdrawProtocolFeesParams,
+) -> TYieldResult<u8> {
+    let mut multisig = ctx
+        .accounts
+        .multisig
+        .load_mut()
+        .map_err(|_| ErrorCode::InvalidBump)?;
+
+    let instruction_data = Multisig::get_instruction_data(AdminInstruction::WithdrawFees, &params)
+        .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    let current_time = ctx.accounts.t_yield.get_time()?;
+    let nonce = current_time as u64;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &instruction_data,
+        nonce,
+        current_time,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    let t_yield = ctx.accounts.t_yield.as_mut();
+    let vault_balance = ctx.accounts.protocol_token_account.amount;
+
+    // 1. Determine withdrawable amount
+    let amount = match params.amount {
+        Some(a) => a,
+        None => t_yield.get_withdrawable_fees(vault_balance)?,
+    };
+    t_yield.record_fee_withdrawal(amount, vault_balance)?;
+
+    // 2. Transfer tokens from protocol to treasury
+    let mint =
+        try_from!(Account<Mint>, ctx.accounts.y_mint).map_err(|_| ErrorCode::AccountFromError)?;
+
+    TYield::transfer_tokens(
+        ctx.accounts.protocol_token_account.to_account_info(),
+        mint.to_account_info(),
+        ctx.accounts.treasury_token_account.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+        mint.decimals,
+    )
+    .map_err(|_| ErrorCode::InvalidInstructionHash)?;
+
+    msg!("Withdrew {} in protocol fees to treasury", amount);
+
+    emit_cpi!(WithdrawFeesEvent {
+        treasury: t_yield.treasury,
+        amount,
+        total_withdrawn: t_yield.protocol_fees_withdrawn,
+        timestamp: current_time,
+    });
+
+    Ok(0)
+}
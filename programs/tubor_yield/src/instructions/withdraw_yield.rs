@@ -99,6 +99,7 @@ pub fn withdraw_yield<'info>(
     // 3. Update protocol/user balances
     user.claim_yield(amount)?;
     t_yield.protocol_total_balance_usd = t_yield.protocol_total_balance_usd.safe_sub(amount)?;
+    t_yield.protocol_yield_owed = t_yield.protocol_yield_owed.safe_sub(amount)?;
 
     // 4. Return Ok(0) or error
     Ok(0)
@@ -13,7 +13,7 @@ use instructions::*;
 
 use crate::{
     error::TYieldResult,
-    state::{AgentPrice, OraclePrice},
+    state::{trade::TradeCrankSummary, AgentPrice, MasterAgentPerformance, OraclePrice},
 };
 
 declare_id!("EiifDJcZo3QthKQ2ZrdNSMsDufw4A4sGdsEQkZyRnhNs");
@@ -36,6 +36,13 @@ pub mod tuboryield {
         instructions::update_trade(ctx, params)
     }
 
+    pub fn crank_trades<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankTrades<'info>>,
+        params: CrankTradesParams,
+    ) -> TYieldResult<TradeCrankSummary> {
+        instructions::crank_trades(ctx, params)
+    }
+
     // NEW: Secure oracle update instruction
     pub fn secure_oracle_update<'info>(
         ctx: Context<'_, '_, '_, 'info, SecureOracleUpdate<'info>>,
@@ -82,14 +89,52 @@ pub mod tuboryield {
         instructions::register_user(ctx, params)
     }
 
-    pub fn buy_agent<'info>(ctx: Context<'_, '_, '_, 'info, BuyAgent<'info>>) -> TYieldResult<()> {
-        instructions::buy_agent(ctx)
+    pub fn create_referral_code<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateReferralCode<'info>>,
+        params: CreateReferralCodeParams,
+    ) -> TYieldResult<()> {
+        instructions::create_referral_code(ctx, params)
+    }
+
+    pub fn revoke_referral_code<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevokeReferralCode<'info>>,
+    ) -> TYieldResult<u8> {
+        instructions::revoke_referral_code(ctx)
+    }
+
+    pub fn buy_agent<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyAgent<'info>>,
+        params: BuyAgentParams,
+    ) -> TYieldResult<()> {
+        instructions::buy_agent(ctx, params)
     }
 
     pub fn sell_agent<'info>(
-        ctx: Context<'_, '_, '_, 'info, SellAgent<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, SellAgent<'info>>,
+        params: SellAgentParams,
+    ) -> TYieldResult<()> {
+        instructions::sell_agent(ctx, params)
+    }
+
+    pub fn redeem_agent<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RedeemAgent<'info>>,
+        params: RedeemAgentParams,
+    ) -> TYieldResult<()> {
+        instructions::redeem_agent(ctx, params)
+    }
+
+    pub fn buy_agents<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyAgents<'info>>,
+        params: BuyAgentsParams,
+    ) -> TYieldResult<()> {
+        instructions::buy_agents(ctx, params)
+    }
+
+    pub fn sell_agents<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SellAgents<'info>>,
+        params: SellAgentsParams,
     ) -> TYieldResult<()> {
-        instructions::sell_agent(ctx)
+        instructions::sell_agents(ctx, params)
     }
 
     pub fn open_trade<'info>(
@@ -106,12 +151,192 @@ pub mod tuboryield {
         instructions::close_trade(ctx, params)
     }
 
-    // pub fn transfer_agent<'info>(
-    //     ctx: Context<'_, '_, '_, 'info, TransferAgent<'info>>,
-    //     params: TransferAgentParams,
-    // ) -> TYieldResult<u8> {
-    //     instructions::transfer_agent(ctx, params)
-    // }
+    pub fn modify_trade<'info>(
+        ctx: Context<'_, '_, '_, 'info, ModifyTrade<'info>>,
+        params: ModifyTradeParams,
+    ) -> TYieldResult<u8> {
+        instructions::modify_trade(ctx, params)
+    }
+
+    pub fn migrate_trade<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateTrade<'info>>,
+        params: MigrateTradeParams,
+    ) -> TYieldResult<u8> {
+        instructions::migrate_trade(ctx, params)
+    }
+
+    pub fn migrate_t_yield<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateTYield<'info>>,
+        params: MigrateTYieldParams,
+    ) -> TYieldResult<u8> {
+        instructions::migrate_t_yield(ctx, params)
+    }
+
+    pub fn migrate_master_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateMasterAgent<'info>>,
+        params: MigrateMasterAgentParams,
+    ) -> TYieldResult<u8> {
+        instructions::migrate_master_agent(ctx, params)
+    }
+
+    pub fn migrate_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateAgent<'info>>,
+    ) -> TYieldResult<u8> {
+        instructions::migrate_agent(ctx)
+    }
+
+    pub fn transfer_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferAgent<'info>>,
+        params: TransferAgentParams,
+    ) -> TYieldResult<u8> {
+        instructions::transfer_agent(ctx, params)
+    }
+
+    pub fn list_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, ListAgent<'info>>,
+        params: ListAgentParams,
+    ) -> TYieldResult<u8> {
+        instructions::list_agent(ctx, params)
+    }
+
+    pub fn update_listing_price<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateListingPrice<'info>>,
+        params: UpdateListingPriceParams,
+    ) -> TYieldResult<u8> {
+        instructions::update_listing_price(ctx, params)
+    }
+
+    pub fn cancel_listing<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelListing<'info>>,
+    ) -> TYieldResult<u8> {
+        instructions::cancel_listing(ctx)
+    }
+
+    pub fn buy_listing<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyListing<'info>>,
+        params: BuyListingParams,
+    ) -> TYieldResult<u8> {
+        instructions::buy_listing(ctx, params)
+    }
+
+    pub fn start_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, StartAuction<'info>>,
+        params: StartAuctionParams,
+    ) -> TYieldResult<u8> {
+        instructions::start_auction(ctx, params)
+    }
+
+    pub fn bid_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BidAuction<'info>>,
+        params: BidAuctionParams,
+    ) -> TYieldResult<()> {
+        instructions::bid_auction(ctx, params)
+    }
+
+    pub fn settle_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleAuction<'info>>,
+    ) -> TYieldResult<u8> {
+        instructions::settle_auction(ctx)
+    }
+
+    pub fn refund_auction_bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefundAuctionBid<'info>>,
+    ) -> TYieldResult<u8> {
+        instructions::refund_auction_bid(ctx)
+    }
+
+    pub fn configure_presale<'info>(
+        ctx: Context<'_, '_, '_, 'info, ConfigurePresale<'info>>,
+        params: ConfigurePresaleParams,
+    ) -> TYieldResult<u8> {
+        instructions::configure_presale(ctx, params)
+    }
+
+    pub fn set_whitelist_root<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetWhitelistRoot<'info>>,
+        params: SetWhitelistRootParams,
+    ) -> TYieldResult<u8> {
+        instructions::set_whitelist_root(ctx, params)
+    }
+
+    pub fn set_relist_policy<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetRelistPolicy<'info>>,
+        params: SetRelistPolicyParams,
+    ) -> TYieldResult<u8> {
+        instructions::set_relist_policy(ctx, params)
+    }
+
+    pub fn start_sunset<'info>(
+        ctx: Context<'_, '_, '_, 'info, StartSunset<'info>>,
+        params: StartSunsetParams,
+    ) -> TYieldResult<u8> {
+        instructions::start_sunset(ctx, params)
+    }
+
+    pub fn claim_sunset_buyback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimSunsetBuyback<'info>>,
+    ) -> TYieldResult<()> {
+        instructions::claim_sunset_buyback(ctx)
+    }
+
+    pub fn sweep_sunset<'info>(
+        ctx: Context<'_, '_, '_, 'info, SweepSunset<'info>>,
+    ) -> TYieldResult<u8> {
+        instructions::sweep_sunset(ctx)
+    }
+
+    pub fn fractionalize_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, FractionalizeAgent<'info>>,
+        params: FractionalizeAgentParams,
+    ) -> TYieldResult<u8> {
+        instructions::fractionalize_agent(ctx, params)
+    }
+
+    pub fn reassemble_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReassembleAgent<'info>>,
+    ) -> TYieldResult<u8> {
+        instructions::reassemble_agent(ctx)
+    }
+
+    pub fn stake_agent_shares<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeAgentShares<'info>>,
+        params: StakeAgentSharesParams,
+    ) -> TYieldResult<u8> {
+        instructions::stake_agent_shares(ctx, params)
+    }
+
+    pub fn unstake_agent_shares<'info>(
+        ctx: Context<'_, '_, '_, 'info, UnstakeAgentShares<'info>>,
+        params: UnstakeAgentSharesParams,
+    ) -> TYieldResult<u8> {
+        instructions::unstake_agent_shares(ctx, params)
+    }
+
+    pub fn create_agent_rental<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateAgentRental<'info>>,
+        params: CreateAgentRentalParams,
+    ) -> TYieldResult<u8> {
+        instructions::create_agent_rental(ctx, params)
+    }
+
+    pub fn accept_agent_rental<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptAgentRental<'info>>,
+    ) -> TYieldResult<u8> {
+        instructions::accept_agent_rental(ctx)
+    }
+
+    pub fn cancel_agent_rental<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelAgentRental<'info>>,
+    ) -> TYieldResult<u8> {
+        instructions::cancel_agent_rental(ctx)
+    }
+
+    pub fn buy_presale_agent<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyPresaleAgent<'info>>,
+        params: BuyPresaleAgentParams,
+    ) -> TYieldResult<()> {
+        instructions::buy_presale_agent(ctx, params)
+    }
 
     pub fn claim_referral_rewards<'info>(
         ctx: Context<'_, '_, '_, 'info, ClaimReferralRewards<'info>>,
@@ -119,6 +344,12 @@ pub mod tuboryield {
         instructions::claim_referral_rewards(ctx)
     }
 
+    pub fn accrue_yield<'info>(
+        ctx: Context<'_, '_, '_, 'info, AccrueYield<'info>>,
+    ) -> TYieldResult<u64> {
+        instructions::accrue_yield(ctx)
+    }
+
     pub fn withdraw_yield<'info>(
         ctx: Context<'_, '_, '_, 'info, WithdrawYield<'info>>,
         params: WithdrawYieldParams,
@@ -126,6 +357,20 @@ pub mod tuboryield {
         instructions::withdraw_yield(ctx, params)
     }
 
+    pub fn withdraw_protocol_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawProtocolFees<'info>>,
+        params: WithdrawProtocolFeesParams,
+    ) -> TYieldResult<u8> {
+        instructions::withdraw_protocol_fees(ctx, params)
+    }
+
+    pub fn fund_keeper_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, FundKeeperRewards<'info>>,
+        params: FundKeeperRewardsParams,
+    ) -> TYieldResult<u8> {
+        instructions::fund_keeper_rewards(ctx, params)
+    }
+
     pub fn update_price<'info>(
         ctx: Context<'_, '_, '_, 'info, UpdatePrice<'info>>,
         params: UpdatePriceParams,
@@ -154,14 +399,22 @@ pub mod tuboryield {
 
     pub fn get_buy_agent_price<'info>(
         ctx: Context<'_, '_, '_, 'info, GetBuyAgentPrice<'info>>,
+        params: GetBuyAgentPriceParams,
     ) -> TYieldResult<AgentPrice> {
-        instructions::get_buy_agent_price(ctx)
+        instructions::get_buy_agent_price(ctx, params)
     }
 
     pub fn get_sell_agent_price<'info>(
         ctx: Context<'_, '_, '_, 'info, GetSellAgentPrice<'info>>,
+        params: GetSellAgentPriceParams,
     ) -> TYieldResult<AgentPrice> {
-        instructions::get_sell_agent_price(ctx)
+        instructions::get_sell_agent_price(ctx, params)
+    }
+
+    pub fn get_master_agent_performance<'info>(
+        ctx: Context<'_, '_, '_, 'info, GetMasterAgentPerformance<'info>>,
+    ) -> TYieldResult<MasterAgentPerformance> {
+        instructions::get_master_agent_performance(ctx)
     }
 
     pub fn get_pair_price<'info>(
@@ -9,6 +9,13 @@ pub const ORACLE_MAX_PRICE: u64 = (1 << 28) - 1; // 2^28 - 1
 pub const USD_DECIMALS: u8 = 6;
 
 pub const MAX_SIGNERS: usize = 6;
+pub const MAX_AGENT_BATCH_SIZE: usize = 5;
+pub const MAX_TRADE_CRANK_SIZE: usize = 10;
+pub const KEEPER_EPOCH_DURATION: i64 = 86_400; // 1 day
+pub const KEEPER_ACCRUAL_INTERVAL: i64 = 86_400; // 1 day
+pub const KEEPER_ACCRUAL_REWARD_SHARE: u64 = 100; // 1% of the accrued yield, PERCENTAGE PRECISION
+pub const MAX_KEEPER_REWARD: u64 = 1_000_000; // 1 y_mint token, QUOTE PRECISION
+pub const MAX_KEEPER_EPOCH_CAP: u64 = 1_000_000_000; // 1,000 y_mint tokens, QUOTE PRECISION
 
 pub const QUOTE_PRECISION: u128 = 1_000_000; // expo = -6
 pub const QUOTE_PRECISION_I128: i128 = 1_000_000; // expo = -6
@@ -24,3 +31,7 @@ pub const DAILY_SECONDS_PRECISION: u64 = 86_400;
 pub const DAILY_SECONDS_PRECISION_U128: u64 = 86_400;
 pub const DAILY_SECONDS_PRECISION_I128: i128 = 86_400;
 pub const DAILY_SECONDS_PRECISION_I64: i64 = 86_400;
+
+pub const YEARLY_SECONDS_PRECISION: u64 = 31_536_000;
+pub const YIELD_INDEX_PRECISION: u128 = 1_000_000_000_000; // expo = -12
+pub const CURVE_PRECISION: u128 = 1_000_000_000_000; // expo = -12
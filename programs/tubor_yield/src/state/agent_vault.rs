@@ -0,0 +1,386 @@
+use anchor_lang::prelude::*;
+
+use crate::error::{ErrorCode, TYieldResult};
+use crate::math::{SafeMath, U192, YIELD_INDEX_PRECISION};
+use crate::state::Size;
+
+/// Vault locking a fractionalized agent.
+///
+/// The agent NFT is held by the protocol and owned by the vault, and `total_shares`
+/// SPL shares of `share_mint` are minted to the creator. Yield earned by the agent is
+/// split pro-rata over all shares through `yield_per_share`; holders earn their share
+/// by staking shares in a `SharePosition`. The share of unstaked shares is retained by
+/// the vault and paid to the holder of every share on reassembly.
+///
+/// # Fields
+///
+/// - `agent`: The fractionalized agent
+/// - `share_mint`: SPL mint of the shares
+/// - `creator`: The user who fractionalized the agent
+/// - `total_shares`: Fixed share supply
+/// - `staked_shares`: Shares staked in positions
+/// - `total_yield`: Yield earned by the agent while fractionalized
+/// - `paid_yield`: Yield credited to share holders so far
+/// - `retained_yield`: Yield earned by unstaked shares, not yet paid out
+/// - `created_at`: Timestamp the agent was fractionalized
+/// - `last_updated`: Timestamp of the last update to the vault
+/// - `yield_per_share`: Yield accumulated per share
+/// - `bump`: PDA bump seed for the vault account
+/// - `is_bought_back`: Whether the agent was bought back by a master agent sunset
+/// - `_padding`: Reserved space for future additions
+#[account]
+#[derive(Eq, PartialEq, Debug, Default)]
+pub struct AgentVault {
+    // 8-byte aligned fields (largest first)
+    pub agent: Pubkey,       // 32 bytes
+    pub share_mint: Pubkey,  // 32 bytes
+    pub creator: Pubkey,     // 32 bytes
+    pub total_shares: u64,   // 8 bytes
+    pub staked_shares: u64,  // 8 bytes
+    pub total_yield: u64,    // 8 bytes
+    pub paid_yield: u64,     // 8 bytes
+    pub retained_yield: u64, // 8 bytes
+    pub created_at: i64,     // 8 bytes
+    pub last_updated: i64,   // 8 bytes
+
+    /// PRECISION: YIELD_INDEX_PRECISION
+    pub yield_per_share: u128, // 16 bytes
+
+    // 1-byte aligned fields (smallest last)
+    pub bump: u8,             // 1 byte
+    pub is_bought_back: bool, // 1 byte
+
+    // Future-proofing padding
+    pub _padding: [u8; 6], // 6 bytes for future additions
+}
+
+/// Shares of a fractionalized agent staked by a single holder.
+///
+/// # Fields
+///
+/// - `vault`: The vault the shares belong to
+/// - `owner`: The share holder
+/// - `shares`: Shares staked by the holder
+/// - `yield_index_checkpoint`: Vault `yield_per_share` at the last settlement
+/// - `bump`: PDA bump seed for the position account
+/// - `_padding`: Reserved space for future additions
+#[account]
+#[derive(Eq, PartialEq, Debug, Default)]
+pub struct SharePosition {
+    pub vault: Pubkey, // 32 bytes
+    pub owner: Pubkey, // 32 bytes
+    pub shares: u64,   // 8 bytes
+
+    /// PRECISION: YIELD_INDEX_PRECISION
+    pub yield_index_checkpoint: u128, // 16 bytes
+
+    pub bump: u8,          // 1 byte
+    pub _padding: [u8; 7], // 7 bytes for future additions
+}
+
+/// Event emitted when an agent is fractionalized.
+#[event]
+pub struct FractionalizeAgentEvent {
+    pub agent: Pubkey,
+    pub vault: Pubkey,
+    pub share_mint: Pubkey,
+    pub owner: Pubkey,
+    pub total_shares: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when all shares are burned to unlock a fractionalized agent.
+#[event]
+pub struct ReassembleAgentEvent {
+    pub agent: Pubkey,
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub total_shares: u64,
+    /// Yield earned by the agent while fractionalized
+    pub total_yield: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a holder stakes or unstakes shares.
+#[event]
+pub struct SharePositionEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    /// Shares staked after the update
+    pub shares: u64,
+    /// Yield credited to the holder
+    pub yield_settled: u64,
+    pub timestamp: i64,
+}
+
+impl AgentVault {
+    /// Locks an agent behind `total_shares` shares.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::MathError` - If the share supply is zero
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tubor_yield::state::agent_vault::AgentVault;
+    /// use anchor_lang::solana_program::pubkey::Pubkey;
+    ///
+    /// let mut vault = AgentVault::default();
+    /// vault
+    ///     .initialize(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 1_000, 1000, 255)
+    ///     .unwrap();
+    /// vault.accrue_yield(500, 1100).unwrap();
+    /// assert_eq!(vault.total_yield, 500);
+    /// ```
+    pub fn initialize(
+        &mut self,
+        agent: Pubkey,
+        share_mint: Pubkey,
+        creator: Pubkey,
+        total_shares: u64,
+        current_time: i64,
+        bump: u8,
+    ) -> TYieldResult<()> {
+        if total_shares == 0 {
+            return Err(ErrorCode::MathError);
+        }
+
+        self.agent = agent;
+        self.share_mint = share_mint;
+        self.creator = creator;
+        self.total_shares = total_shares;
+        self.staked_shares = 0;
+        self.total_yield = 0;
+        self.paid_yield = 0;
+        self.retained_yield = 0;
+        self.created_at = current_time;
+        self.last_updated = current_time;
+        self.yield_per_share = 0;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Splits yield earned by the agent over all shares.
+    ///
+    /// Only staked shares can claim their part, so the part of the unstaked shares is
+    /// added to `retained_yield` instead.
+    pub fn accrue_yield(&mut self, amount: u64, current_time: i64) -> TYieldResult<()> {
+        if amount > 0 {
+            let per_share = (amount as u128)
+                .safe_mul(YIELD_INDEX_PRECISION)?
+                .safe_div(self.total_shares as u128)?;
+            let staked_yield = U192::from(per_share)
+                .safe_mul(U192::from(self.staked_shares))?
+                .safe_div(U192::from(YIELD_INDEX_PRECISION))?
+                .try_to_u64()?;
+            self.yield_per_share = self.yield_per_share.safe_add(per_share)?;
+            self.retained_yield = self
+                .retained_yield
+                .safe_add(amount.safe_sub(staked_yield)?)?;
+            self.total_yield = self.total_yield.safe_add(amount)?;
+        }
+        self.last_updated = current_time;
+        Ok(())
+    }
+
+    /// Pays the sunset buyback price of the agent to the shares.
+    ///
+    /// The price is split over the shares like yield. The agent earns nothing for the
+    /// vault afterwards.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If the agent was already bought back
+    pub fn record_buyback(&mut self, price: u64, current_time: i64) -> TYieldResult<()> {
+        if self.is_bought_back {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        self.accrue_yield(price, current_time)?;
+        self.is_bought_back = true;
+        Ok(())
+    }
+
+    /// Pays out the yield retained for unstaked shares.
+    ///
+    /// # Returns
+    /// * `Ok(amount)` - The retained yield, now counted as paid
+    pub fn take_retained_yield(&mut self) -> TYieldResult<u64> {
+        let amount = self.retained_yield;
+        self.retained_yield = 0;
+        self.paid_yield = self.paid_yield.safe_add(amount)?;
+        Ok(amount)
+    }
+
+    /// Settles the yield owed to a position and moves its checkpoint forward.
+    ///
+    /// # Returns
+    /// * `Ok(amount)` - The yield owed to the position owner
+    pub fn settle_position(&mut self, position: &mut SharePosition) -> TYieldResult<u64> {
+        let index_delta = self
+            .yield_per_share
+            .safe_sub(position.yield_index_checkpoint)?;
+        let amount = U192::from(index_delta)
+            .safe_mul(U192::from(position.shares))?
+            .safe_div(U192::from(YIELD_INDEX_PRECISION))?
+            .try_to_u64()?;
+
+        position.yield_index_checkpoint = self.yield_per_share;
+        self.paid_yield = self.paid_yield.safe_add(amount)?;
+        Ok(amount)
+    }
+
+    /// Stakes `amount` shares in a position after settling its yield.
+    ///
+    /// # Returns
+    /// * `Ok(amount)` - The yield owed to the position owner
+    pub fn stake(
+        &mut self,
+        position: &mut SharePosition,
+        amount: u64,
+        current_time: i64,
+    ) -> TYieldResult<u64> {
+        let settled = self.settle_position(position)?;
+        position.shares = position.shares.safe_add(amount)?;
+        self.staked_shares = self.staked_shares.safe_add(amount)?;
+        if self.staked_shares > self.total_shares {
+            return Err(ErrorCode::InvalidState);
+        }
+        self.last_updated = current_time;
+        Ok(settled)
+    }
+
+    /// Unstakes `amount` shares from a position after settling its yield.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::InsufficientFunds` - If the position holds fewer than `amount` shares
+    pub fn unstake(
+        &mut self,
+        position: &mut SharePosition,
+        amount: u64,
+        current_time: i64,
+    ) -> TYieldResult<u64> {
+        if amount > position.shares {
+            return Err(ErrorCode::InsufficientFunds);
+        }
+        let settled = self.settle_position(position)?;
+        position.shares = position.shares.safe_sub(amount)?;
+        self.staked_shares = self.staked_shares.safe_sub(amount)?;
+        self.last_updated = current_time;
+        Ok(settled)
+    }
+}
+
+impl Size for AgentVault {
+    const SIZE: usize = 184; // 8 (discriminator) + 176 (struct, including padding) = 184 bytes
+}
+
+impl Size for SharePosition {
+    const SIZE: usize = 104; // 8 (discriminator) + 96 (struct, including padding) = 104 bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_vault() -> AgentVault {
+        let mut vault = AgentVault::default();
+        vault
+            .initialize(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                1_000,
+                1_000,
+                255,
+            )
+            .unwrap();
+        vault
+    }
+
+    #[test]
+    fn test_agent_vault_sizes() {
+        assert_eq!(8 + std::mem::size_of::<AgentVault>(), AgentVault::SIZE);
+        assert_eq!(
+            8 + std::mem::size_of::<SharePosition>(),
+            SharePosition::SIZE
+        );
+    }
+
+    #[test]
+    fn test_agent_vault_initialize() {
+        let mut vault = AgentVault::default();
+        assert_eq!(
+            vault.initialize(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                0,
+                1_000,
+                255
+            ),
+            Err(ErrorCode::MathError)
+        );
+    }
+
+    #[test]
+    fn test_share_yield_pro_rata() {
+        let mut vault = create_test_vault();
+        let mut alice = SharePosition::default();
+        let mut bob = SharePosition::default();
+
+        assert_eq!(vault.stake(&mut alice, 600, 1_000).unwrap(), 0);
+        assert_eq!(vault.stake(&mut bob, 200, 1_000).unwrap(), 0);
+        assert_eq!(vault.staked_shares, 800);
+
+        // Unstaked shares' part is retained by the vault
+        vault.accrue_yield(10_000, 2_000).unwrap();
+        assert_eq!(vault.retained_yield, 2_000);
+        assert_eq!(vault.settle_position(&mut alice).unwrap(), 6_000);
+        assert_eq!(vault.settle_position(&mut alice).unwrap(), 0);
+
+        // Bob unstakes and is paid up to now; later yield is not his
+        assert!(vault.unstake(&mut bob, 201, 2_000).is_err());
+        assert_eq!(vault.unstake(&mut bob, 200, 2_000).unwrap(), 2_000);
+        vault.accrue_yield(5_000, 3_000).unwrap();
+        assert_eq!(vault.settle_position(&mut bob).unwrap(), 0);
+        assert_eq!(vault.settle_position(&mut alice).unwrap(), 3_000);
+        assert_eq!(vault.retained_yield, 4_000);
+        assert_eq!(vault.total_yield, 15_000);
+        assert_eq!(vault.paid_yield, 11_000);
+
+        // A single staked share only earns its own part
+        assert_eq!(vault.unstake(&mut alice, 599, 3_000).unwrap(), 0);
+        vault.accrue_yield(2_000, 4_000).unwrap();
+        assert_eq!(vault.settle_position(&mut alice).unwrap(), 2);
+        assert_eq!(vault.retained_yield, 5_998);
+
+        // Retained yield is paid out once
+        assert_eq!(vault.take_retained_yield().unwrap(), 5_998);
+        assert_eq!(vault.retained_yield, 0);
+        assert_eq!(vault.paid_yield, vault.total_yield);
+
+        // Cannot stake more than the share supply
+        assert!(vault.stake(&mut bob, 1_001, 4_000).is_err());
+    }
+
+    #[test]
+    fn test_share_buyback() {
+        let mut vault = create_test_vault();
+        let mut alice = SharePosition::default();
+        vault.stake(&mut alice, 500, 1_000).unwrap();
+
+        // The buyback price is split over the shares like yield
+        vault.record_buyback(900_000, 2_000).unwrap();
+        assert!(vault.is_bought_back);
+        assert_eq!(vault.settle_position(&mut alice).unwrap(), 450_000);
+        assert_eq!(vault.take_retained_yield().unwrap(), 450_000);
+
+        // An agent is bought back once
+        assert_eq!(
+            vault.record_buyback(900_000, 3_000),
+            Err(ErrorCode::CannotPerformAction)
+        );
+    }
+}
@@ -1,8 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
 
 use crate::error::{ErrorCode, TYieldResult};
-use crate::math::SafeMath;
-use crate::state::Size;
+use crate::math::{Cast, SafeMath, PERCENTAGE_PRECISION_U64, U192, YIELD_INDEX_PRECISION};
+use crate::state::{MasterAgent, Size};
 
 /// Represents an agent in the Tubor Yield protocol.
 ///
@@ -18,7 +21,12 @@ use crate::state::Size;
 /// - `booster`: Boost multiplier as a percentage (e.g., 15000 = 150%)
 /// - `created_at`: Timestamp when the agent was created
 /// - `last_updated`: Timestamp of the last update to the agent
+/// - `last_yield_accrual`: Timestamp up to which yield has been credited to the owner
+/// - `yield_index_checkpoint`: Master agent yield index at the last settlement
+/// - `distribution_index_checkpoint`: Master agent distribution index at the last settlement
+/// - `relist_at`: Timestamp a returned agent is relisted at (0 = none)
 /// - `is_listed`: Whether the agent is currently listed for trading
+/// - `is_rented`: Whether a rental agreement is open for the agent's yield
 /// - `bump`: PDA bump seed for the agent account
 /// - `_padding`: Reserved space for future additions
 #[account]
@@ -37,9 +45,21 @@ pub struct Agent {
     // 1-byte aligned fields (smallest last)
     pub is_listed: bool, // 1 byte
     pub bump: u8,        // 1 byte
+    pub is_rented: bool, // 1 byte
 
     // Future-proofing padding
-    pub _padding: [u8; 6], // 6 bytes for future additions
+    pub _padding: [u8; 5], // 5 bytes for future additions
+
+    // Fields below were appended after the initial layout and are
+    // populated for existing accounts by `migrate_agent`
+    /// PRECISION: YIELD_INDEX_PRECISION
+    pub yield_index_checkpoint: u128, // 16 bytes
+
+    /// PRECISION: YIELD_INDEX_PRECISION
+    pub distribution_index_checkpoint: u128, // 16 bytes
+
+    pub last_yield_accrual: i64, // 8 bytes
+    pub relist_at: i64,          // 8 bytes
 }
 
 /// Event emitted when an agent is bought.
@@ -57,6 +77,67 @@ pub struct SellAgentEvent {
     pub agent: Pubkey,
     pub owner: Pubkey,
     pub master_agent: Pubkey,
+    /// `RelistPolicy` applied to the agent
+    pub relist_policy: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when several agents are bought in one instruction.
+#[event]
+pub struct BuyAgentsEvent {
+    pub agents: Vec<Pubkey>,
+    pub owner: Pubkey,
+    pub master_agent: Pubkey,
+    pub total_price: u64,
+    pub tax_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when several agents are sold in one instruction.
+#[event]
+pub struct SellAgentsEvent {
+    pub agents: Vec<Pubkey>,
+    pub owner: Pubkey,
+    pub master_agent: Pubkey,
+    pub net_price: u64,
+    pub tax_amount: u64,
+    /// `RelistPolicy` applied to the agents
+    pub relist_policy: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an agent is redeemed for its NAV share.
+#[event]
+pub struct RedeemAgentEvent {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub master_agent: Pubkey,
+    /// NAV share paid to the owner
+    pub payout: u64,
+    /// `RelistPolicy` applied to the agent
+    pub relist_policy: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an agent is transferred between users.
+#[event]
+pub struct TransferAgentEvent {
+    pub agent: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub master_agent: Pubkey,
+    /// Yield settled to the sender before the transfer
+    pub yield_settled: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when yield is credited to an agent holder.
+#[event]
+pub struct AccrueYieldEvent {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub master_agent: Pubkey,
+    pub amount: u64,
     pub timestamp: i64,
 }
 
@@ -118,10 +199,31 @@ impl Agent {
         self.is_listed = true;
         self.created_at = current_time;
         self.last_updated = current_time;
+        self.last_yield_accrual = current_time;
         self.bump = bump;
         Ok(())
     }
 
+    /// Populates the fields appended to the layout for an agent created before them.
+    ///
+    /// Yield starts accruing to the owner from the migration on; the agent is neither rented
+    /// nor scheduled for relisting.
+    ///
+    /// # Arguments
+    ///
+    /// * `master_agent` - The master agent this agent belongs to
+    /// * `current_time` - Current timestamp
+    pub fn migrate(
+        &mut self,
+        master_agent: &mut MasterAgent,
+        current_time: i64,
+    ) -> TYieldResult<()> {
+        self.relist_at = 0;
+        self.reset_yield_accrual(master_agent, current_time)?;
+        self.last_updated = current_time;
+        Ok(())
+    }
+
     /// Updates the booster value of the agent.
     ///
     /// # Arguments
@@ -231,6 +333,123 @@ impl Agent {
         Ok(())
     }
 
+    /// Returns the agent to the protocol after a sale, redemption or sunset buyback.
+    ///
+    /// The agent is listed again right away when `relist_at` has been reached, or held
+    /// unlisted until then. Without a `relist_at` it is held unlisted.
+    ///
+    /// # Arguments
+    ///
+    /// * `protocol` - The protocol authority taking ownership of the agent
+    /// * `relist_at` - Timestamp the agent can be bought again at (`None` = not relisted)
+    /// * `current_time` - Current timestamp
+    ///
+    /// # Errors
+    ///
+    /// Returns `ErrorCode::InvalidAccount` if the agent is listed or `protocol` is invalid.
+    pub fn return_to_protocol(
+        &mut self,
+        protocol: Pubkey,
+        relist_at: Option<i64>,
+        current_time: i64,
+    ) -> TYieldResult<()> {
+        self.transfer_ownership(protocol, current_time)?;
+        match relist_at {
+            Some(relist_at) if relist_at <= current_time => {
+                self.relist_at = 0;
+                self.list(current_time)
+            }
+            Some(relist_at) => {
+                self.relist_at = relist_at;
+                Ok(())
+            }
+            None => {
+                self.relist_at = 0;
+                Ok(())
+            }
+        }
+    }
+
+    /// Lists a returned agent whose relist cooldown has elapsed.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the agent was listed.
+    pub fn release_cooldown(&mut self, current_time: i64) -> bool {
+        if self.is_listed || self.relist_at == 0 || current_time < self.relist_at {
+            return false;
+        }
+        self.relist_at = 0;
+        self.is_listed = true;
+        self.last_updated = current_time;
+        true
+    }
+
+    /// Settles the yield accrued since the last checkpoint.
+    ///
+    /// Checkpoints the master agent's yield index, computes the yield owed to the
+    /// current owner from the index growth since `yield_index_checkpoint` scaled by
+    /// the booster, adds the unboosted distributions since `distribution_index_checkpoint`,
+    /// and moves the agent checkpoints forward. This is constant time
+    /// regardless of how many agents the master agent has. The caller is responsible
+    /// for crediting the returned amount to the owner.
+    ///
+    /// # Arguments
+    ///
+    /// * `master_agent` - The master agent this agent belongs to
+    /// * `current_time` - Current timestamp
+    ///
+    /// # Returns
+    ///
+    /// Returns the amount of yield owed to the owner.
+    pub fn settle_yield(
+        &mut self,
+        master_agent: &mut MasterAgent,
+        current_time: i64,
+    ) -> TYieldResult<u64> {
+        let index = master_agent.update_yield_index(current_time)?;
+        let index_delta = index.safe_sub(self.yield_index_checkpoint)?;
+
+        let accrued = U192::from(index_delta)
+            .safe_mul(U192::from(self.booster))?
+            .safe_div(
+                U192::from(PERCENTAGE_PRECISION_U64).safe_mul(U192::from(YIELD_INDEX_PRECISION))?,
+            )?
+            .try_to_u64()?;
+
+        // Distributed yield (realized PnL) is paid per agent without the booster
+        let distributed = master_agent
+            .distribution_index
+            .safe_sub(self.distribution_index_checkpoint)?
+            .safe_div(YIELD_INDEX_PRECISION)?;
+        let amount = accrued.safe_add(distributed.cast()?)?;
+
+        self.yield_index_checkpoint = index;
+        self.distribution_index_checkpoint = master_agent.distribution_index;
+        self.last_yield_accrual = self.last_yield_accrual.max(current_time);
+        Ok(amount)
+    }
+
+    /// Restarts yield accrual from `current_time` without paying anything out.
+    ///
+    /// Used when an agent enters or leaves protocol custody, since no user is
+    /// owed yield for the time the protocol held it.
+    ///
+    /// # Arguments
+    ///
+    /// * `master_agent` - The master agent this agent belongs to
+    /// * `current_time` - Current timestamp
+    pub fn reset_yield_accrual(
+        &mut self,
+        master_agent: &mut MasterAgent,
+        current_time: i64,
+    ) -> TYieldResult<()> {
+        self.yield_index_checkpoint = master_agent.update_yield_index(current_time)?;
+        self.distribution_index_checkpoint = master_agent.distribution_index;
+        self.last_yield_accrual = current_time;
+        Ok(())
+    }
+
     /// Checks if the agent is currently listed for trading.
     ///
     /// # Returns
@@ -240,6 +459,14 @@ impl Agent {
         self.is_listed
     }
 
+    /// Checks if the agent can change hands.
+    ///
+    /// Agents with an open rental agreement cannot be transferred or sold until
+    /// the rental is cancelled or closed.
+    pub fn is_transferable(&self) -> bool {
+        !self.is_rented
+    }
+
     /// Checks if the agent belongs to a specific owner.
     ///
     /// # Arguments
@@ -455,6 +682,7 @@ impl Agent {
     /// This method is intended for testing purposes and should not be used in production.
     pub fn reset(&mut self) {
         self.is_listed = false;
+        self.relist_at = 0;
         self.last_updated = self.created_at;
     }
 
@@ -535,8 +763,155 @@ impl Agent {
     }
 }
 
+/// Accounts of one agent in a batch buy or sell, read from `remaining_accounts`.
+///
+/// Each agent of a buy takes `AgentBatchAccounts::LEN` consecutive accounts:
+/// `[agent, mint, metadata, transfer_authority_ta, user_agent_ta]`. Each agent of a sell
+/// takes `AgentBatchAccounts::SELL_LEN` accounts and carries its master edition as well,
+/// so it can be burned: `[agent, mint, metadata, master_edition, transfer_authority_ta, user_agent_ta]`.
+pub struct AgentBatchAccounts<'info> {
+    pub agent: Account<'info, Agent>,
+    pub mint: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    /// Master edition of the agent NFT, only loaded for sells
+    pub master_edition: Option<AccountInfo<'info>>,
+    pub transfer_authority_ta: Account<'info, TokenAccount>,
+    pub user_agent_ta: Account<'info, TokenAccount>,
+}
+
+impl<'info> AgentBatchAccounts<'info> {
+    /// Number of accounts per agent of a buy.
+    pub const LEN: usize = 5;
+    /// Number of accounts per agent of a sell.
+    pub const SELL_LEN: usize = 6;
+
+    /// Loads and validates `count` agent groups of a buy from the front of `accounts`.
+    ///
+    /// # Arguments
+    /// * `accounts` - The instruction's remaining accounts
+    /// * `count` - Number of agents in the batch
+    /// * `master_agent` - Master agent every agent must belong to
+    /// * `transfer_authority` - Owner of the protocol token accounts
+    /// * `user` - Owner of the user token accounts
+    ///
+    /// # Errors
+    /// * `ErrorCode::InvalidAccount` - If an account is missing, repeated or does not match its agent
+    /// * `ErrorCode::AccountFromError` - If an account cannot be deserialized
+    pub fn load_batch(
+        accounts: &'info [AccountInfo<'info>],
+        count: usize,
+        master_agent: &Pubkey,
+        transfer_authority: &Pubkey,
+        user: &Pubkey,
+    ) -> TYieldResult<Vec<Self>> {
+        Self::load(
+            accounts,
+            count,
+            false,
+            master_agent,
+            transfer_authority,
+            user,
+        )
+    }
+
+    /// Loads and validates `count` agent groups of a sell, including their master editions,
+    /// from the front of `accounts`.
+    ///
+    /// # Errors
+    /// * `ErrorCode::InvalidAccount` - If an account is missing, repeated or does not match its agent
+    /// * `ErrorCode::AccountFromError` - If an account cannot be deserialized
+    pub fn load_sell_batch(
+        accounts: &'info [AccountInfo<'info>],
+        count: usize,
+        master_agent: &Pubkey,
+        transfer_authority: &Pubkey,
+        user: &Pubkey,
+    ) -> TYieldResult<Vec<Self>> {
+        Self::load(
+            accounts,
+            count,
+            true,
+            master_agent,
+            transfer_authority,
+            user,
+        )
+    }
+
+    fn load(
+        accounts: &'info [AccountInfo<'info>],
+        count: usize,
+        with_master_edition: bool,
+        master_agent: &Pubkey,
+        transfer_authority: &Pubkey,
+        user: &Pubkey,
+    ) -> TYieldResult<Vec<Self>> {
+        let len = if with_master_edition {
+            Self::SELL_LEN
+        } else {
+            Self::LEN
+        };
+        if count == 0 || accounts.len() < count.saturating_mul(len) {
+            return Err(ErrorCode::InvalidAccount);
+        }
+
+        let mut batch: Vec<Self> = Vec::with_capacity(count);
+        for group in accounts.chunks_exact(len).take(count) {
+            let agent =
+                Account::<Agent>::try_from(&group[0]).map_err(|_| ErrorCode::AccountFromError)?;
+            let mint = group[1].clone();
+            let metadata = group[2].clone();
+            let master_edition = with_master_edition.then(|| group[3].clone());
+            let transfer_authority_ta = Account::<TokenAccount>::try_from(&group[len - 2])
+                .map_err(|_| ErrorCode::AccountFromError)?;
+            let user_agent_ta = Account::<TokenAccount>::try_from(&group[len - 1])
+                .map_err(|_| ErrorCode::AccountFromError)?;
+
+            let (metadata_pda, _) = Pubkey::find_program_address(
+                &[b"metadata", METADATA_PROGRAM_ID.as_ref(), mint.key.as_ref()],
+                &METADATA_PROGRAM_ID,
+            );
+            if let Some(master_edition) = master_edition.as_ref() {
+                let (master_edition_pda, _) = Pubkey::find_program_address(
+                    &[
+                        b"metadata",
+                        METADATA_PROGRAM_ID.as_ref(),
+                        mint.key.as_ref(),
+                        b"edition",
+                    ],
+                    &METADATA_PROGRAM_ID,
+                );
+                if !master_edition.is_writable || master_edition.key() != master_edition_pda {
+                    return Err(ErrorCode::InvalidAccount);
+                }
+            }
+            if !group[0].is_writable
+                || agent.mint != mint.key()
+                || !agent.belongs_to_master_agent(master_agent)
+                || metadata.key() != metadata_pda
+                || transfer_authority_ta.mint != mint.key()
+                || transfer_authority_ta.owner != *transfer_authority
+                || user_agent_ta.mint != mint.key()
+                || user_agent_ta.owner != *user
+                || batch.iter().any(|other| other.mint.key() == mint.key())
+            {
+                return Err(ErrorCode::InvalidAccount);
+            }
+
+            batch.push(Self {
+                agent,
+                mint,
+                metadata,
+                master_edition,
+                transfer_authority_ta,
+                user_agent_ta,
+            });
+        }
+        Ok(batch)
+    }
+}
+
 impl Size for Agent {
-    const SIZE: usize = 136; // 8 (discriminator) + 128 (struct, including padding) = 136 bytes
+    const SIZE: usize = 184; // 8 (discriminator) + 176 (struct, including padding) = 184 bytes
 }
 
 #[cfg(test)]
@@ -554,6 +929,38 @@ mod tests {
         println!("Agent on-chain size: {} bytes", Agent::SIZE);
     }
 
+    #[test]
+    fn test_agent_legacy_layout() {
+        let agent = Agent {
+            master_agent: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            booster: 15000,
+            created_at: 1_640_995_200,
+            last_updated: 1_640_995_200,
+            is_listed: true,
+            bump: 254,
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        agent.try_serialize(&mut data).unwrap();
+
+        // Accounts created before the appended fields only hold the first 136 bytes
+        let mut legacy = data[..136].to_vec();
+        legacy.resize(Agent::SIZE, 0);
+        let mut migrated = Agent::try_deserialize(&mut &legacy[..]).unwrap();
+        assert_eq!(migrated, agent);
+
+        let mut master_agent = MasterAgent {
+            last_yield_index_update: 1_700_000_000,
+            ..Default::default()
+        };
+        migrated.migrate(&mut master_agent, 1_700_000_000).unwrap();
+        assert_eq!(migrated.last_yield_accrual, 1_700_000_000);
+        assert_eq!(migrated.yield_index_checkpoint, master_agent.yield_index);
+        assert!(!migrated.is_rented);
+    }
+
     #[test]
     fn test_agent_memory_layout() {
         // Test that Agent struct can be created and serialized
@@ -562,11 +969,13 @@ mod tests {
         assert_eq!(agent.mint, Pubkey::default());
         assert_eq!(agent.owner, Pubkey::default());
         assert_eq!(agent.booster, 0);
+        assert_eq!(agent.yield_index_checkpoint, 0);
         assert_eq!(agent.created_at, 0);
         assert_eq!(agent.last_updated, 0);
+        assert_eq!(agent.last_yield_accrual, 0);
         assert_eq!(agent.is_listed, false);
         assert_eq!(agent.bump, 0);
-        assert_eq!(agent._padding, [0; 6]);
+        assert_eq!(agent._padding, [0; 5]);
     }
 
     #[test]
@@ -588,6 +997,7 @@ mod tests {
         assert_eq!(agent.booster, booster);
         assert_eq!(agent.created_at, current_time);
         assert_eq!(agent.last_updated, current_time);
+        assert_eq!(agent.last_yield_accrual, current_time);
         assert_eq!(agent.is_listed, true);
         assert_eq!(agent.bump, bump);
     }
@@ -626,6 +1036,43 @@ mod tests {
         assert!(agent.is_listed_for_trading());
     }
 
+    #[test]
+    fn test_agent_return_to_protocol() {
+        let mut agent = Agent::default();
+        let protocol = Pubkey::new_unique();
+        let current_time = 1640995200;
+
+        // Relisted immediately
+        agent
+            .return_to_protocol(protocol, Some(current_time), current_time)
+            .unwrap();
+        assert_eq!(agent.owner, protocol);
+        assert!(agent.is_listed_for_trading());
+        assert!(agent
+            .return_to_protocol(protocol, Some(current_time), current_time)
+            .is_err());
+
+        // Held unlisted for a cooldown
+        agent.unlist(current_time).unwrap();
+        agent
+            .return_to_protocol(protocol, Some(current_time + 3600), current_time)
+            .unwrap();
+        assert!(!agent.is_listed_for_trading());
+        assert!(!agent.release_cooldown(current_time + 3599));
+        assert!(agent.release_cooldown(current_time + 3600));
+        assert!(agent.is_listed_for_trading());
+        assert_eq!(agent.relist_at, 0);
+        assert!(!agent.release_cooldown(current_time + 7200));
+
+        // Held unlisted without a relist time
+        agent.unlist(current_time).unwrap();
+        agent
+            .return_to_protocol(protocol, None, current_time)
+            .unwrap();
+        assert!(!agent.is_listed_for_trading());
+        assert!(!agent.release_cooldown(current_time + 7200));
+    }
+
     #[test]
     fn test_agent_booster_operations() {
         let mut agent = Agent::default();
@@ -772,4 +1219,142 @@ mod tests {
         let score_old = agent.get_performance_score(old_time);
         assert!(score_old < base_score);
     }
+
+    #[test]
+    fn test_agent_settle_yield() {
+        let mut agent = Agent::default();
+        let current_time = 1640995200;
+        agent
+            .initialize(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                15000, // 150% boost
+                current_time,
+                255,
+            )
+            .unwrap();
+
+        let mut master_agent = MasterAgent {
+            price: 1_000_000,
+            w_yield: 1000, // 10% annual
+            last_yield_index_update: current_time,
+            ..Default::default()
+        };
+        agent
+            .reset_yield_accrual(&mut master_agent, current_time)
+            .unwrap();
+
+        // Nothing accrues without time passing
+        assert_eq!(
+            agent.settle_yield(&mut master_agent, current_time).unwrap(),
+            0
+        );
+
+        // One year at 10% with a 1.5x boost
+        let one_year_later = current_time + 31_536_000;
+        let owed = agent
+            .settle_yield(&mut master_agent, one_year_later)
+            .unwrap();
+        assert_eq!(owed, 150_000);
+        assert_eq!(agent.last_yield_accrual, one_year_later);
+        assert_eq!(agent.yield_index_checkpoint, master_agent.yield_index);
+
+        // Settling again at the same time pays nothing
+        assert_eq!(
+            agent
+                .settle_yield(&mut master_agent, one_year_later)
+                .unwrap(),
+            0
+        );
+
+        // Reset moves the checkpoint without paying out
+        let reset_time = one_year_later + 86400;
+        agent
+            .reset_yield_accrual(&mut master_agent, reset_time)
+            .unwrap();
+        assert_eq!(
+            agent.settle_yield(&mut master_agent, reset_time).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_agent_settle_yield_independent_checkpoints() {
+        let current_time = 1640995200;
+        let mut master_agent = MasterAgent {
+            price: 1_000_000,
+            w_yield: 1000, // 10% annual
+            last_yield_index_update: current_time,
+            ..Default::default()
+        };
+
+        let mut early = Agent {
+            booster: 10000,
+            ..Default::default()
+        };
+        let mut late = Agent {
+            booster: 10000,
+            ..Default::default()
+        };
+        early
+            .reset_yield_accrual(&mut master_agent, current_time)
+            .unwrap();
+
+        // Second agent joins half a year later
+        let half_year = current_time + 15_768_000;
+        late.reset_yield_accrual(&mut master_agent, half_year)
+            .unwrap();
+
+        let one_year_later = current_time + 31_536_000;
+        let early_owed = early
+            .settle_yield(&mut master_agent, one_year_later)
+            .unwrap();
+        let late_owed = late
+            .settle_yield(&mut master_agent, one_year_later)
+            .unwrap();
+        assert_eq!(early_owed, 100_000);
+        assert_eq!(late_owed, 50_000);
+    }
+
+    #[test]
+    fn test_agent_settle_distributed_yield_mixed_boosters() {
+        let current_time = 1640995200;
+        // No time-based yield, so only the distribution is paid out
+        let mut master_agent = MasterAgent {
+            last_yield_index_update: current_time,
+            ..Default::default()
+        };
+
+        let mut agents = [10000, 15000, 25000].map(|booster| Agent {
+            booster,
+            ..Default::default()
+        });
+        for agent in agents.iter_mut() {
+            agent
+                .reset_yield_accrual(&mut master_agent, current_time)
+                .unwrap();
+            master_agent.record_agent_purchase(1_000_000).unwrap();
+        }
+
+        let distributed = 10_000;
+        master_agent
+            .distribute_yield(distributed, current_time)
+            .unwrap();
+
+        let settled: u64 = agents
+            .iter_mut()
+            .map(|agent| agent.settle_yield(&mut master_agent, current_time).unwrap())
+            .sum();
+        assert!(settled <= distributed);
+        assert_eq!(settled, 9_999);
+
+        // A distribution is only paid once
+        for agent in agents.iter_mut() {
+            assert_eq!(
+                agent.settle_yield(&mut master_agent, current_time).unwrap(),
+                0
+            );
+        }
+    }
 }
@@ -0,0 +1,412 @@
+use anchor_lang::prelude::*;
+
+use crate::error::{ErrorCode, TYieldResult};
+use crate::math::{SafeMath, PERCENTAGE_PRECISION_U64};
+use crate::state::Size;
+
+/// Dutch auction used to launch the supply of a master agent.
+///
+/// The price starts at `start_price` and decays (linearly, or in steps of
+/// `step_duration` seconds) to `floor_price` at `end_time`. Every bid pays the
+/// current price; once the auction is settled each bidder is refunded down to the
+/// clearing price, which is the price paid by the last bidder.
+///
+/// # Fields
+///
+/// - `master_agent`: The master agent whose supply is auctioned
+/// - `start_price`: Price at `start_time`
+/// - `floor_price`: Price at `end_time` (the master agent price when the auction started)
+/// - `buy_tax_percentage`: Buy tax snapshot applied to all bids
+/// - `start_time` / `end_time`: Auction window
+/// - `step_duration`: Length of each price step in seconds (0 = linear decay)
+/// - `last_price`: Price paid by the most recent bid
+/// - `clearing_price`: Final price every bidder pays (set on settlement)
+/// - `total_sold`: Agents sold through the auction
+/// - `total_raised`: Total paid by bidders, including tax and before refunds
+/// - `open_bids`: Bid receipts that have not been refunded yet
+/// - `is_settled`: Whether the clearing price is final
+/// - `bump`: PDA bump seed for the auction account
+/// - `_padding`: Reserved space for future additions
+#[account]
+#[derive(Eq, PartialEq, Debug, Default)]
+pub struct AgentAuction {
+    // 8-byte aligned fields (largest first)
+    pub master_agent: Pubkey,    // 32 bytes
+    pub start_price: u64,        // 8 bytes
+    pub floor_price: u64,        // 8 bytes
+    pub buy_tax_percentage: u64, // 8 bytes
+    pub start_time: i64,         // 8 bytes
+    pub end_time: i64,           // 8 bytes
+    pub step_duration: i64,      // 8 bytes
+    pub last_price: u64,         // 8 bytes
+    pub clearing_price: u64,     // 8 bytes
+    pub total_sold: u64,         // 8 bytes
+    pub total_raised: u64,       // 8 bytes
+    pub open_bids: u64,          // 8 bytes
+
+    // 1-byte aligned fields (smallest last)
+    pub is_settled: bool, // 1 byte
+    pub bump: u8,         // 1 byte
+
+    // Future-proofing padding
+    pub _padding: [u8; 6], // 6 bytes for future additions
+}
+
+/// A bidder's receipt for an auction. Closed when the bidder is refunded.
+///
+/// # Fields
+///
+/// - `auction`: The auction the bids were placed in
+/// - `buyer`: The bidder
+/// - `quantity`: Number of agents bought
+/// - `total_paid`: Total paid, including tax
+/// - `bump`: PDA bump seed for the bid account
+/// - `_padding`: Reserved space for future additions
+#[account]
+#[derive(Eq, PartialEq, Debug, Default)]
+pub struct AuctionBid {
+    pub auction: Pubkey,   // 32 bytes
+    pub buyer: Pubkey,     // 32 bytes
+    pub quantity: u64,     // 8 bytes
+    pub total_paid: u64,   // 8 bytes
+    pub bump: u8,          // 1 byte
+    pub _padding: [u8; 7], // 7 bytes for future additions
+}
+
+/// Event emitted when an auction is started.
+#[event]
+pub struct AuctionStartedEvent {
+    pub master_agent: Pubkey,
+    pub start_price: u64,
+    pub floor_price: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub step_duration: i64,
+}
+
+/// Event emitted for every agent bought in an auction.
+#[event]
+pub struct AuctionBidEvent {
+    pub master_agent: Pubkey,
+    pub agent: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64,
+    pub tax_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the clearing price of an auction is finalized.
+#[event]
+pub struct AuctionSettledEvent {
+    pub master_agent: Pubkey,
+    pub clearing_price: u64,
+    pub total_sold: u64,
+    pub total_raised: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a bidder is refunded down to the clearing price.
+#[event]
+pub struct AuctionRefundEvent {
+    pub master_agent: Pubkey,
+    pub buyer: Pubkey,
+    pub quantity: u64,
+    pub refund: u64,
+    pub timestamp: i64,
+}
+
+impl AgentAuction {
+    /// Starts (or restarts) the auction.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If a previous auction still has unsettled bids
+    /// * `ErrorCode::MathError` - If the start price is not above the floor price or the window is invalid
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tubor_yield::state::auction::AgentAuction;
+    /// use anchor_lang::solana_program::pubkey::Pubkey;
+    ///
+    /// let mut auction = AgentAuction::default();
+    /// // 5.0 -> 1.0 over one hour, linear decay
+    /// auction
+    ///     .start(Pubkey::new_unique(), 5_000_000, 1_000_000, 250, 1000, 3600, 0, 255)
+    ///     .unwrap();
+    /// assert_eq!(auction.get_current_price(1000 + 1800).unwrap(), 3_000_000);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn start(
+        &mut self,
+        master_agent: Pubkey,
+        start_price: u64,
+        floor_price: u64,
+        buy_tax_percentage: u64,
+        start_time: i64,
+        duration: i64,
+        step_duration: i64,
+        bump: u8,
+    ) -> TYieldResult<()> {
+        if self.open_bids > 0 || (self.master_agent != Pubkey::default() && !self.is_settled) {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        if start_price <= floor_price || floor_price == 0 {
+            return Err(ErrorCode::MathError);
+        }
+        if duration <= 0 || step_duration < 0 || step_duration > duration {
+            return Err(ErrorCode::MathError);
+        }
+        if buy_tax_percentage > PERCENTAGE_PRECISION_U64 {
+            return Err(ErrorCode::MathError);
+        }
+
+        self.master_agent = master_agent;
+        self.start_price = start_price;
+        self.floor_price = floor_price;
+        self.buy_tax_percentage = buy_tax_percentage;
+        self.start_time = start_time;
+        self.end_time = start_time.safe_add(duration)?;
+        self.step_duration = step_duration;
+        self.last_price = start_price;
+        self.clearing_price = 0;
+        self.total_sold = 0;
+        self.total_raised = 0;
+        self.open_bids = 0;
+        self.is_settled = false;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Gets the auction price at `current_time`.
+    pub fn get_current_price(&self, current_time: i64) -> TYieldResult<u64> {
+        if current_time <= self.start_time {
+            return Ok(self.start_price);
+        }
+        if current_time >= self.end_time {
+            return Ok(self.floor_price);
+        }
+
+        let mut elapsed = current_time.safe_sub(self.start_time)?;
+        if self.step_duration > 0 {
+            elapsed = elapsed
+                .safe_div(self.step_duration)?
+                .safe_mul(self.step_duration)?;
+        }
+        let duration = self.end_time.safe_sub(self.start_time)?;
+        let decay = self
+            .start_price
+            .safe_sub(self.floor_price)?
+            .safe_mul(elapsed as u64)?
+            .safe_div(duration as u64)?;
+
+        self.start_price.safe_sub(decay)
+    }
+
+    /// Checks if bids are accepted at `current_time`.
+    pub fn is_active(&self, current_time: i64) -> bool {
+        !self.is_settled && current_time >= self.start_time && current_time < self.end_time
+    }
+
+    /// Calculates the price of one agent including the buy tax snapshot.
+    ///
+    /// # Returns
+    /// * `Ok((total_price, tax_amount, base_price))` - The price breakdown
+    pub fn calculate_price_with_tax(&self, base_price: u64) -> TYieldResult<(u64, u64, u64)> {
+        let tax_amount = base_price
+            .safe_mul(self.buy_tax_percentage)?
+            .safe_div(PERCENTAGE_PRECISION_U64)?;
+        Ok((base_price.safe_add(tax_amount)?, tax_amount, base_price))
+    }
+
+    /// Records a bid at `price` and returns its price breakdown.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If the auction is not active
+    pub fn record_bid(
+        &mut self,
+        bid: &mut AuctionBid,
+        current_time: i64,
+    ) -> TYieldResult<(u64, u64, u64)> {
+        if !self.is_active(current_time) {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+
+        let price = self.calculate_price_with_tax(self.get_current_price(current_time)?)?;
+        if bid.quantity == 0 {
+            self.open_bids = self.open_bids.safe_add(1)?;
+        }
+        bid.quantity = bid.quantity.safe_add(1)?;
+        bid.total_paid = bid.total_paid.safe_add(price.0)?;
+
+        self.last_price = price.2;
+        self.total_sold = self.total_sold.safe_add(1)?;
+        self.total_raised = self.total_raised.safe_add(price.0)?;
+        Ok(price)
+    }
+
+    /// Finalizes the clearing price once the window has closed or the supply is sold out.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If the auction is already settled or still running
+    pub fn settle(&mut self, sold_out: bool, current_time: i64) -> TYieldResult<u64> {
+        if self.is_settled || (!sold_out && current_time < self.end_time) {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        self.clearing_price = if self.total_sold > 0 {
+            self.last_price
+        } else {
+            self.floor_price
+        };
+        self.is_settled = true;
+        Ok(self.clearing_price)
+    }
+
+    /// Calculates what a bidder is owed back at the clearing price and releases the bid.
+    ///
+    /// # Returns
+    /// * `Ok((refund, final_cost, final_tax))` - Refund, and the amount and tax kept by the protocol
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If the auction is not settled
+    pub fn settle_bid(&mut self, bid: &AuctionBid) -> TYieldResult<(u64, u64, u64)> {
+        if !self.is_settled {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        let (total_price, tax_amount, _) = self.calculate_price_with_tax(self.clearing_price)?;
+        let final_cost = total_price.safe_mul(bid.quantity)?;
+        let final_tax = tax_amount.safe_mul(bid.quantity)?;
+        let refund = bid.total_paid.safe_sub(final_cost)?;
+
+        self.open_bids = self.open_bids.safe_sub(1)?;
+        Ok((refund, final_cost, final_tax))
+    }
+}
+
+impl Size for AgentAuction {
+    const SIZE: usize = 136; // 8 (discriminator) + 128 (struct, including padding) = 136 bytes
+}
+
+impl Size for AuctionBid {
+    const SIZE: usize = 96; // 8 (discriminator) + 88 (struct, including padding) = 96 bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_auction(step_duration: i64) -> AgentAuction {
+        let mut auction = AgentAuction::default();
+        auction
+            .start(
+                Pubkey::new_unique(),
+                5_000_000,
+                1_000_000,
+                250,
+                1_000,
+                4_000,
+                step_duration,
+                255,
+            )
+            .unwrap();
+        auction
+    }
+
+    #[test]
+    fn test_auction_sizes() {
+        assert_eq!(8 + std::mem::size_of::<AgentAuction>(), AgentAuction::SIZE);
+        assert_eq!(8 + std::mem::size_of::<AuctionBid>(), AuctionBid::SIZE);
+    }
+
+    #[test]
+    fn test_auction_start_validation() {
+        let mut auction = AgentAuction::default();
+        let master_agent = Pubkey::new_unique();
+        // Start must be above floor
+        assert!(auction
+            .start(master_agent, 1_000, 1_000, 250, 0, 100, 0, 255)
+            .is_err());
+        // Step longer than window
+        assert!(auction
+            .start(master_agent, 2_000, 1_000, 250, 0, 100, 101, 255)
+            .is_err());
+
+        let mut auction = create_test_auction(0);
+        // Cannot restart a running auction
+        assert_eq!(
+            auction.start(master_agent, 2_000, 1_000, 250, 0, 100, 0, 255),
+            Err(ErrorCode::CannotPerformAction)
+        );
+    }
+
+    #[test]
+    fn test_linear_decay() {
+        let auction = create_test_auction(0);
+        assert_eq!(auction.get_current_price(0).unwrap(), 5_000_000);
+        assert_eq!(auction.get_current_price(1_000).unwrap(), 5_000_000);
+        assert_eq!(auction.get_current_price(2_000).unwrap(), 4_000_000);
+        assert_eq!(auction.get_current_price(4_999).unwrap(), 1_001_000);
+        assert_eq!(auction.get_current_price(5_000).unwrap(), 1_000_000);
+        assert_eq!(auction.get_current_price(10_000).unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn test_stepwise_decay() {
+        let auction = create_test_auction(1_000);
+        assert_eq!(auction.get_current_price(1_999).unwrap(), 5_000_000);
+        assert_eq!(auction.get_current_price(2_000).unwrap(), 4_000_000);
+        assert_eq!(auction.get_current_price(3_500).unwrap(), 3_000_000);
+        assert_eq!(auction.get_current_price(4_999).unwrap(), 2_000_000);
+        assert_eq!(auction.get_current_price(5_000).unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn test_bids_and_refunds() {
+        let mut auction = create_test_auction(0);
+        let mut early = AuctionBid::default();
+        let mut late = AuctionBid::default();
+
+        // Not started yet
+        assert!(auction.record_bid(&mut early, 999).is_err());
+
+        assert_eq!(
+            auction.record_bid(&mut early, 1_000).unwrap(),
+            (5_125_000, 125_000, 5_000_000)
+        );
+        auction.record_bid(&mut early, 2_000).unwrap();
+        auction.record_bid(&mut late, 3_000).unwrap();
+        assert_eq!(auction.open_bids, 2);
+        assert_eq!(auction.total_sold, 3);
+        assert_eq!(early.quantity, 2);
+
+        // Cannot settle early unless sold out
+        assert!(auction.settle(false, 4_000).is_err());
+        assert!(auction.settle_bid(&early).is_err());
+        assert_eq!(auction.settle(true, 4_000).unwrap(), 3_000_000);
+        assert!(auction.settle(true, 4_000).is_err());
+        assert!(auction.record_bid(&mut late, 4_000).is_err());
+
+        // Early bidder paid 5.125 + 4.1, owes 2 * 3.075
+        let (refund, final_cost, final_tax) = auction.settle_bid(&early).unwrap();
+        assert_eq!(
+            (refund, final_cost, final_tax),
+            (3_075_000, 6_150_000, 150_000)
+        );
+        let (refund, _, _) = auction.settle_bid(&late).unwrap();
+        assert_eq!(refund, 0);
+        assert_eq!(auction.open_bids, 0);
+    }
+
+    #[test]
+    fn test_settle_without_bids() {
+        let mut auction = create_test_auction(0);
+        assert_eq!(auction.settle(false, 5_000).unwrap(), 1_000_000);
+        // A settled auction with no open bids can be restarted
+        assert!(auction
+            .start(Pubkey::new_unique(), 2_000, 1_000, 250, 6_000, 100, 0, 255)
+            .is_ok());
+    }
+}
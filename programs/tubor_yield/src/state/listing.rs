@@ -0,0 +1,240 @@
+use anchor_lang::prelude::*;
+
+use crate::error::{ErrorCode, TYieldResult};
+use crate::state::Size;
+
+/// A peer-to-peer sale offer for an agent.
+///
+/// While the listing exists the agent NFT is escrowed in the transfer authority's
+/// token account. The seller keeps ownership of the `Agent` (and its yield) until
+/// a buyer fills the listing.
+///
+/// # Fields
+///
+/// - `agent`: The agent account being sold
+/// - `mint`: The mint address of the agent NFT
+/// - `seller`: The owner who created the listing
+/// - `master_agent`: The master agent the agent belongs to
+/// - `price`: Ask price in y_mint base units
+/// - `created_at`: Timestamp when the listing was created
+/// - `updated_at`: Timestamp of the last price change
+/// - `bump`: PDA bump seed for the listing account
+/// - `_padding`: Reserved space for future additions
+#[account]
+#[derive(Eq, PartialEq, Debug, Default)]
+pub struct AgentListing {
+    // 8-byte aligned fields (largest first)
+    pub agent: Pubkey,        // 32 bytes
+    pub mint: Pubkey,         // 32 bytes
+    pub seller: Pubkey,       // 32 bytes
+    pub master_agent: Pubkey, // 32 bytes
+    pub price: u64,           // 8 bytes
+    pub created_at: i64,      // 8 bytes
+    pub updated_at: i64,      // 8 bytes
+
+    // 1-byte aligned fields (smallest last)
+    pub bump: u8, // 1 byte
+
+    // Future-proofing padding
+    pub _padding: [u8; 7], // 7 bytes for future additions
+}
+
+/// Event emitted when an agent is listed for sale.
+#[event]
+pub struct ListAgentEvent {
+    pub agent: Pubkey,
+    pub seller: Pubkey,
+    pub master_agent: Pubkey,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a listing price is changed.
+#[event]
+pub struct UpdateListingEvent {
+    pub agent: Pubkey,
+    pub seller: Pubkey,
+    pub old_price: u64,
+    pub new_price: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a listing is cancelled and the agent returned to the seller.
+#[event]
+pub struct CancelListingEvent {
+    pub agent: Pubkey,
+    pub seller: Pubkey,
+    pub master_agent: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a listing is filled.
+#[event]
+pub struct BuyListingEvent {
+    pub agent: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub master_agent: Pubkey,
+    /// Full price paid by the buyer
+    pub price: u64,
+    pub protocol_tax: u64,
+    /// Royalty paid to the master agent authority
+    pub royalty: u64,
+    pub seller_proceeds: u64,
+    pub timestamp: i64,
+}
+
+impl AgentListing {
+    /// Initializes a new listing.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent` - The agent account being sold
+    /// * `mint` - The mint address of the agent NFT
+    /// * `seller` - The current owner of the agent
+    /// * `master_agent` - The master agent the agent belongs to
+    /// * `price` - Ask price in y_mint base units
+    /// * `current_time` - Current timestamp
+    /// * `bump` - PDA bump seed for the listing account
+    ///
+    /// # Errors
+    ///
+    /// Returns `ErrorCode::InvalidAccount` if `seller` is the default public key
+    /// and `ErrorCode::MathError` if `price` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tubor_yield::state::listing::AgentListing;
+    /// use anchor_lang::solana_program::pubkey::Pubkey;
+    ///
+    /// let mut listing = AgentListing::default();
+    /// let result = listing.initialize(
+    ///     Pubkey::new_unique(),
+    ///     Pubkey::new_unique(),
+    ///     Pubkey::new_unique(),
+    ///     Pubkey::new_unique(),
+    ///     1_000_000,
+    ///     1640995200,
+    ///     255,
+    /// );
+    /// assert!(result.is_ok());
+    /// assert_eq!(listing.price, 1_000_000);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        &mut self,
+        agent: Pubkey,
+        mint: Pubkey,
+        seller: Pubkey,
+        master_agent: Pubkey,
+        price: u64,
+        current_time: i64,
+        bump: u8,
+    ) -> TYieldResult<()> {
+        if seller == Pubkey::default() {
+            return Err(ErrorCode::InvalidAccount);
+        }
+        if price == 0 {
+            return Err(ErrorCode::MathError);
+        }
+        self.agent = agent;
+        self.mint = mint;
+        self.seller = seller;
+        self.master_agent = master_agent;
+        self.price = price;
+        self.created_at = current_time;
+        self.updated_at = current_time;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Changes the ask price of the listing.
+    ///
+    /// # Returns
+    ///
+    /// Returns the previous price.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ErrorCode::MathError` if `new_price` is zero.
+    pub fn update_price(&mut self, new_price: u64, current_time: i64) -> TYieldResult<u64> {
+        if new_price == 0 {
+            return Err(ErrorCode::MathError);
+        }
+        let old_price = self.price;
+        self.price = new_price;
+        self.updated_at = current_time;
+        Ok(old_price)
+    }
+
+    /// Checks if the listing was created by `seller`.
+    pub fn is_seller(&self, seller: &Pubkey) -> bool {
+        self.seller == *seller
+    }
+}
+
+impl Size for AgentListing {
+    const SIZE: usize = 168; // 8 (discriminator) + 160 (struct, including padding) = 168 bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_listing_size() {
+        let actual_size = 8 + std::mem::size_of::<AgentListing>();
+        assert_eq!(actual_size, AgentListing::SIZE);
+    }
+
+    #[test]
+    fn test_agent_listing_lifecycle() {
+        let seller = Pubkey::new_unique();
+        let mut listing = AgentListing::default();
+
+        assert!(listing
+            .initialize(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::default(),
+                Pubkey::new_unique(),
+                100,
+                1000,
+                255
+            )
+            .is_err());
+        assert!(listing
+            .initialize(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                seller,
+                Pubkey::new_unique(),
+                0,
+                1000,
+                255
+            )
+            .is_err());
+
+        listing
+            .initialize(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                seller,
+                Pubkey::new_unique(),
+                100,
+                1000,
+                255,
+            )
+            .unwrap();
+        assert!(listing.is_seller(&seller));
+        assert!(!listing.is_seller(&Pubkey::new_unique()));
+        assert_eq!(listing.created_at, 1000);
+
+        assert_eq!(listing.update_price(250, 2000).unwrap(), 100);
+        assert_eq!(listing.price, 250);
+        assert_eq!(listing.updated_at, 2000);
+        assert!(listing.update_price(0, 3000).is_err());
+        assert_eq!(listing.price, 250);
+    }
+}
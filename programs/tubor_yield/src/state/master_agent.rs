@@ -10,7 +10,8 @@
 //! - **MasterAgentInitParams**: Parameters required to initialize a new MasterAgent account.
 //! - **TaxConfig**: Struct for buy/sell/max tax rates, with validation and default values.
 //! - **MasterAgent**: The main on-chain account, storing all critical state for a master agent, including authority, price, yield, supply, trading status, and tax config.
-//! - **TradingStatus**: Enum for agent trading modes (Whitelist/Public).
+//! - **TradingStatus**: Enum for agent trading modes (Whitelist/Public/Presale/Sunset).
+//! - **PricingCurve**: Enum for agent pricing (Flat/Linear/Exponential in circulating supply).
 //!
 //! ## Key Methods
 //!
@@ -18,8 +19,11 @@
 //! - `update_price`: Securely updates the agent price, with authority, time, and rate-limit checks.
 //! - `update_yield`: Securely updates the yield rate, with authority, time, and rate-limit checks.
 //! - `update_max_supply`, `add_agent`, `remove_agent`: Manage agent supply.
+//! - `set_relist_policy`, `get_relist_policy`, `take_back_agent`: Relist, hold or burn agents returned to the protocol.
 //! - `set_trading_status`, `toggle_trading_status`: Manage trading mode (Whitelist/Public).
+//! - `set_whitelist_root`, `verify_whitelist_proof`: Merkle-proof whitelist of wallets allowed to buy.
 //! - `calculate_buy_price_with_tax`, `calculate_sell_price_with_tax`: Compute buy/sell prices including tax.
+//! - `calculate_buy_quote`, `calculate_sell_quote`, `get_curve_price`: Quote N agents along the pricing curve.
 //! - `update_tax_config`: Securely update tax configuration with authority and rate-limiting.
 //! - `validate_security`: Comprehensive security validation for all critical fields and activity patterns.
 //!
@@ -47,12 +51,22 @@
 //! ---
 //! For detailed method-level documentation, see the doc comments on each struct and function below.
 
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::hash::hashv};
 
 use crate::error::{ErrorCode, TYieldResult};
-use crate::math::{SafeMath, PERCENTAGE_PRECISION_U64, QUOTE_PRECISION_U64};
+use crate::math::{
+    SafeMath, CURVE_PRECISION, PERCENTAGE_PRECISION_U128, PERCENTAGE_PRECISION_U64,
+    QUOTE_PRECISION_U64, U192, YEARLY_SECONDS_PRECISION, YIELD_INDEX_PRECISION,
+};
 use crate::state::Size;
 
+/// Maximum bonding curve factor (10x).
+/// PRECISION: PERCENTAGE_PRECISION
+pub const MAX_CURVE_FACTOR: u64 = 10 * PERCENTAGE_PRECISION_U64;
+
+/// Maximum depth of a whitelist Merkle proof (about one million wallets).
+pub const MAX_WHITELIST_PROOF_LEN: usize = 20;
+
 /// Represents a price breakdown including total price, tax amount, and base price.
 ///
 /// This struct is used for calculating and returning price information
@@ -77,6 +91,147 @@ pub struct AgentPrice {
     pub base_price: u64,
 }
 
+/// Breakdown of how a realized trade PnL was settled.
+///
+/// Profits are split between a protocol performance fee and holder yield
+/// (distributed through the yield index). Losses, and profits made while no
+/// agents are outstanding, adjust the master agent NAV instead.
+///
+/// # Example
+/// ```
+/// # use tubor_yield::state::master_agent::PnlSettlement;
+/// let settlement = PnlSettlement {
+///     protocol_fee: 1000,   // 10% performance fee on a 10_000 profit
+///     holder_yield: 9000,   // Distributed to agent holders
+///     nav_delta: 0,         // NAV unchanged
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PnlSettlement {
+    /// Performance fee taken by the protocol
+    pub protocol_fee: u64,
+    /// Amount distributed to agent holders as yield
+    pub holder_yield: u64,
+    /// Signed change applied to the master agent NAV
+    pub nav_delta: i64,
+}
+
+/// Realized trading performance of a master agent.
+///
+/// Updated every time a trade PnL is settled. Equity is the cumulative net PnL, starting at
+/// zero, and drawdowns are measured from its running peak.
+///
+/// # Example
+/// ```
+/// # use tubor_yield::state::master_agent::PerformanceLedger;
+/// let mut ledger = PerformanceLedger::default();
+/// ledger.record(10_000).unwrap();
+/// ledger.record(-4_000).unwrap();
+/// assert_eq!(ledger.net_pnl, 6_000);
+/// assert_eq!(ledger.max_drawdown, 4_000);
+/// ```
+#[derive(Debug, Clone, Copy, Default, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
+pub struct PerformanceLedger {
+    /// Sum of all realized profits.
+    /// QUOTE PRECISION
+    pub gross_profit: u64,
+    /// Sum of all realized losses.
+    /// QUOTE PRECISION
+    pub gross_loss: u64,
+    /// Net realized PnL across all settled trades.
+    /// QUOTE PRECISION
+    pub net_pnl: i64,
+    /// Trades settled with a profit
+    pub win_count: u64,
+    /// Trades settled with a loss
+    pub loss_count: u64,
+    /// Largest single realized profit.
+    /// QUOTE PRECISION
+    pub largest_win: u64,
+    /// Largest single realized loss.
+    /// QUOTE PRECISION
+    pub largest_loss: u64,
+    /// Highest net PnL reached so far.
+    /// QUOTE PRECISION
+    pub peak_equity: i64,
+    /// Largest drop of net PnL from its peak.
+    /// QUOTE PRECISION
+    pub max_drawdown: u64,
+}
+
+impl PerformanceLedger {
+    /// Record the realized PnL of a settled trade. Break-even trades count as neither win nor loss.
+    pub fn record(&mut self, pnl: i64) -> TYieldResult<()> {
+        let amount = pnl.unsigned_abs();
+        if pnl > 0 {
+            self.gross_profit = self.gross_profit.safe_add(amount)?;
+            self.win_count = self.win_count.safe_add(1)?;
+            self.largest_win = self.largest_win.max(amount);
+        } else if pnl < 0 {
+            self.gross_loss = self.gross_loss.safe_add(amount)?;
+            self.loss_count = self.loss_count.safe_add(1)?;
+            self.largest_loss = self.largest_loss.max(amount);
+        }
+
+        self.net_pnl = self.net_pnl.safe_add(pnl)?;
+        self.peak_equity = self.peak_equity.max(self.net_pnl);
+        self.max_drawdown = self.max_drawdown.max(self.get_current_drawdown()?);
+        Ok(())
+    }
+
+    /// Drop of net PnL from its peak
+    pub fn get_current_drawdown(&self) -> TYieldResult<u64> {
+        Ok(self.peak_equity.safe_sub(self.net_pnl)?.unsigned_abs())
+    }
+
+    /// Share of winning trades among trades that won or lost.
+    /// PRECISION: PERCENTAGE_PRECISION
+    pub fn get_win_rate(&self) -> TYieldResult<u64> {
+        let decided = self.win_count.safe_add(self.loss_count)?;
+        if decided == 0 {
+            return Ok(0);
+        }
+        self.win_count
+            .safe_mul(PERCENTAGE_PRECISION_U64)?
+            .safe_div(decided)
+    }
+
+    /// Gross profit over gross loss (0 if there were no losses).
+    /// PRECISION: PERCENTAGE_PRECISION
+    pub fn get_profit_factor(&self) -> TYieldResult<u64> {
+        if self.gross_loss == 0 {
+            return Ok(0);
+        }
+        let factor = (self.gross_profit as u128)
+            .safe_mul(PERCENTAGE_PRECISION_U128)?
+            .safe_div(self.gross_loss as u128)?;
+        Ok(u64::try_from(factor).unwrap_or(u64::MAX))
+    }
+}
+
+/// Performance report returned by `get_master_agent_performance`.
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
+pub struct MasterAgentPerformance {
+    /// Realized trading ledger
+    pub ledger: PerformanceLedger,
+    /// Trades closed, including break-even ones
+    pub completed_trades: u64,
+    /// PRECISION: PERCENTAGE_PRECISION
+    pub win_rate: u64,
+    /// PRECISION: PERCENTAGE_PRECISION
+    pub profit_factor: u64,
+    /// QUOTE PRECISION
+    pub current_drawdown: u64,
+    /// QUOTE PRECISION
+    pub total_value_locked: u64,
+    /// QUOTE PRECISION
+    pub total_yield: u64,
+    /// QUOTE PRECISION
+    pub yield_efficiency: u64,
+    /// Trades per day since creation
+    pub activity_score: u64,
+}
+
 #[event]
 pub struct UpdatePriceEvent {
     /// The authority that performed the price update
@@ -180,6 +335,34 @@ pub struct UpdateYieldEvent {
     pub bump: u8,
 }
 
+/// Event emitted when the whitelist Merkle root of a master agent is updated.
+#[event]
+pub struct WhitelistRootUpdatedEvent {
+    /// The master agent account
+    pub master_agent: Pubkey,
+    /// The previous Merkle root (all zeros = none)
+    pub old_root: [u8; 32],
+    /// The new Merkle root (all zeros = none)
+    pub new_root: [u8; 32],
+    /// The timestamp when the root was updated
+    pub timestamp: i64,
+}
+
+/// Event emitted when the relist policy of a master agent is updated.
+#[event]
+pub struct RelistPolicyUpdatedEvent {
+    /// The master agent account
+    pub master_agent: Pubkey,
+    /// The previous `RelistPolicy`
+    pub old_policy: u8,
+    /// The new `RelistPolicy`
+    pub new_policy: u8,
+    /// Seconds returned agents stay unlisted under the cooldown policy
+    pub relist_cooldown: i64,
+    /// The timestamp when the policy was updated
+    pub timestamp: i64,
+}
+
 /// Parameters required for initializing a new MasterAgent account.
 ///
 /// This struct contains all the necessary parameters to create a new MasterAgent,
@@ -189,7 +372,7 @@ pub struct UpdateYieldEvent {
 /// # Example
 /// ```
 /// # use anchor_lang::prelude::*;
-/// # use tubor_yield::state::master_agent::{MasterAgentInitParams, TradingStatus, TaxConfig};
+/// # use tubor_yield::state::master_agent::{MasterAgentInitParams, PricingCurve, TradingStatus, TaxConfig};
 /// let params = MasterAgentInitParams {
 ///     authority: Pubkey::new_unique(),
 ///     mint: Pubkey::new_unique(),
@@ -201,6 +384,9 @@ pub struct UpdateYieldEvent {
 ///     current_time: 1640995200,
 ///     bump: 1,
 ///     tax_config: TaxConfig::default(),
+///     seller_fee_basis_points: 500,
+///     pricing_curve: PricingCurve::Flat,
+///     curve_factor: 0,
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -225,6 +411,12 @@ pub struct MasterAgentInitParams {
     pub bump: u8,
     /// Tax configuration for buy/sell operations
     pub tax_config: TaxConfig,
+    /// Royalty paid to the authority on secondary sales (basis points)
+    pub seller_fee_basis_points: u16,
+    /// Pricing curve applied on top of the base price
+    pub pricing_curve: PricingCurve,
+    /// Curve steepness in basis points (ignored for a flat curve)
+    pub curve_factor: u64,
 }
 
 /// Tax configuration for buy and sell operations.
@@ -288,7 +480,7 @@ impl Default for TaxConfig {
 /// # Example
 /// ```
 /// # use anchor_lang::prelude::*;
-/// # use tubor_yield::state::master_agent::{MasterAgent, MasterAgentInitParams, TradingStatus, TaxConfig};
+/// # use tubor_yield::state::master_agent::{MasterAgent, MasterAgentInitParams, PricingCurve, TradingStatus, TaxConfig};
 /// let mut master_agent = MasterAgent::default();
 /// let params = MasterAgentInitParams {
 ///     authority: Pubkey::new_unique(),
@@ -301,6 +493,9 @@ impl Default for TaxConfig {
 ///     current_time: 1640995200,
 ///     bump: 1,
 ///     tax_config: TaxConfig::default(),
+///     seller_fee_basis_points: 500,
+///     pricing_curve: PricingCurve::Flat,
+///     curve_factor: 0,
 /// };
 /// master_agent.initialize(params).unwrap();
 /// ```
@@ -321,6 +516,7 @@ pub struct MasterAgent {
 
     pub completed_trades: u64,
 
+    /// Sum of absolute realized PnL. Superseded by `performance`.
     /// QUOTE PRECISION
     pub total_pnl: u64,
 
@@ -337,8 +533,49 @@ pub struct MasterAgent {
     // SECURITY: Store tax configuration securely
     pub tax_config: TaxConfig, // 24 bytes (3 u64 fields)
 
-    // Future-proofing padding
-    pub _padding: [u8; 1], // 1 byte for future additions
+    pub pricing_curve: u8, // 1 byte
+
+    // Fields below were appended after the initial layout and are
+    // populated for existing accounts by `migrate_master_agent`
+    /// Root of the Merkle tree of whitelisted wallets (all zeros = use the user whitelist flag).
+    pub whitelist_root: [u8; 32], // 32 bytes
+
+    /// Realized trading performance across all settled trades.
+    pub performance: PerformanceLedger,
+
+    /// Steepness of the pricing curve.
+    /// PRECISION: PERCENTAGE_PRECISION
+    pub curve_factor: u64,
+
+    /// Holder capital backing outstanding agents, less realized losses.
+    /// QUOTE PRECISION
+    pub nav: u64,
+
+    /// Agents currently held by users.
+    pub held_agents: u64,
+
+    /// Accumulated yield per agent at a 1x booster.
+    /// PRECISION: YIELD_INDEX_PRECISION
+    pub yield_index: u128,
+
+    /// Accumulated distributed yield per agent, paid without the booster.
+    /// PRECISION: YIELD_INDEX_PRECISION
+    pub distribution_index: u128,
+
+    pub last_yield_index_update: i64,
+    /// End of the presale window (0 = no presale configured).
+    pub presale_end: i64,
+    /// Seconds returned agents stay unlisted before being relisted (0 = relist immediately).
+    pub relist_cooldown: i64,
+
+    /// Whether new supply is being sold through a Dutch auction.
+    pub auction_live: bool, // 1 byte
+    /// `RelistPolicy` applied to agents returned to the protocol (0 = relist).
+    pub relist_policy: u8, // 1 byte
+
+    /// Royalty paid to the authority on secondary sales.
+    /// PRECISION: PERCENTAGE_PRECISION
+    pub seller_fee_basis_points: u16,
 }
 
 impl MasterAgent {
@@ -358,7 +595,7 @@ impl MasterAgent {
     /// # Example
     /// ```
     /// # use anchor_lang::prelude::*;
-    /// # use tubor_yield::state::master_agent::{MasterAgent, MasterAgentInitParams, TradingStatus, TaxConfig};
+    /// # use tubor_yield::state::master_agent::{MasterAgent, MasterAgentInitParams, PricingCurve, TradingStatus, TaxConfig};
     /// # use tubor_yield::error::TYieldResult;
     /// # fn example() -> TYieldResult<()> {
     /// let mut master_agent = MasterAgent::default();
@@ -373,6 +610,9 @@ impl MasterAgent {
     ///     current_time: 1640995200,
     ///     bump: 1,
     ///     tax_config: TaxConfig::default(),
+    ///     seller_fee_basis_points: 500,
+    ///     pricing_curve: PricingCurve::Flat,
+    ///     curve_factor: 0,
     /// };
     /// master_agent.initialize(params)?;
     /// # Ok(())
@@ -390,8 +630,56 @@ impl MasterAgent {
         self.auto_relist = params.auto_relist;
         self.last_updated = params.current_time;
         self.created_at = params.current_time;
+        self.yield_index = 0;
+        self.distribution_index = 0;
+        self.last_yield_index_update = params.current_time;
         self.bump = params.bump;
         self.tax_config = params.tax_config; // Store tax config securely
+        if params.seller_fee_basis_points as u64 > PERCENTAGE_PRECISION_U64 {
+            return Err(ErrorCode::MathError);
+        }
+        self.seller_fee_basis_points = params.seller_fee_basis_points;
+        if params.curve_factor > MAX_CURVE_FACTOR {
+            return Err(ErrorCode::MathError);
+        }
+        self.pricing_curve = params.pricing_curve as u8;
+        self.curve_factor = params.curve_factor;
+        Ok(())
+    }
+
+    /// Populate the fields appended to the layout for a master agent created before them.
+    ///
+    /// The appended fields read as zero, so the pricing curve stays flat, returned agents keep
+    /// being relisted and the performance ledger starts empty. Holder count, NAV and royalty
+    /// cannot be derived on-chain and are supplied by the protocol admins.
+    ///
+    /// # Arguments
+    /// * `held_agents` - Agents currently held by users
+    /// * `nav` - Holder capital backing the held agents
+    /// * `seller_fee_basis_points` - Royalty recorded in the agent NFTs' metadata
+    /// * `current_time` - The current timestamp, from which yield starts accruing
+    ///
+    /// # Errors
+    /// * `ErrorCode::InvalidAccount` - If `held_agents` exceeds the minted supply
+    /// * `ErrorCode::MathError` - If the royalty exceeds 100%
+    pub fn migrate(
+        &mut self,
+        held_agents: u64,
+        nav: u64,
+        seller_fee_basis_points: u16,
+        current_time: i64,
+    ) -> TYieldResult<()> {
+        if held_agents > self.max_supply {
+            return Err(ErrorCode::InvalidAccount);
+        }
+        if seller_fee_basis_points as u64 > PERCENTAGE_PRECISION_U64 {
+            return Err(ErrorCode::MathError);
+        }
+        self.held_agents = held_agents;
+        self.nav = nav;
+        self.seller_fee_basis_points = seller_fee_basis_points;
+        self.last_yield_index_update = current_time;
+        self.last_updated = current_time;
         Ok(())
     }
 
@@ -488,6 +776,9 @@ impl MasterAgent {
             return Err(ErrorCode::PriceUpdateTooHigh);
         }
 
+        // Checkpoint the yield index so the new price only applies going forward
+        self.update_yield_index(current_time)?;
+
         self.price = new_price;
         self.last_updated = current_time;
         self.last_price_update = current_time;
@@ -592,6 +883,9 @@ impl MasterAgent {
             return Err(ErrorCode::PriceUpdateTooHigh);
         }
 
+        // Checkpoint the yield index so the new rate only applies going forward
+        self.update_yield_index(current_time)?;
+
         self.w_yield = new_yield;
         self.last_updated = current_time;
         Ok(())
@@ -613,24 +907,202 @@ impl MasterAgent {
 
     /// Add an agent to the master agent
     pub fn add_agent(&mut self, current_time: i64) -> TYieldResult<()> {
-        if self.agent_count >= self.max_supply {
+        self.add_agents(1, current_time)
+    }
+
+    /// Add `count` agents to the master agent
+    pub fn add_agents(&mut self, count: u64, current_time: i64) -> TYieldResult<()> {
+        if self.agent_count.safe_add(count)? > self.max_supply {
             return Err(ErrorCode::InsufficientFunds);
         }
-        self.agent_count = self.agent_count.safe_add(1)?;
+        self.agent_count = self.agent_count.safe_add(count)?;
         self.last_updated = current_time;
         Ok(())
     }
 
     /// Remove an agent from the master agent
     pub fn remove_agent(&mut self, current_time: i64) -> TYieldResult<()> {
-        if self.agent_count == 0 {
+        self.remove_agents(1, current_time)
+    }
+
+    /// Remove `count` agents from the master agent
+    pub fn remove_agents(&mut self, count: u64, current_time: i64) -> TYieldResult<()> {
+        if self.agent_count < count {
             return Err(ErrorCode::InsufficientFunds);
         }
-        self.agent_count = self.agent_count.safe_sub(1)?;
+        self.agent_count = self.agent_count.safe_sub(count)?;
         self.last_updated = current_time;
         Ok(())
     }
 
+    /// Record an agent leaving protocol custody for a holder.
+    ///
+    /// The base price paid is added to the NAV backing outstanding agents.
+    pub fn record_agent_purchase(&mut self, base_price: u64) -> TYieldResult<()> {
+        self.record_agent_purchases(1, base_price)
+    }
+
+    /// Record `count` agents leaving protocol custody for holders.
+    ///
+    /// The total base price paid is added to the NAV backing outstanding agents.
+    pub fn record_agent_purchases(&mut self, count: u64, base_price: u64) -> TYieldResult<()> {
+        self.held_agents = self.held_agents.safe_add(count)?;
+        self.nav = self.nav.safe_add(base_price)?;
+        Ok(())
+    }
+
+    /// Record an agent returning from a holder to the protocol.
+    ///
+    /// Releases the agent's pro-rata share of the NAV and returns it.
+    pub fn record_agent_sale(&mut self) -> TYieldResult<u64> {
+        self.record_agent_sales(1)
+    }
+
+    /// Record `count` agents returning from holders to the protocol.
+    ///
+    /// Releases their pro-rata share of the NAV and returns it.
+    pub fn record_agent_sales(&mut self, count: u64) -> TYieldResult<u64> {
+        let nav_share = self.get_nav_per_agent()?.safe_mul(count)?;
+        self.held_agents = self.held_agents.safe_sub(count)?;
+        self.nav = self.nav.safe_sub(nav_share)?;
+        Ok(nav_share)
+    }
+
+    /// Take back an agent returned by a holder according to the relist policy.
+    ///
+    /// Relisted and cooling-down agents rejoin the unsold supply; held agents stay in
+    /// protocol custody outside of it; burned agents are retired from `max_supply`
+    /// (never below the unsold supply).
+    ///
+    /// # Returns
+    /// * `Ok(policy)` - The relist policy applied to the agent
+    pub fn take_back_agent(&mut self, current_time: i64) -> TYieldResult<RelistPolicy> {
+        self.take_back_agents(1, current_time)
+    }
+
+    /// Take back `count` agents returned by holders according to the relist policy.
+    pub fn take_back_agents(
+        &mut self,
+        count: u64,
+        current_time: i64,
+    ) -> TYieldResult<RelistPolicy> {
+        let policy = self.get_relist_policy();
+        match policy {
+            RelistPolicy::Relist | RelistPolicy::Cooldown => {
+                self.add_agents(count, current_time)?
+            }
+            RelistPolicy::Hold => self.last_updated = current_time,
+            RelistPolicy::Burn => self.retire_agents(count, current_time)?,
+        }
+        Ok(policy)
+    }
+
+    /// Retire `count` burned agents from the supply so they can never be minted again.
+    pub fn retire_agents(&mut self, count: u64, current_time: i64) -> TYieldResult<()> {
+        self.max_supply = self.max_supply.safe_sub(count)?.max(self.agent_count);
+        self.last_updated = current_time;
+        Ok(())
+    }
+
+    /// Get the NAV share backing a single outstanding agent
+    pub fn get_nav_per_agent(&self) -> TYieldResult<u64> {
+        if self.held_agents == 0 {
+            return Ok(0);
+        }
+        self.nav.safe_div(self.held_agents)
+    }
+
+    /// Distribute yield evenly across all outstanding agents through the distribution index.
+    ///
+    /// Unlike the time-based yield index, distributed yield is not scaled by the agent
+    /// booster, so holders are never credited more than `amount` in total.
+    ///
+    /// # Arguments
+    /// * `amount` - Total yield to distribute
+    /// * `current_time` - The current timestamp
+    ///
+    /// # Returns
+    /// * `Err(ErrorCode::CannotPerformAction)` - If there are no outstanding agents
+    pub fn distribute_yield(&mut self, amount: u64, current_time: i64) -> TYieldResult<()> {
+        if self.held_agents == 0 {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+
+        let index_delta = U192::from(amount)
+            .safe_mul(U192::from(YIELD_INDEX_PRECISION))?
+            .safe_div(U192::from(self.held_agents))?
+            .try_to_u128()?;
+        self.distribution_index = self.distribution_index.safe_add(index_delta)?;
+        self.last_updated = current_time;
+        Ok(())
+    }
+
+    /// Settle the realized PnL of a completed trade.
+    ///
+    /// Profits pay a performance fee to the protocol and the remainder is
+    /// distributed to agent holders. If no agents are outstanding the remainder
+    /// is retained in the NAV. Losses reduce the NAV, floored at zero. The PnL
+    /// is recorded in the performance ledger either way.
+    ///
+    /// # Arguments
+    /// * `pnl` - Signed realized PnL of the trade
+    /// * `performance_fee` - Protocol performance fee in basis points
+    /// * `current_time` - The current timestamp
+    ///
+    /// # Returns
+    /// * `TYieldResult<PnlSettlement>` - How the PnL was settled
+    ///
+    /// # Example
+    /// ```
+    /// # use tubor_yield::state::master_agent::MasterAgent;
+    /// # use tubor_yield::error::TYieldResult;
+    /// # fn example() -> TYieldResult<()> {
+    /// let mut master_agent = MasterAgent::default();
+    /// master_agent.record_agent_purchase(1_000_000)?;
+    ///
+    /// let settlement = master_agent.settle_realized_pnl(10_000, 1000, 1640995200)?;
+    /// assert_eq!(settlement.protocol_fee, 1_000);
+    /// assert_eq!(settlement.holder_yield, 9_000);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn settle_realized_pnl(
+        &mut self,
+        pnl: i64,
+        performance_fee: u64,
+        current_time: i64,
+    ) -> TYieldResult<PnlSettlement> {
+        if performance_fee > PERCENTAGE_PRECISION_U64 {
+            return Err(ErrorCode::MathError);
+        }
+
+        let mut settlement = PnlSettlement::default();
+        if pnl > 0 {
+            let profit = pnl.unsigned_abs();
+            settlement.protocol_fee = profit
+                .safe_mul(performance_fee)?
+                .safe_div(PERCENTAGE_PRECISION_U64)?;
+            let remainder = profit.safe_sub(settlement.protocol_fee)?;
+
+            if self.held_agents > 0 {
+                self.distribute_yield(remainder, current_time)?;
+                settlement.holder_yield = remainder;
+            } else {
+                self.nav = self.nav.safe_add(remainder)?;
+                settlement.nav_delta = remainder as i64;
+            }
+        } else if pnl < 0 {
+            let loss = pnl.unsigned_abs().min(self.nav);
+            self.nav = self.nav.safe_sub(loss)?;
+            settlement.nav_delta = -(loss as i64);
+        }
+
+        self.performance.record(pnl)?;
+        self.total_pnl = self.total_pnl.safe_add(pnl.unsigned_abs())?;
+        self.last_updated = current_time;
+        Ok(settlement)
+    }
+
     /// Increment trade count
     pub fn increment_trade_count(&mut self, current_time: i64) -> TYieldResult<()> {
         self.trade_count = self.trade_count.safe_add(1)?;
@@ -650,15 +1122,85 @@ impl MasterAgent {
         self.last_updated = current_time;
     }
 
+    /// Set how long returned agents stay unlisted before being relisted.
+    ///
+    /// # Errors
+    /// * `ErrorCode::InvalidState` - If the cooldown is negative
+    pub fn set_relist_cooldown(
+        &mut self,
+        relist_cooldown: i64,
+        current_time: i64,
+    ) -> TYieldResult<()> {
+        if relist_cooldown < 0 {
+            return Err(ErrorCode::InvalidState);
+        }
+        self.relist_cooldown = relist_cooldown;
+        self.last_updated = current_time;
+        Ok(())
+    }
+
+    /// Set what happens to agents returned to the protocol.
+    ///
+    /// # Errors
+    /// * `ErrorCode::InvalidState` - If the policy is `Hold`, which follows from `auto_relist`
+    pub fn set_relist_policy(
+        &mut self,
+        relist_policy: RelistPolicy,
+        current_time: i64,
+    ) -> TYieldResult<()> {
+        if relist_policy == RelistPolicy::Hold {
+            return Err(ErrorCode::InvalidState);
+        }
+        self.relist_policy = relist_policy as u8;
+        self.last_updated = current_time;
+        Ok(())
+    }
+
+    /// Get what happens to agents returned to the protocol.
+    ///
+    /// Burning only happens when `relist_policy` opts into it. Otherwise agents are
+    /// relisted, after `relist_cooldown` seconds for the cooldown policy, unless
+    /// `auto_relist` is disabled, in which case they are held unlisted by the protocol.
+    pub fn get_relist_policy(&self) -> RelistPolicy {
+        match self.relist_policy {
+            2 => RelistPolicy::Burn,
+            _ if !self.auto_relist => RelistPolicy::Hold,
+            1 => RelistPolicy::Cooldown,
+            _ => RelistPolicy::Relist,
+        }
+    }
+
+    /// Get the time from which an agent returned at `current_time` can be bought again.
+    ///
+    /// Returns `None` if returned agents are not relisted.
+    pub fn get_relist_time(&self, current_time: i64) -> TYieldResult<Option<i64>> {
+        match self.get_relist_policy() {
+            RelistPolicy::Relist => Ok(Some(current_time)),
+            RelistPolicy::Cooldown => Ok(Some(current_time.safe_add(self.relist_cooldown)?)),
+            RelistPolicy::Hold | RelistPolicy::Burn => Ok(None),
+        }
+    }
+
     /// Get the current trading status as an enum
     pub fn get_trading_status(&self) -> TradingStatus {
         match self.trading_status {
             0b00000001 => TradingStatus::WhiteList,
             0b00000010 => TradingStatus::Public,
+            0b00000100 => TradingStatus::Presale,
+            0b00001000 => TradingStatus::Sunset,
             _ => TradingStatus::WhiteList, // Default fallback
         }
     }
 
+    /// Get the pricing curve as an enum
+    pub fn get_pricing_curve(&self) -> PricingCurve {
+        match self.pricing_curve {
+            1 => PricingCurve::Linear,
+            2 => PricingCurve::Exponential,
+            _ => PricingCurve::Flat, // Default fallback
+        }
+    }
+
     /// Set the trading status
     /// SECURITY: Added authority validation to prevent unauthorized status changes
     pub fn set_trading_status(
@@ -671,6 +1213,10 @@ impl MasterAgent {
         if authority != &self.authority {
             return Err(ErrorCode::InvalidAuthority);
         }
+        // A sunset is final
+        if self.is_sunset() {
+            return Err(ErrorCode::CannotPerformAction);
+        }
 
         self.trading_status = status as u8;
         self.last_updated = current_time;
@@ -687,6 +1233,134 @@ impl MasterAgent {
         self.get_trading_status() == TradingStatus::Public
     }
 
+    /// Check if the master agent is being wound down
+    pub fn is_sunset(&self) -> bool {
+        self.get_trading_status() == TradingStatus::Sunset
+    }
+
+    /// Switch to sunset mode, blocking new buys and trades for good.
+    ///
+    /// The NAV backing outstanding agents is released, as holders are bought
+    /// back at the buyback price instead.
+    ///
+    /// # Returns
+    /// * `Ok(nav)` - The NAV released by the sunset
+    ///
+    /// # Errors
+    /// * `ErrorCode::CannotPerformAction` - If already sunset or an auction or presale is running
+    pub fn start_sunset(&mut self, current_time: i64) -> TYieldResult<u64> {
+        if self.is_sunset() || self.auction_live || self.is_presale_active(current_time) {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        let nav = self.nav;
+        self.nav = 0;
+        self.trading_status = TradingStatus::Sunset as u8;
+        self.last_updated = current_time;
+        Ok(nav)
+    }
+
+    /// Record an agent being bought back during a sunset.
+    ///
+    /// The NAV was already released when the sunset started, so only the holder count changes.
+    /// The agent follows the relist policy, except that a sunset master agent never sells again:
+    /// under `Burn` it is retired from the supply, under every other policy it is held unlisted
+    /// by the protocol.
+    ///
+    /// # Returns
+    /// * `Ok(policy)` - The relist policy applied to the agent
+    pub fn record_sunset_buyback(&mut self, current_time: i64) -> TYieldResult<RelistPolicy> {
+        self.held_agents = self.held_agents.safe_sub(1)?;
+        let policy = self.get_relist_policy();
+        if policy == RelistPolicy::Burn {
+            self.retire_agents(1, current_time)?;
+        } else {
+            self.last_updated = current_time;
+        }
+        Ok(policy)
+    }
+
+    /// Check if a whitelist Merkle root is set
+    pub fn has_whitelist_root(&self) -> bool {
+        self.whitelist_root != [0; 32]
+    }
+
+    /// Set the whitelist Merkle root. An all-zero root falls back to the user whitelist flag.
+    pub fn set_whitelist_root(&mut self, root: [u8; 32], current_time: i64) {
+        self.whitelist_root = root;
+        self.last_updated = current_time;
+    }
+
+    /// Verify that `wallet` is a leaf of the whitelist Merkle tree.
+    ///
+    /// Leaves are `sha256(wallet)` and every node is the sha256 of its two children
+    /// in ascending byte order, so the proof only needs the sibling hashes.
+    ///
+    /// # Returns
+    /// * `true` - If a root is set and `proof` leads from `wallet` to it
+    pub fn verify_whitelist_proof(&self, wallet: &Pubkey, proof: &[[u8; 32]]) -> bool {
+        if !self.has_whitelist_root() || proof.len() > MAX_WHITELIST_PROOF_LEN {
+            return false;
+        }
+        let mut node = hashv(&[wallet.as_ref()]).to_bytes();
+        for sibling in proof {
+            node = if node <= *sibling {
+                hashv(&[&node, sibling]).to_bytes()
+            } else {
+                hashv(&[sibling, &node]).to_bytes()
+            };
+        }
+        node == self.whitelist_root
+    }
+
+    /// Check if `wallet` may buy from a master agent in whitelist mode.
+    ///
+    /// With a Merkle root set the wallet must prove its membership; otherwise the
+    /// `UserStatus::WithListed` flag of the user is used.
+    pub fn is_wallet_whitelisted(
+        &self,
+        wallet: &Pubkey,
+        proof: &[[u8; 32]],
+        user_is_whitelisted: bool,
+    ) -> bool {
+        if self.has_whitelist_root() {
+            self.verify_whitelist_proof(wallet, proof)
+        } else {
+            user_is_whitelisted
+        }
+    }
+
+    /// Check if the presale window is still open at `current_time`
+    pub fn is_presale_active(&self, current_time: i64) -> bool {
+        self.get_trading_status() == TradingStatus::Presale && current_time < self.presale_end
+    }
+
+    /// Switch to presale mode until `presale_end`.
+    ///
+    /// # Errors
+    /// * `ErrorCode::CannotPerformAction` - If the window has already ended
+    pub fn start_presale(&mut self, presale_end: i64, current_time: i64) -> TYieldResult<()> {
+        if presale_end <= current_time {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        self.trading_status = TradingStatus::Presale as u8;
+        self.presale_end = presale_end;
+        self.last_updated = current_time;
+        Ok(())
+    }
+
+    /// Switch to public mode once the presale window has closed.
+    ///
+    /// # Returns
+    /// * `true` - If the master agent was switched to public mode
+    pub fn close_presale_if_ended(&mut self, current_time: i64) -> bool {
+        if self.get_trading_status() != TradingStatus::Presale || current_time < self.presale_end {
+            return false;
+        }
+        self.trading_status = TradingStatus::Public as u8;
+        self.last_updated = current_time;
+        true
+    }
+
     /// Toggle between whitelist and public mode
     pub fn toggle_trading_status(&mut self, current_time: i64) {
         let new_status = if self.is_whitelist_mode() {
@@ -705,6 +1379,82 @@ impl MasterAgent {
         Ok(yield_with_precision)
     }
 
+    /// Calculate the yield owed to a single agent for a holding period.
+    ///
+    /// `w_yield` is an annual rate on the agent price and `booster` scales it in
+    /// basis points (10000 = 1x), so the result is
+    /// `price * w_yield * booster * elapsed / (10000 * 10000 * YEAR)`.
+    ///
+    /// # Arguments
+    /// * `booster` - Agent boost multiplier in basis points
+    /// * `elapsed` - Seconds the agent has been held since the last accrual
+    ///
+    /// # Returns
+    /// * `TYieldResult<u64>` - Yield owed in Y-mint base units (0 for non-positive periods)
+    pub fn calculate_accrued_yield(&self, booster: u64, elapsed: i64) -> TYieldResult<u64> {
+        if elapsed <= 0 || booster == 0 {
+            return Ok(0);
+        }
+
+        let numerator = U192::from(self.price)
+            .safe_mul(U192::from(self.w_yield))?
+            .safe_mul(U192::from(booster))?
+            .safe_mul(U192::from(elapsed as u64))?;
+        let denominator = U192::from(PERCENTAGE_PRECISION_U64)
+            .safe_mul(U192::from(PERCENTAGE_PRECISION_U64))?
+            .safe_mul(U192::from(YEARLY_SECONDS_PRECISION))?;
+
+        numerator.safe_div(denominator)?.try_to_u64()
+    }
+
+    /// Calculate the yield index including the portion accrued since the last checkpoint.
+    ///
+    /// The index is the yield earned by a single agent at a 1x booster since inception,
+    /// scaled by `YIELD_INDEX_PRECISION`. Intermediate math is done in `U192` so large
+    /// prices and long periods cannot overflow.
+    ///
+    /// # Arguments
+    /// * `current_time` - The current timestamp
+    ///
+    /// # Returns
+    /// * `TYieldResult<u128>` - The yield index as of `current_time`
+    pub fn get_yield_index(&self, current_time: i64) -> TYieldResult<u128> {
+        let elapsed = current_time.safe_sub(self.last_yield_index_update)?;
+        if elapsed <= 0 || self.last_yield_index_update == 0 {
+            return Ok(self.yield_index);
+        }
+
+        let index_delta = U192::from(self.price)
+            .safe_mul(U192::from(self.w_yield))?
+            .safe_mul(U192::from(elapsed as u64))?
+            .safe_mul(U192::from(YIELD_INDEX_PRECISION))?
+            .safe_div(
+                U192::from(PERCENTAGE_PRECISION_U64)
+                    .safe_mul(U192::from(YEARLY_SECONDS_PRECISION))?,
+            )?
+            .try_to_u128()?;
+
+        self.yield_index.safe_add(index_delta)
+    }
+
+    /// Checkpoint the yield index at `current_time`.
+    ///
+    /// Must be called before any change to `price` or `w_yield` so that rate
+    /// changes are never applied retroactively.
+    ///
+    /// # Arguments
+    /// * `current_time` - The current timestamp
+    ///
+    /// # Returns
+    /// * `TYieldResult<u128>` - The updated yield index
+    pub fn update_yield_index(&mut self, current_time: i64) -> TYieldResult<u128> {
+        self.yield_index = self.get_yield_index(current_time)?;
+        if current_time > self.last_yield_index_update {
+            self.last_yield_index_update = current_time;
+        }
+        Ok(self.yield_index)
+    }
+
     /// Get the current yield rate as a percentage
     pub fn get_yield_rate_percentage(&self) -> u64 {
         (self.w_yield.safe_mul(100).unwrap_or(0))
@@ -836,8 +1586,9 @@ impl MasterAgent {
     /// Check if the master agent can be accessed by a user (based on trading status)
     pub fn can_be_accessed_by_user(&self, user_is_whitelisted: bool) -> bool {
         match self.get_trading_status() {
-            TradingStatus::WhiteList => user_is_whitelisted,
+            TradingStatus::WhiteList | TradingStatus::Presale => user_is_whitelisted,
             TradingStatus::Public => true,
+            TradingStatus::Sunset => false,
         }
     }
 
@@ -880,13 +1631,19 @@ impl MasterAgent {
             .unwrap_or(0)
     }
 
-    /// Get performance metrics
-    pub fn get_performance_metrics(&self, current_time: i64) -> TYieldResult<(u64, u64, u64, u64)> {
-        let total_value = self.get_total_value_locked();
-        let total_yield = self.get_total_yield_generated()?;
-        let yield_efficiency = self.get_yield_efficiency()?;
-        let activity_score = self.get_trading_activity_score(current_time);
-        Ok((total_value, total_yield, yield_efficiency, activity_score))
+    /// Get the performance report of the master agent
+    pub fn get_performance(&self, current_time: i64) -> TYieldResult<MasterAgentPerformance> {
+        Ok(MasterAgentPerformance {
+            ledger: self.performance,
+            completed_trades: self.completed_trades,
+            win_rate: self.performance.get_win_rate()?,
+            profit_factor: self.performance.get_profit_factor()?,
+            current_drawdown: self.performance.get_current_drawdown()?,
+            total_value_locked: self.get_total_value_locked(),
+            total_yield: self.get_total_yield_generated()?,
+            yield_efficiency: self.get_yield_efficiency()?,
+            activity_score: self.get_trading_activity_score(current_time),
+        })
     }
 
     /// Reset the master agent (for testing/debugging)
@@ -946,6 +1703,8 @@ impl MasterAgent {
         match self.get_trading_status() {
             TradingStatus::WhiteList => "Whitelist".to_string(),
             TradingStatus::Public => "Public".to_string(),
+            TradingStatus::Presale => "Presale".to_string(),
+            TradingStatus::Sunset => "Sunset".to_string(),
         }
     }
 
@@ -979,11 +1738,24 @@ impl MasterAgent {
     /// - Validates tax rates against maximum limits
     /// - All calculations use safe math operations
     pub fn calculate_buy_price_with_tax(&self) -> TYieldResult<(u64, u64, u64)> {
+        self.calculate_buy_quote(1)
+    }
+
+    /// Calculate the cost of buying `count` agents, walking up the pricing curve.
+    ///
+    /// # Returns
+    /// * `Ok((total_price, tax_amount, base_price))` - The price breakdown for all agents
+    /// * `Err(ErrorCode::MathError)` - If tax configuration is invalid
+    pub fn calculate_buy_quote(&self, count: u64) -> TYieldResult<(u64, u64, u64)> {
         if self.tax_config.buy_tax_percentage > self.tax_config.max_tax_percentage {
             return Err(ErrorCode::MathError);
         }
 
-        let base_price = self.price;
+        let mut base_price: u64 = 0;
+        for i in 0..count {
+            base_price =
+                base_price.safe_add(self.get_curve_price(self.held_agents.safe_add(i)?)?)?;
+        }
         let tax_amount = base_price
             .safe_mul(self.tax_config.buy_tax_percentage)?
             .safe_div(PERCENTAGE_PRECISION_U64)?;
@@ -1022,11 +1794,33 @@ impl MasterAgent {
     /// - Validates tax rates against maximum limits
     /// - All calculations use safe math operations
     pub fn calculate_sell_price_with_tax(&self) -> TYieldResult<(u64, u64, u64)> {
+        self.calculate_sell_quote(1)
+    }
+
+    /// Calculate the proceeds of selling `count` agents, walking down the pricing curve.
+    ///
+    /// # Returns
+    /// * `Ok((net_price, tax_amount, base_price))` - The price breakdown for all agents
+    /// * `Err(ErrorCode::MathError)` - If tax configuration is invalid
+    /// * `Err(ErrorCode::InsufficientFunds)` - If fewer than `count` agents are held on a curve
+    pub fn calculate_sell_quote(&self, count: u64) -> TYieldResult<(u64, u64, u64)> {
         if self.tax_config.sell_tax_percentage > self.tax_config.max_tax_percentage {
             return Err(ErrorCode::MathError);
         }
 
-        let base_price = self.price;
+        let base_price = if self.get_pricing_curve() == PricingCurve::Flat {
+            self.price.safe_mul(count)?
+        } else {
+            if count > self.held_agents {
+                return Err(ErrorCode::InsufficientFunds);
+            }
+            let mut base_price: u64 = 0;
+            for i in 1..=count {
+                base_price =
+                    base_price.safe_add(self.get_curve_price(self.held_agents.safe_sub(i)?)?)?;
+            }
+            base_price
+        };
         let tax_amount = base_price
             .safe_mul(self.tax_config.sell_tax_percentage)?
             .safe_div(PERCENTAGE_PRECISION_U64)?;
@@ -1035,6 +1829,94 @@ impl MasterAgent {
         Ok((net_price, tax_amount, base_price))
     }
 
+    /// Get the curve price of the next agent when `supply` agents are in circulation.
+    ///
+    /// # Example
+    /// ```
+    /// # use tubor_yield::state::master_agent::{MasterAgent, PricingCurve};
+    /// let master_agent = MasterAgent {
+    ///     price: 1_000_000,
+    ///     max_supply: 100,
+    ///     pricing_curve: PricingCurve::Linear as u8,
+    ///     curve_factor: 10_000, // price doubles at full supply
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(master_agent.get_curve_price(0).unwrap(), 1_000_000);
+    /// assert_eq!(master_agent.get_curve_price(50).unwrap(), 1_500_000);
+    /// ```
+    pub fn get_curve_price(&self, supply: u64) -> TYieldResult<u64> {
+        let curve = self.get_pricing_curve();
+        if curve == PricingCurve::Flat || self.max_supply == 0 || self.curve_factor == 0 {
+            return Ok(self.price);
+        }
+
+        // k * s / max_supply
+        let exponent = U192::from(self.curve_factor)
+            .safe_mul(U192::from(supply))?
+            .safe_mul(U192::from(CURVE_PRECISION))?
+            .safe_div(
+                U192::from(PERCENTAGE_PRECISION_U64).safe_mul(U192::from(self.max_supply))?,
+            )?;
+
+        let multiplier = match curve {
+            PricingCurve::Linear => U192::from(CURVE_PRECISION).safe_add(exponent)?,
+            PricingCurve::Exponential => {
+                // Taylor series of e^x, converges well within 64 terms for x <= MAX_CURVE_FACTOR
+                let mut term = U192::from(CURVE_PRECISION);
+                let mut sum = term;
+                for n in 1..64u64 {
+                    term = term
+                        .safe_mul(exponent)?
+                        .safe_div(U192::from(CURVE_PRECISION).safe_mul(U192::from(n))?)?;
+                    if term.is_zero() {
+                        break;
+                    }
+                    sum = sum.safe_add(term)?;
+                }
+                sum
+            }
+            PricingCurve::Flat => U192::from(CURVE_PRECISION),
+        };
+
+        U192::from(self.price)
+            .safe_mul(multiplier)?
+            .safe_div(U192::from(CURVE_PRECISION))?
+            .try_to_u64()
+    }
+
+    /// Calculate how a secondary (peer-to-peer) sale is split.
+    ///
+    /// The buyer pays the full ask `price`. The protocol sell tax and the authority's
+    /// royalty (`seller_fee_basis_points`) are deducted from the seller's proceeds.
+    ///
+    /// # Returns
+    /// * `Ok((seller_proceeds, tax_amount, royalty_amount))` - The price breakdown
+    /// * `Err(ErrorCode::MathError)` - If tax configuration is invalid or fees exceed the price
+    ///
+    /// # Example
+    /// ```
+    /// # use tubor_yield::state::master_agent::MasterAgent;
+    /// let mut master_agent = MasterAgent::default(); // 2.5% sell tax
+    /// master_agent.seller_fee_basis_points = 500; // 5% royalty
+    /// let (proceeds, tax, royalty) = master_agent.calculate_listing_fill(1_000_000).unwrap();
+    /// assert_eq!((proceeds, tax, royalty), (925_000, 25_000, 50_000));
+    /// ```
+    pub fn calculate_listing_fill(&self, price: u64) -> TYieldResult<(u64, u64, u64)> {
+        if self.tax_config.sell_tax_percentage > self.tax_config.max_tax_percentage {
+            return Err(ErrorCode::MathError);
+        }
+
+        let tax_amount = price
+            .safe_mul(self.tax_config.sell_tax_percentage)?
+            .safe_div(PERCENTAGE_PRECISION_U64)?;
+        let royalty_amount = price
+            .safe_mul(self.seller_fee_basis_points as u64)?
+            .safe_div(PERCENTAGE_PRECISION_U64)?;
+        let seller_proceeds = price.safe_sub(tax_amount)?.safe_sub(royalty_amount)?;
+
+        Ok((seller_proceeds, tax_amount, royalty_amount))
+    }
+
     /// Calculate buy price for a specific amount of USDC
     /// Returns (tokens_received, tax_paid, base_amount)
     /// SECURITY: Now uses stored tax configuration
@@ -1123,16 +2005,89 @@ impl MasterAgent {
     /// SECURITY: Now uses stored tax configuration
     pub fn calculate_sell_price_with_slippage(
         &self,
-        slippage_bps: u64, // Slippage in basis points
-    ) -> TYieldResult<(u64, u64)> {
-        let (base_net_price, _, _) = self.calculate_sell_price_with_tax()?;
-
-        let slippage_amount = base_net_price
-            .safe_mul(slippage_bps)?
-            .safe_div(PERCENTAGE_PRECISION_U64)?;
-        let min_price = base_net_price.safe_sub(slippage_amount)?;
+        slippage_bps: u64, // Slippage in basis points
+    ) -> TYieldResult<(u64, u64)> {
+        let (base_net_price, _, _) = self.calculate_sell_price_with_tax()?;
+
+        let slippage_amount = base_net_price
+            .safe_mul(slippage_bps)?
+            .safe_div(PERCENTAGE_PRECISION_U64)?;
+        let min_price = base_net_price.safe_sub(slippage_amount)?;
+
+        Ok((base_net_price, min_price))
+    }
+
+    /// Calculate the buy price and enforce the buyer's price limit.
+    ///
+    /// `max_total_price` is usually quoted with `calculate_buy_price_with_slippage`, so an
+    /// `update_price` landing before the buy cannot silently move the fill.
+    ///
+    /// # Returns
+    /// * `Ok((total_price, tax_amount, base_price))` - The price breakdown
+    /// * `Err(ErrorCode::MaxPriceSlippage)` - If the total price exceeds `max_total_price`
+    pub fn calculate_buy_price_with_limit(
+        &self,
+        max_total_price: u64,
+    ) -> TYieldResult<(u64, u64, u64)> {
+        self.calculate_buy_quote_with_limit(1, max_total_price)
+    }
+
+    /// Calculate the cost of buying `count` agents and enforce the buyer's price limit.
+    ///
+    /// # Returns
+    /// * `Ok((total_price, tax_amount, base_price))` - The price breakdown for all agents
+    /// * `Err(ErrorCode::MaxPriceSlippage)` - If the total price exceeds `max_total_price`
+    pub fn calculate_buy_quote_with_limit(
+        &self,
+        count: u64,
+        max_total_price: u64,
+    ) -> TYieldResult<(u64, u64, u64)> {
+        let price = self.calculate_buy_quote(count)?;
+        if price.0 > max_total_price {
+            msg!(
+                "Buy price {} exceeds maximum total price {}",
+                price.0,
+                max_total_price
+            );
+            return Err(ErrorCode::MaxPriceSlippage);
+        }
+        Ok(price)
+    }
+
+    /// Calculate the sell price and enforce the seller's proceeds limit.
+    ///
+    /// `min_net_proceeds` is usually quoted with `calculate_sell_price_with_slippage`.
+    ///
+    /// # Returns
+    /// * `Ok((net_price, tax_amount, base_price))` - The price breakdown
+    /// * `Err(ErrorCode::MaxPriceSlippage)` - If the net proceeds are below `min_net_proceeds`
+    pub fn calculate_sell_price_with_limit(
+        &self,
+        min_net_proceeds: u64,
+    ) -> TYieldResult<(u64, u64, u64)> {
+        self.calculate_sell_quote_with_limit(1, min_net_proceeds)
+    }
 
-        Ok((base_net_price, min_price))
+    /// Calculate the proceeds of selling `count` agents and enforce the seller's proceeds limit.
+    ///
+    /// # Returns
+    /// * `Ok((net_price, tax_amount, base_price))` - The price breakdown for all agents
+    /// * `Err(ErrorCode::MaxPriceSlippage)` - If the net proceeds are below `min_net_proceeds`
+    pub fn calculate_sell_quote_with_limit(
+        &self,
+        count: u64,
+        min_net_proceeds: u64,
+    ) -> TYieldResult<(u64, u64, u64)> {
+        let price = self.calculate_sell_quote(count)?;
+        if price.0 < min_net_proceeds {
+            msg!(
+                "Sell proceeds {} are below minimum net proceeds {}",
+                price.0,
+                min_net_proceeds
+            );
+            return Err(ErrorCode::MaxPriceSlippage);
+        }
+        Ok(price)
     }
 
     /// Validate tax configuration
@@ -1331,7 +2286,10 @@ impl Default for MasterAgent {
         Self {
             authority: Pubkey::default(),
             mint: Pubkey::default(),
+            whitelist_root: [0; 32],
             trading_status: TradingStatus::WhiteList as u8,
+            pricing_curve: PricingCurve::Flat as u8,
+            curve_factor: 0,
             price: 0,
             w_yield: 0,
             price_update_allowance: 0,
@@ -1341,12 +2299,22 @@ impl Default for MasterAgent {
             trade_count: 0,
             completed_trades: 0,
             total_pnl: 0,
+            performance: PerformanceLedger::default(),
+            nav: 0,
+            held_agents: 0,
+            yield_index: 0,
+            distribution_index: 0,
+            last_yield_index_update: 0,
+            presale_end: 0,
+            relist_cooldown: 0,
             auto_relist: false,
             last_updated: 0,
             created_at: 0,
             bump: 0,
+            seller_fee_basis_points: 0,
             tax_config: TaxConfig::default(),
-            _padding: [0; 1],
+            auction_live: false,
+            relist_policy: RelistPolicy::Relist as u8,
         }
     }
 }
@@ -1368,10 +2336,62 @@ pub enum TradingStatus {
     WhiteList = 0b00000001,
     /// Public mode - anyone can trade
     Public = 0b00000010,
+    /// Presale mode - whitelisted users buy through the presale until `presale_end`
+    Presale = 0b00000100,
+    /// Sunset mode - no new buys or trades, holders redeem at the buyback price
+    Sunset = 0b00001000,
+}
+
+/// What happens to an agent returned to the protocol by a sale, redemption or sunset buyback.
+///
+/// # Example
+/// ```
+/// # use tubor_yield::state::master_agent::RelistPolicy;
+/// let relist = RelistPolicy::Relist; // default
+/// let cooldown = RelistPolicy::Cooldown; // relisted after relist_cooldown
+/// let burn = RelistPolicy::Burn; // explicit opt-in
+/// let hold = RelistPolicy::Hold; // auto_relist disabled
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug, Eq, Default, AnchorDeserialize, AnchorSerialize)]
+pub enum RelistPolicy {
+    /// Listed again immediately at the current price
+    #[default]
+    Relist = 0,
+    /// Held unlisted for `relist_cooldown` seconds, then listed again
+    Cooldown = 1,
+    /// Burned, shrinking the supply
+    Burn = 2,
+    /// Held unlisted in protocol custody, outside of the unsold supply
+    Hold = 3,
+}
+
+/// Pricing curve used to quote agents from a master agent.
+///
+/// The curve position is the circulating supply (`held_agents / max_supply`), so the
+/// price rises as holders buy agents from the protocol and falls as they sell back.
+/// `MasterAgent.price` is the price of the first agent and `curve_factor` (k) sets the
+/// steepness.
+///
+/// # Example
+/// ```
+/// # use tubor_yield::state::master_agent::PricingCurve;
+/// let flat = PricingCurve::Flat; // price
+/// let linear = PricingCurve::Linear; // price * (1 + k * s)
+/// let exponential = PricingCurve::Exponential; // price * e^(k * s)
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug, Eq, Default, AnchorDeserialize, AnchorSerialize)]
+pub enum PricingCurve {
+    /// Fixed price set by the authority
+    #[default]
+    Flat = 0,
+    /// Price grows linearly with circulating supply
+    Linear = 1,
+    /// Price grows exponentially with circulating supply
+    Exponential = 2,
 }
 
 impl Size for MasterAgent {
-    const SIZE: usize = 192; // 8 (discriminator) + 184 (struct, including tax_config and alignment/padding) = 192 bytes
+    const SIZE: usize = 376; // 8 (discriminator) + 368 (struct, including tax_config, performance and alignment/padding) = 376 bytes
 }
 
 #[cfg(test)]
@@ -1397,6 +2417,9 @@ mod tests {
             current_time,
             bump: 1,
             tax_config: TaxConfig::default(),
+            seller_fee_basis_points: 500,
+            pricing_curve: PricingCurve::Flat,
+            curve_factor: 0,
         };
         master_agent.initialize(params).unwrap();
 
@@ -1411,6 +2434,42 @@ mod tests {
         println!("MasterAgent on-chain size: {} bytes", MasterAgent::SIZE);
     }
 
+    #[test]
+    fn test_master_agent_legacy_layout() {
+        let mut master_agent = create_test_master_agent();
+        // Appended fields are zero on a legacy account
+        master_agent.seller_fee_basis_points = 0;
+        master_agent.last_yield_index_update = 0;
+        master_agent.total_pnl = 42;
+        let mut data = Vec::new();
+        master_agent.try_serialize(&mut data).unwrap();
+
+        // Accounts created before the appended fields only hold the first 188 bytes
+        let mut legacy = data[..188].to_vec();
+        legacy.resize(MasterAgent::SIZE, 0);
+        let mut migrated = MasterAgent::try_deserialize(&mut &legacy[..]).unwrap();
+        assert_eq!(migrated, master_agent);
+
+        migrated
+            .migrate(60, 60_000_000, 500, 1_700_000_000)
+            .unwrap();
+        assert_eq!(migrated.held_agents, 60);
+        assert_eq!(migrated.nav, 60_000_000);
+        assert_eq!(migrated.seller_fee_basis_points, 500);
+        assert_eq!(migrated.last_yield_index_update, 1_700_000_000);
+        assert_eq!(migrated.get_relist_policy(), RelistPolicy::Hold);
+
+        // Cannot hold more agents than were minted, or charge more than 100% royalty
+        assert_eq!(
+            migrated.migrate(101, 0, 500, 1_700_000_000),
+            Err(ErrorCode::InvalidAccount)
+        );
+        assert_eq!(
+            migrated.migrate(60, 0, 10_001, 1_700_000_000),
+            Err(ErrorCode::MathError)
+        );
+    }
+
     #[test]
     fn test_master_agent_memory_layout() {
         // Test that MasterAgent struct can be created and serialized
@@ -1422,12 +2481,17 @@ mod tests {
         assert_eq!(master_agent.max_supply, 0);
         assert_eq!(master_agent.agent_count, 0);
         assert_eq!(master_agent.trade_count, 0);
+        assert_eq!(master_agent.performance, PerformanceLedger::default());
+        assert_eq!(master_agent.nav, 0);
+        assert_eq!(master_agent.held_agents, 0);
+        assert_eq!(master_agent.yield_index, 0);
         assert_eq!(master_agent.last_updated, 0);
         assert_eq!(master_agent.created_at, 0);
+        assert_eq!(master_agent.last_yield_index_update, 0);
         assert_eq!(master_agent.trading_status, TradingStatus::WhiteList as u8);
         assert_eq!(master_agent.auto_relist, false);
         assert_eq!(master_agent.bump, 0);
-        assert_eq!(master_agent._padding, [0; 1]);
+        assert!(!master_agent.auction_live);
     }
 
     #[test]
@@ -1448,6 +2512,9 @@ mod tests {
             current_time,
             bump: 2,
             tax_config: TaxConfig::default(),
+            seller_fee_basis_points: 500,
+            pricing_curve: PricingCurve::Flat,
+            curve_factor: 0,
         };
         let result = master_agent.initialize(params);
 
@@ -1554,6 +2621,385 @@ mod tests {
         assert_eq!(result.unwrap_err(), ErrorCode::PriceUpdateTooSoon);
     }
 
+    #[test]
+    fn test_yield_index_accumulates() {
+        let mut master_agent = create_test_master_agent();
+        let start = master_agent.last_yield_index_update;
+        let one_year = YEARLY_SECONDS_PRECISION as i64;
+
+        assert_eq!(master_agent.get_yield_index(start).unwrap(), 0);
+
+        // One year at 5% on a price of 1_000_000 is 50_000 per agent
+        let index = master_agent.update_yield_index(start + one_year).unwrap();
+        assert_eq!(index, 50_000 * YIELD_INDEX_PRECISION);
+        assert_eq!(master_agent.last_yield_index_update, start + one_year);
+
+        // Checkpointing twice at the same time does not double count
+        let index = master_agent.update_yield_index(start + one_year).unwrap();
+        assert_eq!(index, 50_000 * YIELD_INDEX_PRECISION);
+    }
+
+    #[test]
+    fn test_update_yield_checkpoints_index() {
+        let mut master_agent = create_test_master_agent();
+        let start = master_agent.last_yield_index_update;
+        let one_year = YEARLY_SECONDS_PRECISION as i64;
+        let authority = master_agent.authority;
+
+        // The old 5% rate applies to the first year
+        master_agent
+            .update_yield(525, start + one_year, &authority)
+            .unwrap();
+        assert_eq!(master_agent.yield_index, 50_000 * YIELD_INDEX_PRECISION);
+        assert_eq!(master_agent.last_yield_index_update, start + one_year);
+
+        // The new 5.25% rate only applies from the update onwards
+        let index = master_agent.get_yield_index(start + 2 * one_year).unwrap();
+        assert_eq!(index, (50_000 + 52_500) * YIELD_INDEX_PRECISION);
+    }
+
+    #[test]
+    fn test_flat_curve_quotes() {
+        let master_agent = create_test_master_agent();
+        assert_eq!(master_agent.get_curve_price(50).unwrap(), 1_000_000);
+        assert_eq!(
+            master_agent.calculate_buy_quote(3).unwrap(),
+            (3_075_000, 75_000, 3_000_000)
+        );
+        // Flat sells don't depend on circulating supply
+        assert_eq!(
+            master_agent.calculate_sell_quote(2).unwrap(),
+            (1_950_000, 50_000, 2_000_000)
+        );
+    }
+
+    #[test]
+    fn test_linear_curve_quotes() {
+        let mut master_agent = MasterAgent {
+            price: 1_000_000,
+            max_supply: 100,
+            pricing_curve: PricingCurve::Linear as u8,
+            curve_factor: 10_000, // 1x -> 2x at full supply
+            ..Default::default()
+        };
+        assert_eq!(master_agent.get_curve_price(0).unwrap(), 1_000_000);
+        assert_eq!(master_agent.get_curve_price(1).unwrap(), 1_010_000);
+        assert_eq!(master_agent.get_curve_price(100).unwrap(), 2_000_000);
+
+        let (_, _, buy_base) = master_agent.calculate_buy_quote(2).unwrap();
+        assert_eq!(buy_base, 2_010_000);
+        assert_eq!(
+            master_agent.calculate_sell_quote(1),
+            Err(ErrorCode::InsufficientFunds)
+        );
+
+        // Selling back walks down the same steps
+        master_agent.held_agents = 2;
+        let (_, _, sell_base) = master_agent.calculate_sell_quote(2).unwrap();
+        assert_eq!(sell_base, buy_base);
+        let (_, _, next_buy) = master_agent.calculate_buy_price_with_tax().unwrap();
+        assert_eq!(next_buy, 1_020_000);
+    }
+
+    #[test]
+    fn test_exponential_curve_quotes() {
+        let master_agent = MasterAgent {
+            price: 1_000_000,
+            max_supply: 100,
+            pricing_curve: PricingCurve::Exponential as u8,
+            curve_factor: 10_000, // e^1 at full supply
+            ..Default::default()
+        };
+        assert_eq!(master_agent.get_curve_price(0).unwrap(), 1_000_000);
+        let full = master_agent.get_curve_price(100).unwrap();
+        assert!((2_718_280..=2_718_282).contains(&full));
+        // Steeper than linear at the top, flatter at the bottom
+        let half = master_agent.get_curve_price(50).unwrap();
+        assert!(half > 1_000_000 && half < 1_859_141);
+
+        let max_factor = MasterAgent {
+            curve_factor: MAX_CURVE_FACTOR,
+            ..master_agent
+        };
+        assert!(max_factor.get_curve_price(100).unwrap() > 22_000_000_000);
+    }
+
+    #[test]
+    fn test_initialize_rejects_invalid_curve_factor() {
+        let mut master_agent = MasterAgent::default();
+        let params = MasterAgentInitParams {
+            authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            price: 1000000,
+            w_yield: 500,
+            trading_status: TradingStatus::WhiteList,
+            max_supply: 100,
+            auto_relist: false,
+            current_time: 1640995200,
+            bump: 1,
+            tax_config: TaxConfig::default(),
+            seller_fee_basis_points: 0,
+            pricing_curve: PricingCurve::Exponential,
+            curve_factor: MAX_CURVE_FACTOR + 1,
+        };
+        assert_eq!(master_agent.initialize(params), Err(ErrorCode::MathError));
+    }
+
+    #[test]
+    fn test_calculate_listing_fill() {
+        let mut master_agent = MasterAgent {
+            seller_fee_basis_points: 1_000, // 10%
+            ..Default::default()
+        };
+        let (proceeds, tax, royalty) = master_agent.calculate_listing_fill(2_000).unwrap();
+        assert_eq!(tax, 50); // 2.5% default sell tax
+        assert_eq!(royalty, 200);
+        assert_eq!(proceeds, 1_750);
+
+        // No royalty configured
+        master_agent.seller_fee_basis_points = 0;
+        let (proceeds, _, royalty) = master_agent.calculate_listing_fill(2_000).unwrap();
+        assert_eq!((proceeds, royalty), (1_950, 0));
+
+        // Fees larger than the price
+        master_agent.seller_fee_basis_points = 10_000;
+        assert!(master_agent.calculate_listing_fill(2_000).is_err());
+    }
+
+    #[test]
+    fn test_record_agent_purchase_and_sale() {
+        let mut master_agent = create_test_master_agent();
+
+        master_agent.record_agent_purchase(1_000_000).unwrap();
+        master_agent.record_agent_purchase(1_000_000).unwrap();
+        assert_eq!(master_agent.held_agents, 2);
+        assert_eq!(master_agent.nav, 2_000_000);
+        assert_eq!(master_agent.get_nav_per_agent().unwrap(), 1_000_000);
+
+        let released = master_agent.record_agent_sale().unwrap();
+        assert_eq!(released, 1_000_000);
+        assert_eq!(master_agent.held_agents, 1);
+        assert_eq!(master_agent.nav, 1_000_000);
+    }
+
+    #[test]
+    fn test_batch_agent_purchase_and_sale() {
+        let mut master_agent = create_test_master_agent();
+        let current_time = 1640995260;
+
+        master_agent.add_agents(5, current_time).unwrap();
+        master_agent.remove_agents(3, current_time).unwrap();
+        assert_eq!(master_agent.agent_count, 2);
+        assert_eq!(
+            master_agent.remove_agents(3, current_time),
+            Err(ErrorCode::InsufficientFunds)
+        );
+
+        master_agent.record_agent_purchases(3, 3_000_000).unwrap();
+        assert_eq!(master_agent.held_agents, 3);
+        assert_eq!(master_agent.get_nav_per_agent().unwrap(), 1_000_000);
+
+        let released = master_agent.record_agent_sales(2).unwrap();
+        assert_eq!(released, 2_000_000);
+        assert_eq!(master_agent.held_agents, 1);
+        assert_eq!(master_agent.nav, 1_000_000);
+
+        // Quotes for a batch respect the buyer's limit on the whole batch
+        let (total_price, _, _) = master_agent.calculate_buy_quote(3).unwrap();
+        assert_eq!(
+            master_agent
+                .calculate_buy_quote_with_limit(3, total_price)
+                .unwrap()
+                .0,
+            total_price
+        );
+        assert_eq!(
+            master_agent.calculate_buy_quote_with_limit(3, total_price - 1),
+            Err(ErrorCode::MaxPriceSlippage)
+        );
+    }
+
+    #[test]
+    fn test_agent_redemption() {
+        let mut master_agent = create_test_master_agent();
+        let current_time = master_agent.last_yield_index_update;
+
+        master_agent.record_agent_purchases(4, 4_000_000).unwrap();
+        master_agent
+            .settle_realized_pnl(-400_000, 1000, current_time)
+            .unwrap();
+
+        // Realized losses reduce the redemption value
+        let payout = master_agent.record_agent_sale().unwrap();
+        assert_eq!(payout, 900_000);
+        assert_eq!(master_agent.held_agents, 3);
+        assert_eq!(master_agent.nav, 2_700_000);
+    }
+
+    #[test]
+    fn test_relist_policy() {
+        let mut master_agent = create_test_master_agent();
+        let current_time = 1640995260;
+
+        // Hold: auto_relist disabled keeps the agent unlisted, neither relisted nor burned
+        assert_eq!(master_agent.get_relist_policy(), RelistPolicy::Hold);
+        assert_eq!(
+            master_agent.take_back_agent(current_time).unwrap(),
+            RelistPolicy::Hold
+        );
+        assert_eq!(master_agent.max_supply, 100);
+        assert_eq!(master_agent.agent_count, 0);
+        assert_eq!(master_agent.get_relist_time(current_time).unwrap(), None);
+
+        // Relist (default): the agent rejoins the unsold supply
+        master_agent.set_auto_relist(true, current_time);
+        assert_eq!(
+            master_agent.take_back_agent(current_time).unwrap(),
+            RelistPolicy::Relist
+        );
+        assert_eq!(master_agent.agent_count, 1);
+        assert_eq!(
+            master_agent.get_relist_time(current_time).unwrap(),
+            Some(current_time)
+        );
+
+        // Cooldown
+        assert!(master_agent.set_relist_cooldown(-1, current_time).is_err());
+        master_agent
+            .set_relist_cooldown(3600, current_time)
+            .unwrap();
+        assert!(master_agent
+            .set_relist_policy(RelistPolicy::Hold, current_time)
+            .is_err());
+        master_agent
+            .set_relist_policy(RelistPolicy::Cooldown, current_time)
+            .unwrap();
+        assert_eq!(
+            master_agent.take_back_agent(current_time).unwrap(),
+            RelistPolicy::Cooldown
+        );
+        assert_eq!(master_agent.agent_count, 2);
+        assert_eq!(
+            master_agent.get_relist_time(current_time).unwrap(),
+            Some(current_time + 3600)
+        );
+
+        // Burn: explicit opt-in retires the agent from the supply cap
+        master_agent
+            .set_relist_policy(RelistPolicy::Burn, current_time)
+            .unwrap();
+        master_agent.set_auto_relist(false, current_time);
+        assert_eq!(
+            master_agent.take_back_agent(current_time).unwrap(),
+            RelistPolicy::Burn
+        );
+        assert_eq!(master_agent.max_supply, 99);
+        assert_eq!(master_agent.get_relist_time(current_time).unwrap(), None);
+
+        // The supply cap never drops below the unsold supply
+        master_agent.agent_count = 99;
+        master_agent.take_back_agent(current_time).unwrap();
+        assert_eq!(master_agent.max_supply, 99);
+    }
+
+    #[test]
+    fn test_settle_realized_pnl_profit() {
+        let mut master_agent = create_test_master_agent();
+        let current_time = master_agent.last_yield_index_update;
+        master_agent.record_agent_purchase(1_000_000).unwrap();
+        master_agent.record_agent_purchase(1_000_000).unwrap();
+
+        let settlement = master_agent
+            .settle_realized_pnl(10_000, 1000, current_time)
+            .unwrap();
+        assert_eq!(settlement.protocol_fee, 1_000);
+        assert_eq!(settlement.holder_yield, 9_000);
+        assert_eq!(settlement.nav_delta, 0);
+        assert_eq!(master_agent.performance.net_pnl, 10_000);
+        assert_eq!(master_agent.nav, 2_000_000);
+
+        // Each of the two holders is owed half of the holder yield
+        assert_eq!(
+            master_agent.distribution_index,
+            4_500 * YIELD_INDEX_PRECISION
+        );
+        assert_eq!(master_agent.yield_index, 0);
+    }
+
+    #[test]
+    fn test_settle_realized_pnl_without_holders() {
+        let mut master_agent = create_test_master_agent();
+        let current_time = master_agent.last_yield_index_update;
+
+        let settlement = master_agent
+            .settle_realized_pnl(10_000, 1000, current_time)
+            .unwrap();
+        assert_eq!(settlement.protocol_fee, 1_000);
+        assert_eq!(settlement.holder_yield, 0);
+        assert_eq!(settlement.nav_delta, 9_000);
+        assert_eq!(master_agent.nav, 9_000);
+        assert_eq!(master_agent.distribution_index, 0);
+    }
+
+    #[test]
+    fn test_settle_realized_pnl_loss() {
+        let mut master_agent = create_test_master_agent();
+        let current_time = master_agent.last_yield_index_update;
+        master_agent.record_agent_purchase(1_000_000).unwrap();
+
+        let settlement = master_agent
+            .settle_realized_pnl(-250_000, 1000, current_time)
+            .unwrap();
+        assert_eq!(settlement.protocol_fee, 0);
+        assert_eq!(settlement.holder_yield, 0);
+        assert_eq!(settlement.nav_delta, -250_000);
+        assert_eq!(master_agent.nav, 750_000);
+        assert_eq!(master_agent.performance.net_pnl, -250_000);
+
+        // Losses larger than the NAV floor it at zero
+        let settlement = master_agent
+            .settle_realized_pnl(-1_000_000, 1000, current_time)
+            .unwrap();
+        assert_eq!(settlement.nav_delta, -750_000);
+        assert_eq!(master_agent.nav, 0);
+        assert_eq!(master_agent.performance.net_pnl, -1_250_000);
+
+        // Invalid performance fee
+        let result = master_agent.settle_realized_pnl(1_000, 10_001, current_time);
+        assert_eq!(result.unwrap_err(), ErrorCode::MathError);
+    }
+
+    #[test]
+    fn test_performance_ledger() {
+        let mut ledger = PerformanceLedger::default();
+        for pnl in [5_000, -2_000, 0, 8_000, -7_000, -4_000, 1_000] {
+            ledger.record(pnl).unwrap();
+        }
+
+        assert_eq!(ledger.gross_profit, 14_000);
+        assert_eq!(ledger.gross_loss, 13_000);
+        assert_eq!(ledger.net_pnl, 1_000);
+        assert_eq!(ledger.win_count, 3);
+        assert_eq!(ledger.loss_count, 3);
+        assert_eq!(ledger.largest_win, 8_000);
+        assert_eq!(ledger.largest_loss, 7_000);
+        // Equity: 5k, 3k, 3k, 11k (peak), 4k, 0 (trough), 1k
+        assert_eq!(ledger.peak_equity, 11_000);
+        assert_eq!(ledger.max_drawdown, 11_000);
+        assert_eq!(ledger.get_current_drawdown().unwrap(), 10_000);
+        assert_eq!(ledger.get_win_rate().unwrap(), 5_000);
+        assert_eq!(ledger.get_profit_factor().unwrap(), 10_769);
+
+        // A losing strategy ends with negative net PnL and no profit factor without wins
+        let mut ledger = PerformanceLedger::default();
+        ledger.record(-3_000).unwrap();
+        assert_eq!(ledger.net_pnl, -3_000);
+        assert_eq!(ledger.peak_equity, 0);
+        assert_eq!(ledger.max_drawdown, 3_000);
+        assert_eq!(ledger.get_win_rate().unwrap(), 0);
+        assert_eq!(ledger.get_profit_factor().unwrap(), 0);
+    }
+
     #[test]
     fn test_update_max_supply() {
         let mut master_agent = create_test_master_agent();
@@ -1692,6 +3138,132 @@ mod tests {
         assert_eq!(master_agent.get_trading_status(), TradingStatus::Public);
     }
 
+    #[test]
+    fn test_sunset() {
+        let mut master_agent = create_test_master_agent();
+        let current_time = 1640995260;
+        let authority = master_agent.authority;
+
+        master_agent.record_agent_purchases(3, 3_000_000).unwrap();
+        master_agent.auction_live = true;
+        assert!(master_agent.start_sunset(current_time).is_err());
+        master_agent.auction_live = false;
+
+        assert_eq!(master_agent.start_sunset(current_time).unwrap(), 3_000_000);
+        assert!(master_agent.is_sunset());
+        assert_eq!(master_agent.nav, 0);
+        assert!(!master_agent.can_be_accessed_by_user(true));
+        assert_eq!(master_agent.get_trading_status_string(), "Sunset");
+
+        // A sunset is final
+        assert!(master_agent.start_sunset(current_time).is_err());
+        assert!(master_agent
+            .set_trading_status(TradingStatus::Public, &authority, current_time)
+            .is_err());
+        master_agent.toggle_trading_status(current_time);
+        assert!(master_agent.is_sunset());
+
+        // Bought back agents are held, never relisted
+        let max_supply = master_agent.max_supply;
+        let agent_count = master_agent.agent_count;
+        let policy = master_agent.get_relist_policy();
+        assert_ne!(policy, RelistPolicy::Burn);
+        assert_eq!(
+            master_agent.record_sunset_buyback(current_time).unwrap(),
+            policy
+        );
+        assert_eq!(master_agent.held_agents, 2);
+        assert_eq!(master_agent.agent_count, agent_count);
+        assert_eq!(master_agent.max_supply, max_supply);
+
+        // Or burned and retired from the supply under the burn policy
+        master_agent
+            .set_relist_policy(RelistPolicy::Burn, current_time)
+            .unwrap();
+        assert_eq!(
+            master_agent.record_sunset_buyback(current_time).unwrap(),
+            RelistPolicy::Burn
+        );
+        assert_eq!(master_agent.held_agents, 1);
+        assert_eq!(master_agent.agent_count, agent_count);
+        assert_eq!(
+            master_agent.max_supply,
+            max_supply.saturating_sub(1).max(agent_count)
+        );
+    }
+
+    #[test]
+    fn test_presale_window() {
+        let mut master_agent = create_test_master_agent();
+        let current_time = 1640995260;
+
+        assert!(master_agent
+            .start_presale(current_time, current_time)
+            .is_err());
+        master_agent
+            .start_presale(current_time + 100, current_time)
+            .unwrap();
+        assert_eq!(master_agent.get_trading_status(), TradingStatus::Presale);
+        assert!(master_agent.is_presale_active(current_time + 99));
+        assert!(!master_agent.is_whitelist_mode());
+        assert!(master_agent.can_be_accessed_by_user(true));
+        assert!(!master_agent.can_be_accessed_by_user(false));
+
+        // Still open
+        assert!(!master_agent.close_presale_if_ended(current_time + 99));
+        assert_eq!(master_agent.get_trading_status(), TradingStatus::Presale);
+
+        // Window closed: switches to public
+        assert!(!master_agent.is_presale_active(current_time + 100));
+        assert!(master_agent.close_presale_if_ended(current_time + 100));
+        assert!(master_agent.is_public_mode());
+        assert!(!master_agent.close_presale_if_ended(current_time + 200));
+    }
+
+    #[test]
+    fn test_whitelist_merkle_proof() {
+        fn node(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+            if a <= b {
+                hashv(&[&a, &b]).to_bytes()
+            } else {
+                hashv(&[&b, &a]).to_bytes()
+            }
+        }
+
+        let mut master_agent = create_test_master_agent();
+        let wallets: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = wallets
+            .iter()
+            .map(|w| hashv(&[w.as_ref()]).to_bytes())
+            .collect();
+        let left = node(leaves[0], leaves[1]);
+        let right = node(leaves[2], leaves[3]);
+        let root = node(left, right);
+
+        // No root: falls back to the user flag
+        assert!(!master_agent.has_whitelist_root());
+        assert!(!master_agent.verify_whitelist_proof(&wallets[0], &[]));
+        assert!(master_agent.is_wallet_whitelisted(&wallets[0], &[], true));
+        assert!(!master_agent.is_wallet_whitelisted(&wallets[0], &[], false));
+
+        master_agent.set_whitelist_root(root, 1640995300);
+        assert!(master_agent.has_whitelist_root());
+        assert!(master_agent.verify_whitelist_proof(&wallets[0], &[leaves[1], right]));
+        assert!(master_agent.verify_whitelist_proof(&wallets[3], &[leaves[2], left]));
+        // The user flag no longer grants access
+        assert!(!master_agent.is_wallet_whitelisted(&wallets[0], &[], true));
+        assert!(master_agent.is_wallet_whitelisted(&wallets[2], &[leaves[3], left], false));
+
+        // Wrong sibling, wrong wallet and oversized proofs are rejected
+        assert!(!master_agent.verify_whitelist_proof(&wallets[0], &[leaves[2], right]));
+        assert!(!master_agent.verify_whitelist_proof(&Pubkey::new_unique(), &[leaves[1], right]));
+        assert!(!master_agent
+            .verify_whitelist_proof(&wallets[0], &[[0; 32]; MAX_WHITELIST_PROOF_LEN + 1]));
+
+        master_agent.set_whitelist_root([0; 32], 1640995400);
+        assert!(!master_agent.has_whitelist_root());
+    }
+
     #[test]
     fn test_calculate_yield_amount() {
         let mut master_agent = create_test_master_agent();
@@ -1708,6 +3280,28 @@ mod tests {
         assert_eq!(yield_amount, expected_yield);
     }
 
+    #[test]
+    fn test_calculate_accrued_yield() {
+        let master_agent = create_test_master_agent();
+
+        // A full year at 1x boost matches the annual yield amount
+        let accrued = master_agent
+            .calculate_accrued_yield(PERCENTAGE_PRECISION_U64, YEARLY_SECONDS_PRECISION as i64)
+            .unwrap();
+        assert_eq!(accrued, master_agent.calculate_yield_amount().unwrap());
+
+        // Half a year at 2x boost is the same amount
+        let accrued = master_agent
+            .calculate_accrued_yield(20000, (YEARLY_SECONDS_PRECISION / 2) as i64)
+            .unwrap();
+        assert_eq!(accrued, 50000);
+
+        // Non-positive periods and zero boost accrue nothing
+        assert_eq!(master_agent.calculate_accrued_yield(10000, 0).unwrap(), 0);
+        assert_eq!(master_agent.calculate_accrued_yield(10000, -10).unwrap(), 0);
+        assert_eq!(master_agent.calculate_accrued_yield(0, 86400).unwrap(), 0);
+    }
+
     #[test]
     fn test_get_yield_rate_percentage() {
         let mut master_agent = create_test_master_agent();
@@ -2012,14 +3606,24 @@ mod tests {
         master_agent.add_agent(current_time).unwrap();
         master_agent.increment_trade_count(current_time).unwrap();
 
-        let metrics = master_agent.get_performance_metrics(current_time).unwrap();
-        assert_eq!(metrics.0, master_agent.get_total_value_locked()); // TVL
-        assert_eq!(metrics.1, master_agent.get_total_yield_generated().unwrap()); // Total yield
-        assert_eq!(metrics.2, master_agent.get_yield_efficiency().unwrap()); // Yield efficiency
+        let metrics = master_agent.get_performance(current_time).unwrap();
+        assert_eq!(
+            metrics.total_value_locked,
+            master_agent.get_total_value_locked()
+        );
+        assert_eq!(
+            metrics.total_yield,
+            master_agent.get_total_yield_generated().unwrap()
+        );
+        assert_eq!(
+            metrics.yield_efficiency,
+            master_agent.get_yield_efficiency().unwrap()
+        );
         assert_eq!(
-            metrics.3,
+            metrics.activity_score,
             master_agent.get_trading_activity_score(current_time)
-        ); // Activity score
+        );
+        assert_eq!(metrics.ledger, master_agent.performance);
     }
 
     #[test]
@@ -2145,6 +3749,8 @@ mod tests {
         // Test enum values
         assert_eq!(TradingStatus::WhiteList as u8, 0b00000001);
         assert_eq!(TradingStatus::Public as u8, 0b00000010);
+        assert_eq!(TradingStatus::Presale as u8, 0b00000100);
+        assert_eq!(TradingStatus::Sunset as u8, 0b00001000);
 
         // Test enum comparison
         assert_ne!(TradingStatus::WhiteList, TradingStatus::Public);
@@ -2247,6 +3853,40 @@ mod tests {
         assert_eq!(min_price, 965250); // Base - 1% slippage
     }
 
+    #[test]
+    fn test_calculate_price_with_limit() {
+        let mut master_agent = create_test_master_agent();
+        let (_, max_price) = master_agent.calculate_buy_price_with_slippage(100).unwrap();
+        let (_, min_price) = master_agent
+            .calculate_sell_price_with_slippage(100)
+            .unwrap();
+
+        assert_eq!(
+            master_agent
+                .calculate_buy_price_with_limit(max_price)
+                .unwrap(),
+            (1025000, 25000, 1000000)
+        );
+        assert_eq!(
+            master_agent
+                .calculate_sell_price_with_limit(min_price)
+                .unwrap(),
+            (975000, 25000, 1000000)
+        );
+
+        // A price update beyond the quoted slippage is rejected
+        master_agent.price = 1020000;
+        assert_eq!(
+            master_agent.calculate_buy_price_with_limit(max_price),
+            Err(ErrorCode::MaxPriceSlippage)
+        );
+        master_agent.price = 980000;
+        assert_eq!(
+            master_agent.calculate_sell_price_with_limit(min_price),
+            Err(ErrorCode::MaxPriceSlippage)
+        );
+    }
+
     #[test]
     fn test_validate_tax_config() {
         let mut master_agent = create_test_master_agent();
@@ -1,16 +1,28 @@
+pub mod agent_vault;
 pub mod agents;
+pub mod auction;
+pub mod listing;
 pub mod master_agent;
 pub mod multisig;
 pub mod oracle;
+pub mod presale;
+pub mod rental;
+pub mod sunset;
 pub mod t_yield;
 pub mod trade;
 pub mod traits;
 pub mod user;
 
+pub use agent_vault::*;
 pub use agents::*;
+pub use auction::*;
+pub use listing::*;
 pub use master_agent::*;
 pub use multisig::*;
 pub use oracle::*;
+pub use presale::*;
+pub use rental::*;
+pub use sunset::*;
 pub use t_yield::*;
 pub use traits::*;
 pub use user::*;
@@ -56,6 +56,24 @@ pub enum AdminInstruction {
     WithdrawFees,
     /// Open new trading position
     OpenTrade,
+    /// Revoke a referral code
+    RevokeReferralCode,
+    /// Start a Dutch auction for a master agent
+    StartAuction,
+    /// Configure a presale window for a master agent
+    ConfigurePresale,
+    /// Set the whitelist Merkle root of a master agent
+    SetWhitelistRoot,
+    /// Sunset a master agent and set its buyback price
+    StartSunset,
+    /// Move protocol fees into the keeper reward pool
+    FundKeeperRewards,
+    /// Set what happens to agents returned to a master agent
+    SetRelistPolicy,
+    /// Grow a master agent account to the current layout
+    MigrateMasterAgent,
+    /// Grow the protocol state account to the current layout
+    MigrateTYield,
 }
 
 /// Multisig account for protocol admin control
@@ -0,0 +1,290 @@
+use anchor_lang::prelude::*;
+
+use crate::error::{ErrorCode, TYieldResult};
+use crate::math::{SafeMath, PERCENTAGE_PRECISION_U64};
+use crate::state::Size;
+
+/// Presale window of a master agent.
+///
+/// Between `start_time` and `end_time` whitelisted users can buy agents at
+/// `whitelist_price`, up to `max_per_user` agents each. The master agent switches
+/// to public mode once the window closes.
+///
+/// # Fields
+///
+/// - `master_agent`: The master agent the presale belongs to
+/// - `start_time` / `end_time`: Presale window
+/// - `whitelist_price`: Base price of an agent during the presale
+/// - `max_per_user`: Maximum number of agents a user can buy during the presale
+/// - `total_sold`: Agents sold during the current window
+/// - `bump`: PDA bump seed for the presale account
+/// - `_padding`: Reserved space for future additions
+#[account]
+#[derive(Eq, PartialEq, Debug, Default)]
+pub struct PresaleConfig {
+    // 8-byte aligned fields (largest first)
+    pub master_agent: Pubkey, // 32 bytes
+    pub start_time: i64,      // 8 bytes
+    pub end_time: i64,        // 8 bytes
+    pub whitelist_price: u64, // 8 bytes
+    pub max_per_user: u64,    // 8 bytes
+    pub total_sold: u64,      // 8 bytes
+
+    // 1-byte aligned fields (smallest last)
+    pub bump: u8, // 1 byte
+
+    // Future-proofing padding
+    pub _padding: [u8; 7], // 7 bytes for future additions
+}
+
+/// Presale purchases of a single user.
+///
+/// # Fields
+///
+/// - `presale`: The presale the allocation belongs to
+/// - `user`: The buyer
+/// - `purchased`: Agents bought during the window ending at `presale_end`
+/// - `presale_end`: End of the window `purchased` was counted for
+/// - `bump`: PDA bump seed for the allocation account
+/// - `_padding`: Reserved space for future additions
+#[account]
+#[derive(Eq, PartialEq, Debug, Default)]
+pub struct PresaleAllocation {
+    pub presale: Pubkey,   // 32 bytes
+    pub user: Pubkey,      // 32 bytes
+    pub purchased: u64,    // 8 bytes
+    pub presale_end: i64,  // 8 bytes
+    pub bump: u8,          // 1 byte
+    pub _padding: [u8; 7], // 7 bytes for future additions
+}
+
+/// Event emitted when a presale window is configured.
+#[event]
+pub struct PresaleConfiguredEvent {
+    pub master_agent: Pubkey,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub whitelist_price: u64,
+    pub max_per_user: u64,
+}
+
+/// Event emitted for every agent bought during a presale.
+#[event]
+pub struct PresalePurchaseEvent {
+    pub master_agent: Pubkey,
+    pub agent: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64,
+    pub tax_amount: u64,
+    pub purchased: u64,
+    pub timestamp: i64,
+}
+
+impl PresaleConfig {
+    /// Configures a new presale window.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If the current window is still open or the new one has already ended
+    /// * `ErrorCode::MathError` - If the window, price or allocation is invalid
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tubor_yield::state::presale::PresaleConfig;
+    /// use anchor_lang::solana_program::pubkey::Pubkey;
+    ///
+    /// let mut presale = PresaleConfig::default();
+    /// presale
+    ///     .configure(Pubkey::new_unique(), 1000, 2000, 500_000, 3, 900, 255)
+    ///     .unwrap();
+    /// assert!(presale.is_active(1500));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure(
+        &mut self,
+        master_agent: Pubkey,
+        start_time: i64,
+        end_time: i64,
+        whitelist_price: u64,
+        max_per_user: u64,
+        current_time: i64,
+        bump: u8,
+    ) -> TYieldResult<()> {
+        if self.master_agent != Pubkey::default() && current_time < self.end_time {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        if end_time <= current_time {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        if start_time >= end_time || whitelist_price == 0 || max_per_user == 0 {
+            return Err(ErrorCode::MathError);
+        }
+
+        self.master_agent = master_agent;
+        self.start_time = start_time;
+        self.end_time = end_time;
+        self.whitelist_price = whitelist_price;
+        self.max_per_user = max_per_user;
+        self.total_sold = 0;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Checks if purchases are accepted at `current_time`.
+    pub fn is_active(&self, current_time: i64) -> bool {
+        current_time >= self.start_time && current_time < self.end_time
+    }
+
+    /// Calculates the presale price of one agent including the buy tax.
+    ///
+    /// # Returns
+    /// * `Ok((total_price, tax_amount, base_price))` - The price breakdown
+    pub fn calculate_price_with_tax(
+        &self,
+        buy_tax_percentage: u64,
+    ) -> TYieldResult<(u64, u64, u64)> {
+        if buy_tax_percentage > PERCENTAGE_PRECISION_U64 {
+            return Err(ErrorCode::MathError);
+        }
+        let tax_amount = self
+            .whitelist_price
+            .safe_mul(buy_tax_percentage)?
+            .safe_div(PERCENTAGE_PRECISION_U64)?;
+        Ok((
+            self.whitelist_price.safe_add(tax_amount)?,
+            tax_amount,
+            self.whitelist_price,
+        ))
+    }
+
+    /// Records a purchase against the user's allocation and returns its price breakdown.
+    ///
+    /// Allocations counted for an earlier window start again from zero.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If the presale is not active
+    /// * `ErrorCode::PresaleAllocationExceeded` - If the user has bought `max_per_user` agents
+    pub fn record_purchase(
+        &mut self,
+        allocation: &mut PresaleAllocation,
+        buy_tax_percentage: u64,
+        current_time: i64,
+    ) -> TYieldResult<(u64, u64, u64)> {
+        if !self.is_active(current_time) {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        if allocation.presale_end != self.end_time {
+            allocation.purchased = 0;
+            allocation.presale_end = self.end_time;
+        }
+        if allocation.purchased >= self.max_per_user {
+            return Err(ErrorCode::PresaleAllocationExceeded);
+        }
+
+        let price = self.calculate_price_with_tax(buy_tax_percentage)?;
+        allocation.purchased = allocation.purchased.safe_add(1)?;
+        self.total_sold = self.total_sold.safe_add(1)?;
+        Ok(price)
+    }
+}
+
+impl Size for PresaleConfig {
+    const SIZE: usize = 88; // 8 (discriminator) + 80 (struct, including padding) = 88 bytes
+}
+
+impl Size for PresaleAllocation {
+    const SIZE: usize = 96; // 8 (discriminator) + 88 (struct, including padding) = 96 bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_presale() -> PresaleConfig {
+        let mut presale = PresaleConfig::default();
+        presale
+            .configure(Pubkey::new_unique(), 1_000, 2_000, 500_000, 2, 900, 255)
+            .unwrap();
+        presale
+    }
+
+    #[test]
+    fn test_presale_sizes() {
+        assert_eq!(
+            8 + std::mem::size_of::<PresaleConfig>(),
+            PresaleConfig::SIZE
+        );
+        assert_eq!(
+            8 + std::mem::size_of::<PresaleAllocation>(),
+            PresaleAllocation::SIZE
+        );
+    }
+
+    #[test]
+    fn test_presale_configure_validation() {
+        let mut presale = PresaleConfig::default();
+        let master_agent = Pubkey::new_unique();
+        // Window already over
+        assert!(presale
+            .configure(master_agent, 0, 100, 500_000, 2, 100, 255)
+            .is_err());
+        // Start after end
+        assert!(presale
+            .configure(master_agent, 300, 200, 500_000, 2, 100, 255)
+            .is_err());
+        // No allocation
+        assert!(presale
+            .configure(master_agent, 100, 200, 500_000, 0, 100, 255)
+            .is_err());
+
+        let mut presale = create_test_presale();
+        // Cannot reconfigure an open window
+        assert_eq!(
+            presale.configure(master_agent, 1_500, 3_000, 500_000, 2, 1_500, 255),
+            Err(ErrorCode::CannotPerformAction)
+        );
+        assert!(presale
+            .configure(master_agent, 2_500, 3_000, 500_000, 2, 2_000, 255)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_presale_allocation_cap() {
+        let mut presale = create_test_presale();
+        let mut allocation = PresaleAllocation::default();
+
+        // Not started yet
+        assert!(presale.record_purchase(&mut allocation, 250, 999).is_err());
+
+        assert_eq!(
+            presale
+                .record_purchase(&mut allocation, 250, 1_000)
+                .unwrap(),
+            (512_500, 12_500, 500_000)
+        );
+        presale
+            .record_purchase(&mut allocation, 250, 1_500)
+            .unwrap();
+        assert_eq!(
+            presale.record_purchase(&mut allocation, 250, 1_600),
+            Err(ErrorCode::PresaleAllocationExceeded)
+        );
+        assert_eq!(allocation.purchased, 2);
+        assert_eq!(presale.total_sold, 2);
+
+        // Closed
+        let mut other = PresaleAllocation::default();
+        assert!(presale.record_purchase(&mut other, 250, 2_000).is_err());
+
+        // A new window starts a fresh allocation
+        presale
+            .configure(presale.master_agent, 2_500, 3_000, 500_000, 2, 2_000, 255)
+            .unwrap();
+        presale
+            .record_purchase(&mut allocation, 250, 2_500)
+            .unwrap();
+        assert_eq!(allocation.purchased, 1);
+    }
+}
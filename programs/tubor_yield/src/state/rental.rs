@@ -0,0 +1,355 @@
+use anchor_lang::prelude::*;
+
+use crate::error::{ErrorCode, TYieldResult};
+use crate::math::SafeMath;
+use crate::state::{Agent, MasterAgent, Size};
+
+/// Rental of an agent's yield stream.
+///
+/// The owner keeps the agent but, once the renter accepts and pays `price`, the yield
+/// the agent earns between `start_time` and `end_time` is credited to the renter. The
+/// agent cannot be transferred or sold while the rental is open.
+///
+/// # Fields
+///
+/// - `agent`: The rented agent
+/// - `owner`: The agent owner offering the rental
+/// - `renter`: The user receiving the yield during the term
+/// - `price`: Upfront payment from the renter to the owner
+/// - `start_time` / `end_time`: Rental term
+/// - `accepted_at`: Timestamp the renter accepted and paid (0 = offer pending)
+/// - `renter_yield`: Yield credited to the renter so far
+/// - `bump`: PDA bump seed for the rental account
+/// - `_padding`: Reserved space for future additions
+#[account]
+#[derive(Eq, PartialEq, Debug, Default)]
+pub struct AgentRental {
+    // 8-byte aligned fields (largest first)
+    pub agent: Pubkey,     // 32 bytes
+    pub owner: Pubkey,     // 32 bytes
+    pub renter: Pubkey,    // 32 bytes
+    pub price: u64,        // 8 bytes
+    pub start_time: i64,   // 8 bytes
+    pub end_time: i64,     // 8 bytes
+    pub accepted_at: i64,  // 8 bytes
+    pub renter_yield: u64, // 8 bytes
+
+    // 1-byte aligned fields (smallest last)
+    pub bump: u8, // 1 byte
+
+    // Future-proofing padding
+    pub _padding: [u8; 7], // 7 bytes for future additions
+}
+
+/// Event emitted when an owner offers an agent's yield for rent.
+#[event]
+pub struct RentalCreatedEvent {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub renter: Pubkey,
+    pub price: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+/// Event emitted when a renter accepts and pays for a rental.
+#[event]
+pub struct RentalAcceptedEvent {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub renter: Pubkey,
+    pub price: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+/// Event emitted when a rental is cancelled or closed after its term.
+#[event]
+pub struct RentalClosedEvent {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub renter: Pubkey,
+    /// Yield credited to the renter over the whole rental
+    pub renter_yield: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when yield earned during a rental is credited to the renter.
+#[event]
+pub struct RentalYieldEvent {
+    pub agent: Pubkey,
+    pub renter: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+impl AgentRental {
+    /// Offers the agent's yield between `start_time` and `end_time` to `renter`.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If the term has already ended or the owner rents to themselves
+    /// * `ErrorCode::MathError` - If the term is empty
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tubor_yield::state::rental::AgentRental;
+    /// use anchor_lang::solana_program::pubkey::Pubkey;
+    ///
+    /// let mut rental = AgentRental::default();
+    /// rental
+    ///     .initialize(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 500_000, 1000, 2000, 900, 255)
+    ///     .unwrap();
+    /// assert!(!rental.is_accepted());
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        &mut self,
+        agent: Pubkey,
+        owner: Pubkey,
+        renter: Pubkey,
+        price: u64,
+        start_time: i64,
+        end_time: i64,
+        current_time: i64,
+        bump: u8,
+    ) -> TYieldResult<()> {
+        if end_time <= current_time || renter == owner {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        if start_time >= end_time {
+            return Err(ErrorCode::MathError);
+        }
+
+        self.agent = agent;
+        self.owner = owner;
+        self.renter = renter;
+        self.price = price;
+        self.start_time = start_time;
+        self.end_time = end_time;
+        self.accepted_at = 0;
+        self.renter_yield = 0;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Checks if the renter has accepted the rental.
+    pub fn is_accepted(&self) -> bool {
+        self.accepted_at != 0
+    }
+
+    /// Checks if the rental term has ended.
+    pub fn is_expired(&self, current_time: i64) -> bool {
+        current_time >= self.end_time
+    }
+
+    /// Checks if `signer` can end the rental at `current_time`.
+    ///
+    /// Pending offers can be withdrawn by either side, an accepted rental can only be
+    /// given up early by the renter, and anyone can close a rental whose term has ended.
+    pub fn can_close(&self, signer: &Pubkey, current_time: i64) -> bool {
+        if self.is_expired(current_time) {
+            true
+        } else if self.is_accepted() {
+            *signer == self.renter
+        } else {
+            *signer == self.owner || *signer == self.renter
+        }
+    }
+
+    /// Accepts the rental. A term that has already started begins at `current_time`.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If the rental was already accepted or has expired
+    pub fn accept(&mut self, current_time: i64) -> TYieldResult<()> {
+        if self.is_accepted() || self.is_expired(current_time) {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        self.start_time = self.start_time.max(current_time);
+        self.accepted_at = current_time;
+        Ok(())
+    }
+
+    /// Checks if the renter holds the agent's yield rights at `current_time`.
+    ///
+    /// The renter holds them from the start of an accepted term until it ends; the owner
+    /// holds them otherwise.
+    pub fn renter_holds_yield(&self, current_time: i64) -> bool {
+        self.is_accepted() && current_time >= self.start_time && !self.is_expired(current_time)
+    }
+
+    /// Settles the agent's yield up to `current_time` and credits it to whoever holds the
+    /// yield rights now.
+    ///
+    /// Yield-index and distribution-index deltas are not pro-rated over time: distributions
+    /// are lump sums, so the whole delta goes to the current holder. The owner's yield is
+    /// settled when the rental is accepted and both sides can accrue up to the end of the term.
+    ///
+    /// # Returns
+    /// * `Ok((owner_amount, renter_amount))`
+    pub fn settle_yield(
+        &mut self,
+        agent: &mut Agent,
+        master_agent: &mut MasterAgent,
+        current_time: i64,
+    ) -> TYieldResult<(u64, u64)> {
+        let amount = agent.settle_yield(master_agent, current_time)?;
+        if self.renter_holds_yield(current_time) {
+            self.renter_yield = self.renter_yield.safe_add(amount)?;
+            Ok((0, amount))
+        } else {
+            Ok((amount, 0))
+        }
+    }
+}
+
+impl Size for AgentRental {
+    const SIZE: usize = 152; // 8 (discriminator) + 144 (struct, including padding) = 152 bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_rental() -> AgentRental {
+        let mut rental = AgentRental::default();
+        rental
+            .initialize(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                500_000,
+                1_000,
+                2_000,
+                900,
+                255,
+            )
+            .unwrap();
+        rental
+    }
+
+    #[test]
+    fn test_rental_size() {
+        assert_eq!(8 + std::mem::size_of::<AgentRental>(), AgentRental::SIZE);
+    }
+
+    #[test]
+    fn test_rental_initialize_and_accept() {
+        let mut rental = AgentRental::default();
+        let owner = Pubkey::new_unique();
+        assert!(rental
+            .initialize(
+                Pubkey::new_unique(),
+                owner,
+                owner,
+                0,
+                1_000,
+                2_000,
+                900,
+                255
+            )
+            .is_err());
+        assert!(rental
+            .initialize(
+                Pubkey::new_unique(),
+                owner,
+                Pubkey::new_unique(),
+                0,
+                1_000,
+                2_000,
+                2_000,
+                255
+            )
+            .is_err());
+
+        let mut rental = create_test_rental();
+        // A late acceptance starts the term at acceptance
+        rental.accept(1_500).unwrap();
+        assert_eq!(rental.start_time, 1_500);
+        assert!(rental.accept(1_600).is_err());
+
+        let mut expired = create_test_rental();
+        assert_eq!(expired.accept(2_000), Err(ErrorCode::CannotPerformAction));
+    }
+
+    #[test]
+    fn test_rental_can_close() {
+        let mut rental = create_test_rental();
+        let (owner, renter, other) = (rental.owner, rental.renter, Pubkey::new_unique());
+
+        // Pending offer
+        assert!(rental.can_close(&owner, 1_500));
+        assert!(rental.can_close(&renter, 1_500));
+        assert!(!rental.can_close(&other, 1_500));
+
+        // Accepted: only the renter can give it up early
+        rental.accept(1_000).unwrap();
+        assert!(!rental.can_close(&owner, 1_500));
+        assert!(rental.can_close(&renter, 1_500));
+
+        // Expired: anyone can close it
+        assert!(rental.can_close(&other, 2_000));
+    }
+
+    #[test]
+    fn test_rental_yield_rights() {
+        let mut rental = create_test_rental();
+
+        // Pending offers leave the yield rights with the owner
+        assert!(!rental.renter_holds_yield(1_500));
+
+        rental.accept(900).unwrap();
+        assert_eq!(rental.start_time, 1_000);
+        // Before, during and after the term
+        assert!(!rental.renter_holds_yield(999));
+        assert!(rental.renter_holds_yield(1_000));
+        assert!(rental.renter_holds_yield(1_999));
+        assert!(!rental.renter_holds_yield(2_000));
+    }
+
+    #[test]
+    fn test_rental_settle_yield_lump_distribution() {
+        let mut rental = create_test_rental();
+        let mut agent = Agent {
+            booster: 10_000,
+            ..Default::default()
+        };
+        let mut master_agent = MasterAgent {
+            held_agents: 1,
+            last_yield_index_update: 900,
+            ..Default::default()
+        };
+        agent.reset_yield_accrual(&mut master_agent, 900).unwrap();
+
+        // The owner's yield is settled on acceptance
+        master_agent.distribute_yield(1_000, 950).unwrap();
+        assert_eq!(
+            rental
+                .settle_yield(&mut agent, &mut master_agent, 950)
+                .unwrap(),
+            (1_000, 0)
+        );
+        rental.accept(950).unwrap();
+
+        // A distribution settled during the term goes entirely to the renter
+        master_agent.distribute_yield(3_000, 1_900).unwrap();
+        assert_eq!(
+            rental
+                .settle_yield(&mut agent, &mut master_agent, 1_900)
+                .unwrap(),
+            (0, 3_000)
+        );
+        assert_eq!(rental.renter_yield, 3_000);
+
+        // After the term the owner holds the yield rights again
+        master_agent.distribute_yield(500, 2_100).unwrap();
+        assert_eq!(
+            rental
+                .settle_yield(&mut agent, &mut master_agent, 2_100)
+                .unwrap(),
+            (500, 0)
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use anchor_lang::prelude::*;
+
+use crate::error::{ErrorCode, TYieldResult};
+use crate::math::SafeMath;
+use crate::state::Size;
+
+/// Wind-down of a retired master agent.
+///
+/// Once a master agent is sunset every holder can redeem their agents at
+/// `buyback_price` until `deadline`. Funds reserved for agents that were not
+/// redeemed in time are swept to the treasury afterwards.
+///
+/// # Fields
+///
+/// - `master_agent`: The master agent being wound down
+/// - `buyback_price`: Price paid for every redeemed agent
+/// - `reserved`: Funds set aside for the buyback (`buyback_price` x holders' agents)
+/// - `claimed`: Funds paid out to holders so far
+/// - `outstanding`: Agents that have not been redeemed yet
+/// - `started_at`: Timestamp the sunset started
+/// - `deadline`: Last moment agents can be redeemed
+/// - `is_swept`: Whether the unclaimed funds were swept to the treasury
+/// - `bump`: PDA bump seed for the sunset account
+/// - `_padding`: Reserved space for future additions
+#[account]
+#[derive(Eq, PartialEq, Debug, Default)]
+pub struct AgentSunset {
+    // 8-byte aligned fields (largest first)
+    pub master_agent: Pubkey, // 32 bytes
+    pub buyback_price: u64,   // 8 bytes
+    pub reserved: u64,        // 8 bytes
+    pub claimed: u64,         // 8 bytes
+    pub outstanding: u64,     // 8 bytes
+    pub started_at: i64,      // 8 bytes
+    pub deadline: i64,        // 8 bytes
+
+    // 1-byte aligned fields (smallest last)
+    pub is_swept: bool, // 1 byte
+    pub bump: u8,       // 1 byte
+
+    // Future-proofing padding
+    pub _padding: [u8; 6], // 6 bytes for future additions
+}
+
+/// Event emitted when a master agent is sunset.
+#[event]
+pub struct SunsetStartedEvent {
+    pub master_agent: Pubkey,
+    pub buyback_price: u64,
+    pub outstanding: u64,
+    pub reserved: u64,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an agent is bought back at the buyback price.
+#[event]
+pub struct SunsetBuybackEvent {
+    pub master_agent: Pubkey,
+    pub agent: Pubkey,
+    /// Recorded owner paid the buyback price (a wallet or the vault of a fractionalized agent)
+    pub owner: Pubkey,
+    /// Caller that cranked the buyback
+    pub caller: Pubkey,
+    pub price: u64,
+    pub outstanding: u64,
+    /// `RelistPolicy` applied to the agent
+    pub relist_policy: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when unclaimed buyback funds are swept to the treasury.
+#[event]
+pub struct SunsetSweptEvent {
+    pub master_agent: Pubkey,
+    pub treasury: Pubkey,
+    pub amount: u64,
+    pub unredeemed_agents: u64,
+    pub timestamp: i64,
+}
+
+impl AgentSunset {
+    /// Starts the buyback window.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If the deadline has already passed
+    /// * `ErrorCode::MathError` - If the buyback price is zero
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tubor_yield::state::sunset::AgentSunset;
+    /// use anchor_lang::solana_program::pubkey::Pubkey;
+    ///
+    /// let mut sunset = AgentSunset::default();
+    /// sunset
+    ///     .start(Pubkey::new_unique(), 900_000, 10, 1000, 2000, 255)
+    ///     .unwrap();
+    /// assert_eq!(sunset.reserved, 9_000_000);
+    /// assert!(sunset.is_open(1500));
+    /// ```
+    pub fn start(
+        &mut self,
+        master_agent: Pubkey,
+        buyback_price: u64,
+        outstanding: u64,
+        current_time: i64,
+        deadline: i64,
+        bump: u8,
+    ) -> TYieldResult<()> {
+        if deadline <= current_time {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        if buyback_price == 0 {
+            return Err(ErrorCode::MathError);
+        }
+
+        self.master_agent = master_agent;
+        self.buyback_price = buyback_price;
+        self.reserved = buyback_price.safe_mul(outstanding)?;
+        self.claimed = 0;
+        self.outstanding = outstanding;
+        self.started_at = current_time;
+        self.deadline = deadline;
+        self.is_swept = false;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Checks if agents can be redeemed at `current_time`.
+    pub fn is_open(&self, current_time: i64) -> bool {
+        !self.is_swept && current_time < self.deadline
+    }
+
+    /// Records the redemption of one agent and returns the price owed to its holder.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If the buyback window has closed
+    pub fn record_buyback(&mut self, current_time: i64) -> TYieldResult<u64> {
+        if !self.is_open(current_time) {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        self.outstanding = self.outstanding.safe_sub(1)?;
+        self.claimed = self.claimed.safe_add(self.buyback_price)?;
+        Ok(self.buyback_price)
+    }
+
+    /// Closes the buyback and returns the unclaimed funds.
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CannotPerformAction` - If the deadline has not passed or the funds were already swept
+    pub fn sweep(&mut self, current_time: i64) -> TYieldResult<u64> {
+        if self.is_swept || current_time < self.deadline {
+            return Err(ErrorCode::CannotPerformAction);
+        }
+        self.is_swept = true;
+        self.reserved.safe_sub(self.claimed)
+    }
+}
+
+impl Size for AgentSunset {
+    const SIZE: usize = 96; // 8 (discriminator) + 88 (struct, including padding) = 96 bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sunset_size() {
+        assert_eq!(8 + std::mem::size_of::<AgentSunset>(), AgentSunset::SIZE);
+    }
+
+    #[test]
+    fn test_sunset_buyback_and_sweep() {
+        let mut sunset = AgentSunset::default();
+        let master_agent = Pubkey::new_unique();
+
+        assert!(sunset
+            .start(master_agent, 900_000, 3, 1_000, 1_000, 255)
+            .is_err());
+        assert!(sunset.start(master_agent, 0, 3, 1_000, 2_000, 255).is_err());
+        sunset
+            .start(master_agent, 900_000, 3, 1_000, 2_000, 255)
+            .unwrap();
+        assert_eq!(sunset.reserved, 2_700_000);
+
+        assert_eq!(sunset.record_buyback(1_500).unwrap(), 900_000);
+        assert_eq!(sunset.record_buyback(1_999).unwrap(), 900_000);
+        assert_eq!(sunset.outstanding, 1);
+        assert_eq!(sunset.claimed, 1_800_000);
+
+        // Cannot sweep before the deadline
+        assert_eq!(sunset.sweep(1_999), Err(ErrorCode::CannotPerformAction));
+        // Cannot redeem after the deadline
+        assert_eq!(
+            sunset.record_buyback(2_000),
+            Err(ErrorCode::CannotPerformAction)
+        );
+
+        assert_eq!(sunset.sweep(2_000).unwrap(), 900_000);
+        assert!(sunset.is_swept);
+        assert_eq!(sunset.sweep(3_000), Err(ErrorCode::CannotPerformAction));
+    }
+}
@@ -32,7 +32,7 @@
 use crate::program::Tuboryield;
 
 use mpl_token_metadata::{
-    instructions::{CreateV1CpiBuilder, MintV1CpiBuilder, TransferCpiBuilder},
+    instructions::{BurnV1CpiBuilder, CreateV1CpiBuilder, MintV1CpiBuilder, TransferCpiBuilder},
     types::{
         Collection, Creator, PrintSupply, TokenStandard as MetaplexTokenStandard, TransferArgs,
     },
@@ -42,8 +42,14 @@ use {
     crate::{
         error::{ErrorCode, TYieldResult},
         instructions::{MintAgent, MintAgentParams, MintMasterAgent, MintMasterAgentParams},
-        math::SafeMath,
-        state::{OracleParams, Size},
+        math::{
+            SafeMath, KEEPER_ACCRUAL_REWARD_SHARE, KEEPER_EPOCH_DURATION, MAX_KEEPER_EPOCH_CAP,
+            MAX_KEEPER_REWARD, PERCENTAGE_PRECISION_U64,
+        },
+        state::{
+            OracleParams, ReferralLink, ReferralRegistry, Size, User, UserStatus,
+            MAX_REFERRAL_DEPTH,
+        },
         try_from,
     },
     anchor_lang::prelude::*,
@@ -128,8 +134,12 @@ pub struct ParameterBounds {
 /// # Fields
 /// - `oracle_param`: Oracle configuration and price feed parameters.
 /// - `y_mint`: The protocol's yield token mint address.
-/// - `buy_tax`, `sell_tax`, `max_tax_percentage`, `ref_earn_percentage`, `max_agent_price_new`: Tax and referral parameters.
+/// - `treasury`: Owner of the token account receiving withdrawn protocol fees.
+/// - `buy_tax`, `sell_tax`, `max_tax_percentage`, `ref_earn_percentage`, `ref_tier_percentages`, `max_agent_price_new`: Tax and referral parameters.
+/// - `performance_fee`: Protocol cut of realized trade profits.
 /// - `protocol_current_holding`, `protocol_total_fees`, `protocol_total_earnings`, `protocol_total_balance_usd`: Protocol financials.
+/// - `protocol_fees_withdrawn`, `protocol_yield_owed`, `protocol_referral_owed`: Fees already sent to the treasury and amounts still owed to users and referrers.
+/// - `keeper_reward`, `keeper_epoch_cap`, `keeper_reward_pool`, `keeper_rewards_funded`, `keeper_epoch_paid`, `keeper_epoch_start`: Keeper rewards for settlement and yield cranks, funded from protocol fees.
 /// - `inception_time`: Protocol start timestamp.
 /// - `permissions`: Fine-grained permissions for protocol actions.
 /// - `paused`: Emergency pause flag.
@@ -186,6 +196,62 @@ pub struct TYield {
 
     // Padding for future-proofing and alignment
     pub _padding: [u8; 3], // 3 bytes to align to 8-byte boundary
+
+    // Fields below were appended after the initial layout and are
+    // populated for an existing account by `migrate_t_yield`
+    /// Owner of the token account that receives withdrawn protocol fees
+    pub treasury: Pubkey,
+
+    /// Referral share for levels 2..=MAX_REFERRAL_DEPTH
+    /// PRECISION PERCENTAGE_PRECISION
+    pub ref_tier_percentages: [u64; MAX_REFERRAL_DEPTH - 1],
+
+    /// PRECISION PERCENTAGE_PRECISION
+    pub performance_fee: u64,
+
+    /// PRECISION QUOTE_PRECISION
+    pub protocol_fees_withdrawn: u64,
+
+    /// Yield credited to users but not yet withdrawn
+    /// PRECISION QUOTE_PRECISION
+    pub protocol_yield_owed: u64,
+
+    /// Referral earnings credited to referrers but not yet claimed
+    /// PRECISION QUOTE_PRECISION
+    pub protocol_referral_owed: u64,
+
+    /// Reward paid per state-changing keeper crank
+    /// PRECISION QUOTE_PRECISION
+    pub keeper_reward: u64,
+
+    /// Maximum keeper rewards paid per epoch (KEEPER_EPOCH_DURATION)
+    /// PRECISION QUOTE_PRECISION
+    pub keeper_epoch_cap: u64,
+
+    /// Protocol fees set aside for keeper rewards and not yet paid
+    /// PRECISION QUOTE_PRECISION
+    pub keeper_reward_pool: u64,
+
+    /// Protocol fees moved into the keeper reward pool over time
+    /// PRECISION QUOTE_PRECISION
+    pub keeper_rewards_funded: u64,
+
+    /// Keeper rewards paid in the current epoch
+    /// PRECISION QUOTE_PRECISION
+    pub keeper_epoch_paid: u64,
+
+    /// Start of the current keeper reward epoch
+    pub keeper_epoch_start: i64,
+}
+
+/// Emitted when an account written with an older layout is grown to the current one
+#[event]
+pub struct AccountMigratedEvent {
+    pub account: Pubkey,
+    pub payer: Pubkey,
+    pub old_size: u64,
+    pub new_size: u64,
+    pub timestamp: i64,
 }
 
 #[event]
@@ -197,6 +263,40 @@ pub struct InitProtocolEvent {
 #[event]
 pub struct UpdateProtocolEvent {}
 
+#[event]
+pub struct FundKeeperRewardsEvent {
+    pub amount: u64,
+    pub keeper_reward_pool: u64,
+    pub timestamp: i64,
+}
+
+/// Crank a keeper is rewarded for.
+#[derive(Clone, Copy, PartialEq, Debug, Eq, AnchorDeserialize, AnchorSerialize)]
+pub enum KeeperAction {
+    /// Trades closed at take profit or stop loss
+    TradeSettlement = 0,
+    /// Agent yield accrued to its holders
+    YieldAccrual = 1,
+}
+
+#[event]
+pub struct KeeperRewardEvent {
+    pub keeper: Pubkey,
+    pub action: KeeperAction,
+    /// Number of state changes rewarded
+    pub actions: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawFeesEvent {
+    pub treasury: Pubkey,
+    pub amount: u64,
+    pub total_withdrawn: u64,
+    pub timestamp: i64,
+}
+
 impl TYield {
     /// Validate that buy and sell tax parameters are within allowed bounds.
     ///
@@ -272,6 +372,329 @@ impl TYield {
         Ok(())
     }
 
+    /// Returns the amount of accrued protocol fees that can currently be withdrawn.
+    ///
+    /// Fees are only withdrawable while the vault keeps enough tokens to cover
+    /// `protocol_current_holding`, the yield owed to users, unclaimed referral earnings
+    /// and the keeper reward pool. Fees moved into the keeper reward pool are no longer accrued.
+    ///
+    /// # Arguments
+    /// * `vault_balance` - Current balance of the protocol token account
+    ///
+    /// # Returns
+    /// * `Ok(u64)` - The withdrawable amount
+    pub fn get_withdrawable_fees(&self, vault_balance: u64) -> TYieldResult<u64> {
+        let accrued = self
+            .protocol_total_fees
+            .saturating_sub(self.protocol_fees_withdrawn)
+            .saturating_sub(self.keeper_rewards_funded);
+        let reserved = self
+            .protocol_current_holding
+            .safe_add(self.protocol_yield_owed)?
+            .safe_add(self.protocol_referral_owed)?
+            .safe_add(self.keeper_reward_pool)?;
+        Ok(accrued.min(vault_balance.saturating_sub(reserved)))
+    }
+
+    /// Validates per-level referral percentages.
+    ///
+    /// The combined share of all levels cannot exceed the collected tax.
+    ///
+    /// # Arguments
+    /// * `ref_earn_percentage` - Direct referrer share (basis points)
+    /// * `ref_tier_percentages` - Shares for levels 2..=MAX_REFERRAL_DEPTH (basis points)
+    pub fn validate_referral_percentages(
+        ref_earn_percentage: u64,
+        ref_tier_percentages: &[u64; MAX_REFERRAL_DEPTH - 1],
+    ) -> TYieldResult<()> {
+        let mut total = ref_earn_percentage;
+        for percentage in ref_tier_percentages.iter() {
+            total = total.safe_add(*percentage)?;
+        }
+        if total > PERCENTAGE_PRECISION_U64 {
+            msg!("Referral percentages {} exceed 100% of the tax", total);
+            return Err(ErrorCode::MathError);
+        }
+        Ok(())
+    }
+
+    /// Validates the keeper reward settings.
+    ///
+    /// # Arguments
+    /// * `keeper_reward` - Reward per state-changing crank, at most `MAX_KEEPER_REWARD`
+    /// * `keeper_epoch_cap` - Rewards paid per epoch, at most `MAX_KEEPER_EPOCH_CAP`
+    pub fn validate_keeper_parameters(
+        keeper_reward: u64,
+        keeper_epoch_cap: u64,
+    ) -> TYieldResult<()> {
+        if keeper_reward > MAX_KEEPER_REWARD || keeper_epoch_cap > MAX_KEEPER_EPOCH_CAP {
+            msg!("Keeper reward or epoch cap exceeds the maximum");
+            return Err(ErrorCode::MathError);
+        }
+        if keeper_reward > keeper_epoch_cap {
+            msg!("Keeper reward cannot exceed the epoch cap");
+            return Err(ErrorCode::MathError);
+        }
+        Ok(())
+    }
+
+    /// Returns the referral percentage for a level (0 = direct referrer).
+    pub fn get_referral_percentage(&self, level: usize) -> u64 {
+        match level {
+            0 => self.ref_earn_percentage,
+            _ => self
+                .ref_tier_percentages
+                .get(level - 1)
+                .copied()
+                .unwrap_or(0),
+        }
+    }
+
+    /// Calculates a referrer's share of a collected tax.
+    ///
+    /// # Arguments
+    /// * `tax` - Tax collected on a buy or sell
+    /// * `level` - Referral level (0 = direct referrer)
+    ///
+    /// # Returns
+    /// * `Ok(u64)` - The level's percentage of the tax
+    pub fn calculate_referral_fee(&self, tax: u64, level: usize) -> TYieldResult<u64> {
+        let percentage = self.get_referral_percentage(level);
+        if percentage > PERCENTAGE_PRECISION_U64 {
+            return Err(ErrorCode::MathError);
+        }
+        tax.safe_mul(percentage)?.safe_div(PERCENTAGE_PRECISION_U64)
+    }
+
+    /// Credits every level of a paying user's referral chain its share of the tax and
+    /// records the total as referral earnings owed by the protocol.
+    ///
+    /// When the user has a referrer, the direct referrer's registry and the user's referral
+    /// link are both required, so no level can be skipped by leaving accounts out.
+    ///
+    /// # Arguments
+    /// * `user` - The paying user
+    /// * `referral_registry` - Registry of the user's direct referrer
+    /// * `referral_link` - Referral link of the user
+    /// * `upline_registries` - Writable registry accounts for the levels above the direct referrer
+    /// * `tax` - Tax collected on the purchase or sale
+    /// * `current_time` - The current Unix timestamp
+    ///
+    /// # Returns
+    /// * `Ok(Vec<(Pubkey, u8, u64)>)` - Referrer, level and amount credited
+    ///
+    /// # Errors
+    /// * `ErrorCode::CannotPerformAction` - If the user has a referrer and an account is missing
+    /// * `ErrorCode::InvalidAccount` - If the registry does not belong to the user's referrer
+    pub fn credit_referrals<'info>(
+        &mut self,
+        user: &User,
+        referral_registry: Option<&mut ReferralRegistry>,
+        referral_link: Option<&ReferralLink>,
+        upline_registries: &'info [AccountInfo<'info>],
+        tax: u64,
+        current_time: i64,
+    ) -> TYieldResult<Vec<(Pubkey, u8, u64)>> {
+        if !user.has_referrer() {
+            return Ok(Vec::new());
+        }
+        let referral_registry = referral_registry.ok_or(ErrorCode::CannotPerformAction)?;
+        if referral_registry.referrer != user.referrer {
+            return Err(ErrorCode::InvalidAccount);
+        }
+        let referral_link = referral_link.ok_or(ErrorCode::CannotPerformAction)?;
+
+        let mut credited = Vec::new();
+        let fee = self.calculate_referral_fee(tax, 0)?;
+        if fee > 0 {
+            referral_registry.credit_referral_fee(fee, current_time)?;
+            credited.push((user.referrer, 1, fee));
+        }
+        credited.extend(self.credit_upline_referrals(
+            referral_link,
+            upline_registries,
+            tax,
+            current_time,
+        )?);
+
+        for (_, _, fee) in credited.iter() {
+            self.protocol_referral_owed = self.protocol_referral_owed.safe_add(*fee)?;
+        }
+        Ok(credited)
+    }
+
+    /// Credits the upper levels of a referral chain (levels 2..=MAX_REFERRAL_DEPTH).
+    ///
+    /// `registries` holds the `ReferralRegistry` accounts of the link's ancestors
+    /// after the direct referrer, in order. Every level with a non-zero share must be
+    /// provided, so upper levels cannot be skipped.
+    ///
+    /// # Arguments
+    /// * `link` - Referral link of the paying user
+    /// * `registries` - Writable registry accounts for `link.ancestors[1..]`
+    /// * `tax` - Tax collected on a buy or sell
+    /// * `current_time` - The current Unix timestamp
+    ///
+    /// # Returns
+    /// * `Ok(Vec<(Pubkey, u8, u64)>)` - Referrer, level and amount credited
+    pub fn credit_upline_referrals<'info>(
+        &self,
+        link: &ReferralLink,
+        registries: &'info [AccountInfo<'info>],
+        tax: u64,
+        current_time: i64,
+    ) -> TYieldResult<Vec<(Pubkey, u8, u64)>> {
+        let mut credited = Vec::new();
+        for (level, ancestor) in link.get_ancestors().iter().enumerate().skip(1) {
+            let fee = self.calculate_referral_fee(tax, level)?;
+            if fee == 0 {
+                continue;
+            }
+
+            let registry_info = registries
+                .get(level - 1)
+                .ok_or(ErrorCode::CannotPerformAction)?;
+            if registry_info.key() != Self::get_referral_registry_pda(ancestor).0
+                || !registry_info.is_writable
+            {
+                return Err(ErrorCode::InvalidAccount);
+            }
+
+            let mut registry = Account::<ReferralRegistry>::try_from(registry_info)
+                .map_err(|_| ErrorCode::AccountFromError)?;
+            registry.credit_referral_fee(fee, current_time)?;
+            registry
+                .exit(&crate::ID)
+                .map_err(|_| ErrorCode::AccountFromError)?;
+
+            credited.push((*ancestor, (level + 1) as u8, fee));
+        }
+        Ok(credited)
+    }
+
+    /// Records a protocol fee withdrawal to the treasury.
+    ///
+    /// # Arguments
+    /// * `amount` - Amount being withdrawn
+    /// * `vault_balance` - Balance of the protocol token account before the withdrawal
+    pub fn record_fee_withdrawal(&mut self, amount: u64, vault_balance: u64) -> TYieldResult<()> {
+        if amount == 0 || amount > self.get_withdrawable_fees(vault_balance)? {
+            msg!("Fee withdrawal of {} exceeds withdrawable fees", amount);
+            return Err(ErrorCode::InsufficientFunds);
+        }
+        self.protocol_fees_withdrawn = self.protocol_fees_withdrawn.safe_add(amount)?;
+        Ok(())
+    }
+
+    /// Moves withdrawable protocol fees into the keeper reward pool.
+    ///
+    /// # Arguments
+    /// * `amount` - Amount of fees to set aside
+    /// * `vault_balance` - Balance of the protocol token account
+    pub fn fund_keeper_rewards(&mut self, amount: u64, vault_balance: u64) -> TYieldResult<()> {
+        if amount == 0 || amount > self.get_withdrawable_fees(vault_balance)? {
+            msg!("Keeper funding of {} exceeds withdrawable fees", amount);
+            return Err(ErrorCode::InsufficientFunds);
+        }
+        self.keeper_rewards_funded = self.keeper_rewards_funded.safe_add(amount)?;
+        self.keeper_reward_pool = self.keeper_reward_pool.safe_add(amount)?;
+        Ok(())
+    }
+
+    /// Pays the keeper reward for `actions` state-changing cranks out of the pool.
+    ///
+    /// The reward is `keeper_reward` per action, limited by the pool and by what is left of
+    /// `keeper_epoch_cap` in the current epoch. The paid amount becomes yield owed to the keeper.
+    ///
+    /// # Returns
+    /// * `Ok(u64)` - The reward to credit to the keeper (0 if none)
+    pub fn pay_keeper_reward(&mut self, actions: u64, current_time: i64) -> TYieldResult<u64> {
+        self.pay_keeper_amount(self.keeper_reward.saturating_mul(actions), current_time)
+    }
+
+    /// Pays the keeper reward for a yield accrual crank out of the pool.
+    ///
+    /// The reward is `keeper_reward` scaled down to at most `KEEPER_ACCRUAL_REWARD_SHARE` of
+    /// the yield the crank accrued, so cranking agents with little yield earns little.
+    ///
+    /// # Returns
+    /// * `Ok(u64)` - The reward to credit to the keeper (0 if none)
+    pub fn pay_accrual_keeper_reward(
+        &mut self,
+        accrued: u64,
+        current_time: i64,
+    ) -> TYieldResult<u64> {
+        let max_reward = accrued
+            .safe_mul(KEEPER_ACCRUAL_REWARD_SHARE)?
+            .safe_div(PERCENTAGE_PRECISION_U64)?;
+        self.pay_keeper_amount(self.keeper_reward.min(max_reward), current_time)
+    }
+
+    /// Pays up to `amount`, limited by the pool and the epoch cap.
+    fn pay_keeper_amount(&mut self, amount: u64, current_time: i64) -> TYieldResult<u64> {
+        if current_time >= self.keeper_epoch_start.safe_add(KEEPER_EPOCH_DURATION)? {
+            self.keeper_epoch_start = current_time;
+            self.keeper_epoch_paid = 0;
+        }
+
+        let reward = amount
+            .min(self.keeper_reward_pool)
+            .min(self.keeper_epoch_cap.saturating_sub(self.keeper_epoch_paid));
+        if reward == 0 {
+            return Ok(0);
+        }
+
+        self.keeper_reward_pool = self.keeper_reward_pool.safe_sub(reward)?;
+        self.keeper_epoch_paid = self.keeper_epoch_paid.safe_add(reward)?;
+        self.protocol_yield_owed = self.protocol_yield_owed.safe_add(reward)?;
+        Ok(reward)
+    }
+
+    /// Credits the keeper reward for `actions` state-changing cranks to the keeper's user account.
+    ///
+    /// Banned users are not rewarded.
+    ///
+    /// # Returns
+    /// * `Ok(u64)` - The reward credited (0 if none)
+    pub fn reward_keeper(
+        &mut self,
+        keeper_user: &mut User,
+        actions: u64,
+        current_time: i64,
+    ) -> TYieldResult<u64> {
+        if actions == 0 || keeper_user.has_status(UserStatus::Banned) {
+            return Ok(0);
+        }
+        let reward = self.pay_keeper_reward(actions, current_time)?;
+        if reward > 0 {
+            keeper_user.add_unclaimed_yield(reward)?;
+        }
+        Ok(reward)
+    }
+
+    /// Credits the keeper reward for a yield accrual crank that accrued `accrued` to the
+    /// keeper's user account.
+    ///
+    /// Banned users are not rewarded.
+    ///
+    /// # Returns
+    /// * `Ok(u64)` - The reward credited (0 if none)
+    pub fn reward_accrual_keeper(
+        &mut self,
+        keeper_user: &mut User,
+        accrued: u64,
+        current_time: i64,
+    ) -> TYieldResult<u64> {
+        if accrued == 0 || keeper_user.has_status(UserStatus::Banned) {
+            return Ok(0);
+        }
+        let reward = self.pay_accrual_keeper_reward(accrued, current_time)?;
+        if reward > 0 {
+            keeper_user.add_unclaimed_yield(reward)?;
+        }
+        Ok(reward)
+    }
+
     pub fn check_rate_limit(&self, current_time: i64) -> TYieldResult<()> {
         // Check minimum interval between updates
         let time_since_last = current_time.safe_sub(self.rate_limiter.last_update_time)?;
@@ -657,6 +1080,85 @@ impl TYield {
             .map_err(|_| ProgramError::InvalidRealloc.into())
     }
 
+    /// Grows an account written with an older layout of `T` and loads it.
+    ///
+    /// Fields appended to `T` since the account was created read as zero. The funding account
+    /// tops up the rent-exempt balance for the new length.
+    ///
+    /// # Arguments
+    /// * `funding_account` - The account to fund the reallocation.
+    /// * `target_account` - The legacy account to migrate.
+    /// * `system_program` - The system program.
+    /// * `new_len` - The size of the current layout.
+    ///
+    /// # Errors
+    /// * `ErrorCode::InvalidAccount` - If the account is not a `T` owned by the program
+    /// * `ErrorCode::InvalidState` - If the account already has the current layout
+    pub fn load_legacy_account<'info, T: AccountDeserialize + Discriminator>(
+        funding_account: AccountInfo<'info>,
+        target_account: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        new_len: usize,
+    ) -> TYieldResult<T> {
+        if *target_account.owner != crate::ID {
+            return Err(ErrorCode::InvalidAccount);
+        }
+        {
+            let data = target_account
+                .try_borrow_data()
+                .map_err(|_| ErrorCode::AccountFromError)?;
+            if !data.starts_with(T::DISCRIMINATOR) {
+                return Err(ErrorCode::InvalidAccount);
+            }
+            if data.len() >= new_len {
+                return Err(ErrorCode::InvalidState);
+            }
+        }
+
+        TYield::realloc(
+            funding_account,
+            target_account.clone(),
+            system_program,
+            new_len,
+            true,
+        )
+        .map_err(|_| ErrorCode::AccountFromError)?;
+
+        let data = target_account
+            .try_borrow_data()
+            .map_err(|_| ErrorCode::AccountFromError)?;
+        T::try_deserialize(&mut &data[..]).map_err(|_| ErrorCode::AccountFromError)
+    }
+
+    /// Writes an account loaded with `load_legacy_account` back in the current layout.
+    pub fn store_migrated_account<T: AccountSerialize>(
+        account: &T,
+        target_account: &AccountInfo,
+    ) -> TYieldResult<()> {
+        let mut data = target_account
+            .try_borrow_mut_data()
+            .map_err(|_| ErrorCode::AccountFromError)?;
+        account
+            .try_serialize(&mut &mut data[..])
+            .map_err(|_| ErrorCode::AccountFromError)
+    }
+
+    /// Populates the fields appended to the layout for a protocol state created before them.
+    ///
+    /// Referral tiers above the direct referrer, the performance fee and keeper rewards stay
+    /// disabled until configured; the keeper epoch starts at the migration.
+    ///
+    /// # Errors
+    /// * `ErrorCode::InvalidAccount` - If `treasury` is the default pubkey
+    pub fn migrate(&mut self, treasury: Pubkey, current_time: i64) -> TYieldResult<()> {
+        if treasury == Pubkey::default() {
+            return Err(ErrorCode::InvalidAccount);
+        }
+        self.treasury = treasury;
+        self.keeper_epoch_start = current_time;
+        Ok(())
+    }
+
     /// Mints a new master agent NFT.
     ///
     /// # Arguments
@@ -814,6 +1316,56 @@ impl TYield {
 
         Ok(())
     }
+
+    /// Burns an agent NFT held by its owner.
+    ///
+    /// Burns the token and closes the token, metadata and master edition accounts,
+    /// returning their rent to the owner.
+    ///
+    /// # Arguments
+    /// * `params` - The parameters for the burn operation.
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok on success, Err on failure.
+    pub fn burn_agent(&self, params: BurnAgentParams) -> Result<()> {
+        BurnV1CpiBuilder::new(&params.metadata_program)
+            .authority(&params.owner)
+            .metadata(&params.metadata)
+            .edition(Some(&params.master_edition))
+            .mint(&params.mint)
+            .token(&params.token_account)
+            .system_program(&params.system_program)
+            .sysvar_instructions(&params.sysvar_instructions)
+            .spl_token_program(&params.token_program)
+            .amount(1)
+            .invoke()?;
+
+        Ok(())
+    }
+}
+
+/// Parameters for burning an agent NFT.
+///
+/// Contains all accounts and programs required to burn an agent NFT through Metaplex.
+pub struct BurnAgentParams<'info> {
+    /// The owner of the NFT (must sign)
+    pub owner: AccountInfo<'info>,
+    /// The owner's NFT token account
+    pub token_account: AccountInfo<'info>,
+    /// The NFT mint account
+    pub mint: AccountInfo<'info>,
+    /// The NFT metadata account
+    pub metadata: AccountInfo<'info>,
+    /// The NFT master edition account
+    pub master_edition: AccountInfo<'info>,
+    /// The metadata program
+    pub metadata_program: AccountInfo<'info>,
+    /// The system program
+    pub system_program: AccountInfo<'info>,
+    /// The token program
+    pub token_program: AccountInfo<'info>,
+    /// The sysvar instructions account
+    pub sysvar_instructions: AccountInfo<'info>,
 }
 
 /// Parameters for transferring an agent NFT.
@@ -848,7 +1400,7 @@ pub struct TransferAgentParams<'info> {
 
 /// Implements the Size trait for TYield, specifying the on-chain account size.
 impl Size for TYield {
-    const SIZE: usize = 328;
+    const SIZE: usize = 456;
 }
 
 #[cfg(test)]
@@ -862,6 +1414,33 @@ mod tests {
         println!("TYield on-chain size: {} bytes", TYield::SIZE);
     }
 
+    #[test]
+    fn test_t_yield_legacy_layout() {
+        let t_yield = TYield {
+            y_mint: Pubkey::new_unique(),
+            buy_tax: 500,
+            protocol_total_fees: 1_000,
+            inception_time: 1_640_995_200,
+            t_yield_bump: 254,
+            paused: true,
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        t_yield.try_serialize(&mut data).unwrap();
+
+        // Accounts created before the appended fields only hold the first 310 bytes
+        let mut legacy = data[..310].to_vec();
+        legacy.resize(TYield::SIZE, 0);
+        let mut migrated = TYield::try_deserialize(&mut &legacy[..]).unwrap();
+        assert_eq!(migrated, t_yield);
+
+        let treasury = Pubkey::new_unique();
+        migrated.migrate(treasury, 1_700_000_000).unwrap();
+        assert_eq!(migrated.treasury, treasury);
+        assert_eq!(migrated.keeper_epoch_start, 1_700_000_000);
+        assert!(migrated.migrate(Pubkey::default(), 1_700_000_000).is_err());
+    }
+
     #[test]
     fn test_t_yield_memory_layout() {
         let t_yield = TYield::default();
@@ -896,6 +1475,158 @@ mod tests {
         assert!(t_yield.validate_protocol_balance(1_000_001).is_err());
     }
 
+    #[test]
+    fn test_get_withdrawable_fees() {
+        let mut t_yield = TYield {
+            protocol_total_fees: 1_000,
+            protocol_current_holding: 2_000,
+            protocol_yield_owed: 500,
+            ..Default::default()
+        };
+        // Vault only covers reserves plus part of the fees
+        assert_eq!(t_yield.get_withdrawable_fees(3_000).unwrap(), 500);
+        // Vault covers everything, capped at accrued fees
+        assert_eq!(t_yield.get_withdrawable_fees(10_000).unwrap(), 1_000);
+        // Vault below reserves
+        assert_eq!(t_yield.get_withdrawable_fees(2_000).unwrap(), 0);
+        // Unclaimed referral earnings are reserved too
+        t_yield.protocol_referral_owed = 500;
+        assert_eq!(t_yield.get_withdrawable_fees(3_500).unwrap(), 500);
+        t_yield.protocol_referral_owed = 0;
+        // Already withdrawn fees are not withdrawable again
+        t_yield.protocol_fees_withdrawn = 800;
+        assert_eq!(t_yield.get_withdrawable_fees(10_000).unwrap(), 200);
+    }
+
+    #[test]
+    fn test_calculate_referral_fee() {
+        let mut t_yield = TYield {
+            ref_earn_percentage: 2_000, // 20%
+            ..Default::default()
+        };
+        assert_eq!(t_yield.calculate_referral_fee(1_000, 0).unwrap(), 200);
+        assert_eq!(t_yield.calculate_referral_fee(0, 0).unwrap(), 0);
+        t_yield.ref_earn_percentage = 0;
+        assert_eq!(t_yield.calculate_referral_fee(1_000, 0).unwrap(), 0);
+        t_yield.ref_earn_percentage = 10_001;
+        assert!(t_yield.calculate_referral_fee(1_000, 0).is_err());
+    }
+
+    #[test]
+    fn test_referral_tier_percentages() {
+        let t_yield = TYield {
+            ref_earn_percentage: 2_000,       // 20%
+            ref_tier_percentages: [500, 200], // 5%, 2%
+            ..Default::default()
+        };
+        assert_eq!(t_yield.calculate_referral_fee(1_000, 0).unwrap(), 200);
+        assert_eq!(t_yield.calculate_referral_fee(1_000, 1).unwrap(), 50);
+        assert_eq!(t_yield.calculate_referral_fee(1_000, 2).unwrap(), 20);
+        // Beyond the maximum depth nothing is paid
+        assert_eq!(
+            t_yield
+                .calculate_referral_fee(1_000, MAX_REFERRAL_DEPTH)
+                .unwrap(),
+            0
+        );
+
+        assert!(TYield::validate_referral_percentages(2_000, &[500, 200]).is_ok());
+        assert!(TYield::validate_referral_percentages(9_000, &[500, 500]).is_ok());
+        assert!(TYield::validate_referral_percentages(9_000, &[500, 501]).is_err());
+    }
+
+    #[test]
+    fn test_record_fee_withdrawal() {
+        let mut t_yield = TYield {
+            protocol_total_fees: 1_000,
+            protocol_yield_owed: 500,
+            ..Default::default()
+        };
+        assert!(t_yield.record_fee_withdrawal(0, 10_000).is_err());
+        assert!(t_yield.record_fee_withdrawal(1_200, 10_000).is_err());
+        // Would dip into user-owed yield
+        assert!(t_yield.record_fee_withdrawal(600, 1_000).is_err());
+        t_yield.record_fee_withdrawal(400, 1_000).unwrap();
+        assert_eq!(t_yield.protocol_fees_withdrawn, 400);
+        assert_eq!(t_yield.get_withdrawable_fees(10_000).unwrap(), 600);
+    }
+
+    #[test]
+    fn test_keeper_rewards() {
+        let mut t_yield = TYield {
+            protocol_total_fees: 1_000,
+            protocol_yield_owed: 500,
+            keeper_reward: 10,
+            keeper_epoch_cap: 25,
+            ..Default::default()
+        };
+        // Funding is limited to withdrawable fees
+        assert!(t_yield.fund_keeper_rewards(0, 10_000).is_err());
+        assert!(t_yield.fund_keeper_rewards(600, 1_000).is_err());
+        t_yield.fund_keeper_rewards(100, 1_000).unwrap();
+        assert_eq!(t_yield.keeper_reward_pool, 100);
+        // The pool is no longer withdrawable as fees
+        assert_eq!(t_yield.get_withdrawable_fees(10_000).unwrap(), 900);
+        assert_eq!(t_yield.get_withdrawable_fees(1_000).unwrap(), 400);
+
+        // Capped per epoch
+        assert_eq!(t_yield.pay_keeper_reward(2, 0).unwrap(), 20);
+        assert_eq!(t_yield.pay_keeper_reward(1, 10).unwrap(), 5);
+        assert_eq!(t_yield.pay_keeper_reward(1, 20).unwrap(), 0);
+        assert_eq!(t_yield.protocol_yield_owed, 525);
+        assert_eq!(t_yield.keeper_reward_pool, 75);
+
+        // A new epoch resets the cap, the pool still limits the reward
+        t_yield.keeper_epoch_cap = 1_000;
+        assert_eq!(
+            t_yield
+                .pay_keeper_reward(10, KEEPER_EPOCH_DURATION)
+                .unwrap(),
+            75
+        );
+        assert_eq!(t_yield.keeper_reward_pool, 0);
+        assert_eq!(
+            t_yield
+                .pay_keeper_reward(1, KEEPER_EPOCH_DURATION + 1)
+                .unwrap(),
+            0
+        );
+
+        // Accrual rewards are scaled down by the accrued yield
+        t_yield.keeper_reward_pool = 100;
+        assert_eq!(
+            t_yield
+                .pay_accrual_keeper_reward(99, KEEPER_EPOCH_DURATION + 2)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            t_yield
+                .pay_accrual_keeper_reward(500, KEEPER_EPOCH_DURATION + 2)
+                .unwrap(),
+            5
+        );
+        assert_eq!(
+            t_yield
+                .pay_accrual_keeper_reward(1_000_000, KEEPER_EPOCH_DURATION + 2)
+                .unwrap(),
+            10
+        );
+        assert_eq!(t_yield.keeper_reward_pool, 85);
+
+        // Configured rewards are bounded
+        assert!(TYield::validate_keeper_parameters(10, 25).is_ok());
+        assert!(
+            TYield::validate_keeper_parameters(MAX_KEEPER_REWARD, MAX_KEEPER_EPOCH_CAP).is_ok()
+        );
+        assert!(
+            TYield::validate_keeper_parameters(MAX_KEEPER_REWARD + 1, MAX_KEEPER_EPOCH_CAP)
+                .is_err()
+        );
+        assert!(TYield::validate_keeper_parameters(10, MAX_KEEPER_EPOCH_CAP + 1).is_err());
+        assert!(TYield::validate_keeper_parameters(30, 25).is_err());
+    }
+
     #[test]
     fn test_rate_limiter() {
         let mut t_yield = TYield::default();
@@ -78,10 +78,12 @@
 
 use anchor_lang::prelude::*;
 
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, TwapUpdate};
+
 use crate::error::{ErrorCode, TYieldResult};
 use crate::math::safe_math::SafeMath;
 use crate::math::PERCENTAGE_PRECISION_U64;
-use crate::state::{OraclePrice, Size};
+use crate::state::{MasterAgent, OraclePrice, Size};
 
 /// Represents a trade in the Tubor Yield protocol.
 ///
@@ -458,9 +460,172 @@ pub struct TradeEvent {
     pub trade_type: TradeType,
     pub result: TradeResult,
     pub pnl: i64,
+    /// Performance fee taken by the protocol from a realized profit
+    pub protocol_fee: u64,
+    /// Realized profit distributed to agent holders as yield
+    pub holder_yield: u64,
+    /// Signed change applied to the master agent NAV
+    pub nav_delta: i64,
     pub created_at: i64,
 }
 
+/// Emitted when a trade is moved from its legacy address to its master agent namespaced address
+#[event]
+pub struct TradeMigratedEvent {
+    pub legacy_trade: Pubkey,
+    pub trade: Pubkey,
+    pub master_agent: Pubkey,
+    pub trade_number: u64,
+}
+
+#[event]
+pub struct TradeModifiedEvent {
+    pub trade: Pubkey,
+    pub authority: Pubkey,
+    pub old_take_profit: u64,
+    pub new_take_profit: u64,
+    pub old_stop_loss: u64,
+    pub new_stop_loss: u64,
+    /// Oracle price the new levels were validated against
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+/// Summary returned by `crank_trades`.
+#[derive(Debug, Clone, Copy, Default, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
+pub struct TradeCrankSummary {
+    /// Trades passed to the crank
+    pub checked: u8,
+    /// Trades closed at take profit
+    pub take_profit: u8,
+    /// Trades closed at stop loss
+    pub stop_loss: u8,
+    /// Trades left untouched (inactive, no price for their feed, or no TP/SL hit)
+    pub skipped: u8,
+    /// Net realized PnL of the settled trades
+    pub pnl: i64,
+    /// Performance fees taken by the protocol from the settled trades
+    pub protocol_fee: u64,
+    /// Reward credited to the keeper for the settled trades
+    pub keeper_reward: u64,
+}
+
+/// Accounts of a `crank_trades` batch, read from `remaining_accounts`.
+///
+/// The first `oracle_count` accounts are price updates, one per feed. They are followed by
+/// `twap_count` TWAP updates, each for the feed of one of the price updates, and then by
+/// `trade_count` pairs of `[trade, master_agent]`. A master agent shared by several trades is
+/// loaded once, so every settlement applies to the same copy.
+pub struct TradeCrankAccounts<'info> {
+    pub oracles: Vec<Account<'info, PriceUpdateV2>>,
+    /// Optional TWAP updates, matched to `oracles` by feed id
+    pub twaps: Vec<Account<'info, TwapUpdate>>,
+    /// Each trade with the index of its master agent in `master_agents`
+    pub trades: Vec<(Account<'info, Trade>, usize)>,
+    pub master_agents: Vec<Account<'info, MasterAgent>>,
+}
+
+impl<'info> TradeCrankAccounts<'info> {
+    /// Number of accounts per trade.
+    pub const LEN: usize = 2;
+
+    /// Loads and validates the crank accounts.
+    ///
+    /// # Arguments
+    /// * `accounts` - The instruction's remaining accounts
+    /// * `oracle_count` - Number of price update accounts
+    /// * `twap_count` - Number of TWAP update accounts
+    /// * `trade_count` - Number of trades in the batch
+    ///
+    /// # Errors
+    /// * `ErrorCode::InvalidAccount` - If an account is missing, repeated, read-only, does not match its trade
+    ///   or a TWAP update has no price update for its feed
+    /// * `ErrorCode::AccountFromError` - If an account cannot be deserialized
+    pub fn load_batch(
+        accounts: &'info [AccountInfo<'info>],
+        oracle_count: usize,
+        twap_count: usize,
+        trade_count: usize,
+    ) -> TYieldResult<Self> {
+        let price_accounts = oracle_count.saturating_add(twap_count);
+        let trade_accounts = trade_count.saturating_mul(Self::LEN);
+        if oracle_count == 0
+            || trade_count == 0
+            || accounts.len() < price_accounts.saturating_add(trade_accounts)
+        {
+            return Err(ErrorCode::InvalidAccount);
+        }
+
+        let mut oracles: Vec<Account<'info, PriceUpdateV2>> = Vec::with_capacity(oracle_count);
+        for info in accounts.iter().take(oracle_count) {
+            let oracle = Account::<PriceUpdateV2>::try_from(info)
+                .map_err(|_| ErrorCode::AccountFromError)?;
+            if oracles
+                .iter()
+                .any(|other| other.price_message.feed_id == oracle.price_message.feed_id)
+            {
+                return Err(ErrorCode::InvalidAccount);
+            }
+            oracles.push(oracle);
+        }
+
+        let mut twaps: Vec<Account<'info, TwapUpdate>> = Vec::with_capacity(twap_count);
+        for info in accounts[oracle_count..price_accounts].iter() {
+            let twap =
+                Account::<TwapUpdate>::try_from(info).map_err(|_| ErrorCode::AccountFromError)?;
+            if !oracles
+                .iter()
+                .any(|oracle| oracle.price_message.feed_id == twap.twap.feed_id)
+                || twaps
+                    .iter()
+                    .any(|other| other.twap.feed_id == twap.twap.feed_id)
+            {
+                return Err(ErrorCode::InvalidAccount);
+            }
+            twaps.push(twap);
+        }
+
+        let mut trades: Vec<(Account<'info, Trade>, usize)> = Vec::with_capacity(trade_count);
+        let mut master_agents: Vec<Account<'info, MasterAgent>> = Vec::new();
+        for group in accounts[price_accounts..]
+            .chunks_exact(Self::LEN)
+            .take(trade_count)
+        {
+            let trade =
+                Account::<Trade>::try_from(&group[0]).map_err(|_| ErrorCode::AccountFromError)?;
+            if !group[0].is_writable
+                || !group[1].is_writable
+                || !trade.belongs_to_master_agent(group[1].key)
+                || trades.iter().any(|(other, _)| other.key() == trade.key())
+            {
+                return Err(ErrorCode::InvalidAccount);
+            }
+
+            let index = match master_agents
+                .iter()
+                .position(|master_agent| master_agent.key() == *group[1].key)
+            {
+                Some(index) => index,
+                None => {
+                    master_agents.push(
+                        Account::<MasterAgent>::try_from(&group[1])
+                            .map_err(|_| ErrorCode::AccountFromError)?,
+                    );
+                    master_agents.len() - 1
+                }
+            };
+            trades.push((trade, index));
+        }
+
+        Ok(Self {
+            oracles,
+            twaps,
+            trades,
+            master_agents,
+        })
+    }
+}
+
 /// Parameters for initializing a Trade
 ///
 #[derive(Clone, Copy)]
@@ -761,6 +926,11 @@ impl Trade {
         self.get_trade_type() == TradeType::Sell
     }
 
+    /// Checks if the trade was opened by the given master agent
+    pub fn belongs_to_master_agent(&self, master_agent: &Pubkey) -> bool {
+        self.master_agent == *master_agent
+    }
+
     /// Validates the trade parameters.
     ///
     /// Performs comprehensive validation of all trade parameters to ensure they meet
@@ -1053,6 +1223,30 @@ impl Trade {
         self._padding = [0; 2];
     }
 
+    /// Copies a trade stored at a legacy (un-namespaced) address into this account.
+    ///
+    /// Every field is carried over unchanged except the bump, which belongs to the new PDA.
+    pub fn migrate_from(&mut self, legacy: &Trade, bump: u8) {
+        self.master_agent = legacy.master_agent;
+        self.feed_id = legacy.feed_id;
+        self.pair = legacy.pair;
+        self.size = legacy.size;
+        self.entry_price = legacy.entry_price;
+        self.take_profit = legacy.take_profit;
+        self.stop_loss = legacy.stop_loss;
+        self.created_at = legacy.created_at;
+        self.updated_at = legacy.updated_at;
+        self.status = legacy.status;
+        self.trade_type = legacy.trade_type;
+        self.result = legacy.result;
+        self.bump = bump;
+        self.authority = legacy.authority;
+        self.oracle_consensus_count = legacy.oracle_consensus_count;
+        self.last_price_update = legacy.last_price_update;
+        self.circuit_breaker_triggered = legacy.circuit_breaker_triggered;
+        self._padding = [0; 2];
+    }
+
     /// Updates mutable fields of the trade and sets updated_at
     pub fn update_trade(
         &mut self,
@@ -1080,6 +1274,32 @@ impl Trade {
         self.updated_at = updated_at;
     }
 
+    /// Validates new take profit and stop loss levels against the current market price.
+    ///
+    /// The levels are checked as if the trade were opened at `current_price`: take profit and stop
+    /// loss must sit on the correct sides of the price (so neither is already hit), keep the minimum
+    /// distance from it and offer the minimum risk-reward ratio from there.
+    pub fn validate_modified_levels(
+        &self,
+        take_profit: u64,
+        stop_loss: u64,
+        current_price: u64,
+        config: &PriceValidationConfig,
+    ) -> TYieldResult<()> {
+        let candidate = Trade {
+            size: self.size,
+            entry_price: current_price,
+            take_profit,
+            stop_loss,
+            trade_type: self.trade_type,
+            status: self.status,
+            ..Default::default()
+        };
+        candidate.validate()?;
+        candidate.validate_risk_management_levels(config.min_distance_bps)?;
+        candidate.validate_risk_reward_ratio(config.min_risk_reward_bps)
+    }
+
     /// Enhanced price validation with slippage protection
     pub fn validate_price_with_slippage(
         &self,
@@ -2355,6 +2575,9 @@ mod tests {
             trade_type: TradeType::Buy,
             result: TradeResult::Success,
             pnl: 1000,
+            protocol_fee: 100,
+            holder_yield: 900,
+            nav_delta: 0,
             created_at: 1234567890,
         };
 
@@ -2362,6 +2585,9 @@ mod tests {
         assert_eq!(trade_event.trade_type, TradeType::Buy);
         assert_eq!(trade_event.result, TradeResult::Success);
         assert_eq!(trade_event.pnl, 1000);
+        assert_eq!(trade_event.protocol_fee, 100);
+        assert_eq!(trade_event.holder_yield, 900);
+        assert_eq!(trade_event.nav_delta, 0);
         assert_eq!(trade_event.created_at, 1234567890);
     }
 
@@ -2499,6 +2725,56 @@ mod tests {
         assert_eq!(trade.updated_at, 2000);
     }
 
+    #[test]
+    fn test_validate_modified_levels() {
+        let config = PriceValidationConfig::default();
+        let trade = create_valid_buy_trade();
+
+        // Trailing the stop above the entry is allowed while the price has moved up
+        assert!(trade
+            .validate_modified_levels(1400, 1050, 1100, &config)
+            .is_ok());
+        // Stop loss already hit at the current price
+        assert_eq!(
+            trade
+                .validate_modified_levels(1400, 1150, 1100, &config)
+                .unwrap_err(),
+            ErrorCode::InvalidStopLossBuy
+        );
+        // Take profit already hit at the current price
+        assert_eq!(
+            trade
+                .validate_modified_levels(1050, 900, 1100, &config)
+                .unwrap_err(),
+            ErrorCode::InvalidTakeProfitBuy
+        );
+        // Take profit too close to the current price
+        assert_eq!(
+            trade
+                .validate_modified_levels(1105, 1000, 1100, &config)
+                .unwrap_err(),
+            ErrorCode::TakeProfitTooClose
+        );
+        // Not enough reward for the risk taken from the current price
+        assert_eq!(
+            trade
+                .validate_modified_levels(1112, 100, 1100, &config)
+                .unwrap_err(),
+            ErrorCode::InsufficientRiskRewardRatio
+        );
+
+        let trade = create_valid_sell_trade();
+        assert!(trade
+            .validate_modified_levels(700, 950, 900, &config)
+            .is_ok());
+        assert_eq!(
+            trade
+                .validate_modified_levels(700, 850, 900, &config)
+                .unwrap_err(),
+            ErrorCode::InvalidStopLossSell
+        );
+    }
+
     #[test]
     fn test_validate_price_with_slippage() {
         let trade = create_valid_buy_trade();
@@ -2814,4 +3090,18 @@ mod tests {
             "Circuit Breaker Active, Low Oracle Consensus"
         );
     }
+
+    #[test]
+    fn test_migrate_from_legacy_trade() {
+        let legacy = create_valid_buy_trade();
+        let mut trade = Trade::default();
+        trade.migrate_from(&legacy, 254);
+
+        assert_eq!(trade.bump, 254);
+        assert!(trade.belongs_to_master_agent(&legacy.master_agent));
+        assert!(!trade.belongs_to_master_agent(&Pubkey::new_unique()));
+
+        trade.bump = legacy.bump;
+        assert_eq!(trade, legacy);
+    }
 }
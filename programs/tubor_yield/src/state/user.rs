@@ -9,17 +9,29 @@
 //! - [`History`]: Tracks lifetime statistics for a user (agents purchased, yield claimed, etc).
 //! - [`ReferralRegistry`]: Tracks referral earnings and referred users for a referrer.
 //! - [`ReferralLink`]: Represents a referral relationship between two users.
+//! - [`ReferralCode`]: Human-readable code pointing at a referrer's [`ReferralRegistry`].
 //! - [`UserStatus`]: Bitflags for user status (active, banned, whitelisted).
 //!
 //! # Events
 //! - [`RegisterUserEvent`]: Emitted when a user is registered.
 //! - [`UpdateUserStatusEvent`]: Emitted when a user's status is updated.
+//! - [`ReferralEarningEvent`]: Emitted when a referrer is credited a share of a fee.
+//! - [`ReferralCodeEvent`]: Emitted when a referral code is created or revoked.
 use anchor_lang::prelude::*;
 
 use crate::error::{ErrorCode, TYieldResult};
 use crate::math::{SafeMath, QUOTE_PRECISION_U64};
 use crate::state::Size;
 
+/// Maximum number of referral levels rewarded on a purchase or sale.
+pub const MAX_REFERRAL_DEPTH: usize = 3;
+
+/// Minimum length of a referral code.
+pub const MIN_REFERRAL_CODE_LEN: usize = 4;
+
+/// Maximum length of a referral code.
+pub const MAX_REFERRAL_CODE_LEN: usize = 16;
+
 /// The primary on-chain user account state.
 ///
 /// Stores authority, delegate, referrer, yield, agent statistics, status flags, and activity timestamps.
@@ -252,6 +264,42 @@ impl User {
         Ok(())
     }
 
+    /// Adds `count` agents bought together to the user's portfolio.
+    ///
+    /// # Arguments
+    /// * `count` - The number of agents to add.
+    /// * `total_value` - The combined value of the agents.
+    pub fn add_agents(&mut self, count: u32, total_value: u64) -> TYieldResult<()> {
+        if count == 0 || total_value == 0 {
+            return Err(ErrorCode::MathError);
+        }
+
+        self.total_agents_owned = self.total_agents_owned.safe_add(count)?;
+        self.total_agents_purchased = self.total_agents_purchased.safe_add(total_value)?;
+        self.history.total_agents_ever_purchased = self
+            .history
+            .total_agents_ever_purchased
+            .safe_add(total_value)?;
+        Ok(())
+    }
+
+    /// Removes `count` agents sold together from the user's portfolio.
+    pub fn remove_agents(&mut self, count: u32) -> TYieldResult<()> {
+        if self.total_agents_owned < count {
+            return Err(ErrorCode::InsufficientFunds);
+        }
+        self.total_agents_owned = self.total_agents_owned.safe_sub(count)?;
+        Ok(())
+    }
+
+    /// Adds an agent received from another user to the portfolio.
+    ///
+    /// Unlike [`User::add_agent`], this does not count towards purchase totals.
+    pub fn receive_agent(&mut self) -> TYieldResult<()> {
+        self.total_agents_owned = self.total_agents_owned.safe_add(1)?;
+        Ok(())
+    }
+
     /// Gets the total number of agents owned by the user.
     pub fn get_agent_count(&self) -> u32 {
         self.total_agents_owned
@@ -891,6 +939,12 @@ impl ReferralRegistry {
         Ok(())
     }
 
+    /// Credit a referral fee (unclaimed) and update the registry timestamp
+    pub fn credit_referral_fee(&mut self, fee: u64, current_time: i64) -> TYieldResult<()> {
+        self.add_unclaimed_referral_earnings(fee)?;
+        self.update_timestamp(current_time)
+    }
+
     /// Claim unclaimed referral earnings (move from unclaimed to claimed)
     pub fn claim_referral_earnings(&mut self, amount: u64) -> TYieldResult<()> {
         if amount == 0 {
@@ -1028,32 +1082,81 @@ pub struct ReferralLink {
     pub referrer: Pubkey,
     pub referred_user: Pubkey,
 
+    /// Referral chain above `referred_user`, nearest first (`ancestors[0] == referrer`)
+    pub ancestors: [Pubkey; MAX_REFERRAL_DEPTH],
+
     // 4 bytes, 4-byte aligned
     pub created_at: i64,
 
     // 1 byte
     pub bump: u8,
 
-    // 7 bytes padding to align to 8 bytes
-    pub _padding: [u8; 7],
+    /// Number of populated entries in `ancestors`
+    pub depth: u8,
+
+    // 6 bytes padding to align to 8 bytes
+    pub _padding: [u8; 6],
 }
 
 impl Size for ReferralLink {
-    const SIZE: usize = 84; // 8 (discriminator) + 76 (struct, including padding) = 84 bytes
+    const SIZE: usize = 184; // 8 (discriminator) + 176 (struct, including padding) = 184 bytes
 }
 
 impl ReferralLink {
     /// Create a new ReferralLink
     pub fn new(referrer: Pubkey, referred_user: Pubkey, created_at: i64, bump: u8) -> Self {
+        let mut ancestors = [Pubkey::default(); MAX_REFERRAL_DEPTH];
+        ancestors[0] = referrer;
         Self {
             referrer,
             referred_user,
+            ancestors,
             created_at,
             bump,
-            _padding: [0; 7],
+            depth: 1,
+            _padding: [0; 6],
         }
     }
 
+    /// Set the referral chain from the referrer's own ancestors.
+    ///
+    /// The chain is truncated to `MAX_REFERRAL_DEPTH` levels. Fails if `referred_user`
+    /// already appears in the chain or the chain repeats a referrer.
+    pub fn set_ancestors(&mut self, referrer_ancestors: &[Pubkey]) -> TYieldResult<()> {
+        let mut ancestors = [Pubkey::default(); MAX_REFERRAL_DEPTH];
+        ancestors[0] = self.referrer;
+        let mut depth = 1;
+        for ancestor in referrer_ancestors.iter().take(MAX_REFERRAL_DEPTH - 1) {
+            if *ancestor == Pubkey::default() {
+                break;
+            }
+            ancestors[depth] = *ancestor;
+            depth += 1;
+        }
+
+        for (i, ancestor) in ancestors[..depth].iter().enumerate() {
+            if *ancestor == self.referred_user || ancestors[..i].contains(ancestor) {
+                msg!("Referral cycle detected at level {}", i + 1);
+                return Err(ErrorCode::ReferralCycleDetected);
+            }
+        }
+
+        self.ancestors = ancestors;
+        self.depth = depth as u8;
+        Ok(())
+    }
+
+    /// Get the referral chain, nearest referrer first.
+    ///
+    /// Links created before multi-tier referrals only hold the direct referrer.
+    pub fn get_ancestors(&self) -> &[Pubkey] {
+        let depth = (self.depth as usize).min(MAX_REFERRAL_DEPTH);
+        if depth == 0 {
+            return std::slice::from_ref(&self.referrer);
+        }
+        &self.ancestors[..depth]
+    }
+
     /// Validate the ReferralLink (basic checks)
     pub fn validate(&self) -> TYieldResult<()> {
         if self.referrer == Pubkey::default() {
@@ -1082,9 +1185,109 @@ impl ReferralLink {
     pub fn reset(&mut self) {
         self.referrer = Pubkey::default();
         self.referred_user = Pubkey::default();
+        self.ancestors = [Pubkey::default(); MAX_REFERRAL_DEPTH];
         self.created_at = 0;
         self.bump = 0;
-        self._padding = [0; 7];
+        self.depth = 0;
+        self._padding = [0; 6];
+    }
+}
+
+/// Human-readable referral code reserved by a referrer.
+///
+/// PDA: ["ref_code", code]. The PDA keeps the code reserved even after revocation.
+#[account]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReferralCode {
+    // 32 bytes each, 8-byte aligned
+    pub referrer: Pubkey,
+    pub referral_registry: Pubkey,
+
+    /// Code bytes, zero padded to `MAX_REFERRAL_CODE_LEN`
+    pub code: [u8; MAX_REFERRAL_CODE_LEN],
+
+    pub created_at: i64,
+
+    // 1 byte each
+    pub bump: u8,
+    pub revoked: bool,
+
+    // 6 bytes padding to align to 8 bytes
+    pub _padding: [u8; 6],
+}
+
+impl Size for ReferralCode {
+    const SIZE: usize = 104; // 8 (discriminator) + 96 (struct, including padding) = 104 bytes
+}
+
+impl ReferralCode {
+    /// Validate a referral code.
+    ///
+    /// Codes are `MIN_REFERRAL_CODE_LEN` to `MAX_REFERRAL_CODE_LEN` characters of
+    /// lowercase ASCII letters, digits or `_`.
+    pub fn validate_code(code: &str) -> TYieldResult<()> {
+        if code.len() < MIN_REFERRAL_CODE_LEN || code.len() > MAX_REFERRAL_CODE_LEN {
+            msg!(
+                "Referral code must be {}-{} characters",
+                MIN_REFERRAL_CODE_LEN,
+                MAX_REFERRAL_CODE_LEN
+            );
+            return Err(ErrorCode::InvalidReferralCode);
+        }
+        if !code
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')
+        {
+            msg!("Referral code may only contain a-z, 0-9 and _");
+            return Err(ErrorCode::InvalidReferralCode);
+        }
+        Ok(())
+    }
+
+    /// Initialize the referral code.
+    pub fn initialize(
+        &mut self,
+        code: &str,
+        referrer: Pubkey,
+        referral_registry: Pubkey,
+        current_time: i64,
+        bump: u8,
+    ) -> TYieldResult<()> {
+        Self::validate_code(code)?;
+        let mut code_bytes = [0u8; MAX_REFERRAL_CODE_LEN];
+        code_bytes[..code.len()].copy_from_slice(code.as_bytes());
+
+        self.referrer = referrer;
+        self.referral_registry = referral_registry;
+        self.code = code_bytes;
+        self.created_at = current_time;
+        self.bump = bump;
+        self.revoked = false;
+        Ok(())
+    }
+
+    /// Get the code as a string
+    pub fn get_code_string(&self) -> String {
+        let len = self
+            .code
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.code.len());
+        String::from_utf8_lossy(&self.code[..len]).to_string()
+    }
+
+    /// Check the code can still be used to register
+    pub fn is_active(&self) -> bool {
+        !self.revoked
+    }
+
+    /// Revoke the code. The code stays reserved and cannot be reused.
+    pub fn revoke(&mut self) -> TYieldResult<()> {
+        if self.revoked {
+            return Err(ErrorCode::ReferralCodeRevoked);
+        }
+        self.revoked = true;
+        Ok(())
     }
 }
 
@@ -1124,6 +1327,40 @@ pub struct RegisterUserEvent {
     pub created_at: i64,
 }
 
+#[event]
+pub struct ReferralEarningEvent {
+    /// Referrer credited with the earnings
+    pub referrer: Pubkey,
+
+    /// User whose purchase or sale generated the fee
+    pub referred_user: Pubkey,
+
+    /// Referral level of the referrer (1 = direct referrer)
+    pub level: u8,
+
+    /// Amount credited to the referrer (unclaimed)
+    pub amount: u64,
+
+    /// Total tax the referral share was taken from
+    pub tax: u64,
+
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralCodeEvent {
+    /// Referrer the code points at
+    pub referrer: Pubkey,
+
+    /// Code bytes, zero padded
+    pub code: [u8; MAX_REFERRAL_CODE_LEN],
+
+    /// Whether the code has been revoked
+    pub revoked: bool,
+
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct UpdateUserStatusEvent {
     /// The owner/authority of the account
@@ -1337,6 +1574,32 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_batch_agent_management() {
+        let mut user = create_test_user();
+
+        user.add_agents(3, 3000).unwrap();
+        assert_eq!(user.get_agent_count(), 3);
+        assert_eq!(user.get_total_agents_purchased(), 3000);
+        assert!(user.add_agents(0, 1000).is_err());
+
+        user.remove_agents(2).unwrap();
+        assert_eq!(user.get_agent_count(), 1);
+        assert!(user.remove_agents(2).is_err());
+        assert_eq!(user.get_agent_count(), 1);
+    }
+
+    #[test]
+    fn test_receive_agent() {
+        let mut user = create_test_user();
+
+        user.receive_agent().unwrap();
+        assert_eq!(user.get_agent_count(), 1);
+        // Transfers are not purchases
+        assert_eq!(user.get_total_agents_purchased(), 0);
+        assert_eq!(user.history.total_agents_ever_purchased, 0);
+    }
+
     // Fee management tests
     #[test]
     fn test_fee_management() {
@@ -1578,6 +1841,25 @@ mod tests {
         assert_eq!(registry.get_average_earnings_per_user().unwrap(), 1000);
     }
 
+    #[test]
+    fn test_referral_registry_credit_fee() {
+        let mut registry = ReferralRegistry {
+            referrer: Pubkey::new_unique(),
+            created_at: 1000,
+            updated_at: 1000,
+            ..Default::default()
+        };
+
+        registry.credit_referral_fee(250, 2000).unwrap();
+        assert_eq!(registry.total_referral_earnings_uc, 250);
+        assert_eq!(registry.total_referral_earnings, 0);
+        assert_eq!(registry.updated_at, 2000);
+
+        // Zero fees and timestamps before creation are rejected
+        assert!(registry.credit_referral_fee(0, 3000).is_err());
+        assert!(registry.credit_referral_fee(100, 500).is_err());
+    }
+
     #[test]
     fn test_referral_registry_validation() {
         let mut registry = ReferralRegistry::default();
@@ -1949,6 +2231,100 @@ mod tests {
         assert_eq!(link.referred_user, Pubkey::default());
         assert_eq!(link.created_at, 0);
         assert_eq!(link.bump, 0);
-        assert_eq!(link._padding, [0; 7]);
+        assert_eq!(link.depth, 0);
+        assert_eq!(link._padding, [0; 6]);
+    }
+
+    #[test]
+    fn test_referral_code_size() {
+        assert_eq!(8 + std::mem::size_of::<ReferralCode>(), ReferralCode::SIZE);
+    }
+
+    #[test]
+    fn test_referral_code_validation() {
+        assert!(ReferralCode::validate_code("alice").is_ok());
+        assert!(ReferralCode::validate_code("bob_2024").is_ok());
+        assert!(ReferralCode::validate_code("abcdefghijklmnop").is_ok());
+        // Too short / too long
+        assert!(ReferralCode::validate_code("abc").is_err());
+        assert!(ReferralCode::validate_code("abcdefghijklmnopq").is_err());
+        // Invalid characters
+        assert!(ReferralCode::validate_code("Alice").is_err());
+        assert!(ReferralCode::validate_code("al ice").is_err());
+        assert!(ReferralCode::validate_code("alice-1").is_err());
+        assert!(ReferralCode::validate_code("alicé").is_err());
+    }
+
+    #[test]
+    fn test_referral_code_lifecycle() {
+        let referrer = Pubkey::new_unique();
+        let registry = Pubkey::new_unique();
+        let mut code = ReferralCode::default();
+        code.initialize("alice", referrer, registry, 1_700_000_000, 254)
+            .unwrap();
+        assert_eq!(code.referrer, referrer);
+        assert_eq!(code.referral_registry, registry);
+        assert_eq!(code.get_code_string(), "alice");
+        assert!(code.is_active());
+
+        code.revoke().unwrap();
+        assert!(!code.is_active());
+        assert_eq!(code.revoke(), Err(ErrorCode::ReferralCodeRevoked));
+
+        let mut invalid = ReferralCode::default();
+        assert!(invalid
+            .initialize("no", referrer, registry, 1_700_000_000, 254)
+            .is_err());
+    }
+
+    #[test]
+    fn test_referral_link_size() {
+        assert_eq!(8 + std::mem::size_of::<ReferralLink>(), ReferralLink::SIZE);
+    }
+
+    #[test]
+    fn test_referral_link_ancestors() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let c = Pubkey::new_unique();
+        let d = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+
+        // Direct referral only
+        let link = ReferralLink::new(a, user, 1_700_000_000, 1);
+        assert_eq!(link.get_ancestors(), &[a]);
+
+        // Chain is extended from the referrer's ancestors and capped at max depth
+        let mut link = ReferralLink::new(a, user, 1_700_000_000, 1);
+        link.set_ancestors(&[b, c, d]).unwrap();
+        assert_eq!(link.depth as usize, MAX_REFERRAL_DEPTH);
+        assert_eq!(link.get_ancestors(), &[a, b, c]);
+
+        // Legacy links without a depth fall back to the direct referrer
+        let mut legacy = ReferralLink::new(a, user, 1_700_000_000, 1);
+        legacy.depth = 0;
+        assert_eq!(legacy.get_ancestors(), &[a]);
+    }
+
+    #[test]
+    fn test_referral_link_cycle_detection() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+
+        // Referred user is already an ancestor
+        let mut link = ReferralLink::new(a, user, 1_700_000_000, 1);
+        assert_eq!(
+            link.set_ancestors(&[b, user]),
+            Err(ErrorCode::ReferralCycleDetected)
+        );
+
+        // Referrer repeated further up the chain
+        let mut link = ReferralLink::new(a, user, 1_700_000_000, 1);
+        assert_eq!(
+            link.set_ancestors(&[b, a]),
+            Err(ErrorCode::ReferralCycleDetected)
+        );
+        assert_eq!(link.get_ancestors(), &[a]);
     }
 }